@@ -1,40 +1,164 @@
+use std::{ops::Range, str::FromStr};
+
+use base64::{DecodeError, Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use ipld_core::cid::Cid;
-use rust_unixfs::file::adder::FileAdder;
+use rust_unixfs::file::{
+    adder::FileAdder,
+    visit::{Cache, FileVisit, IdleFileVisit},
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::Jwe;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Data {
     Base64(String),
-    Encrypted(()),
+    Encrypted(Jwe),
+}
+
+impl Data {
+    /// Computes the data CID.
+    /// Encrypted data is hashed over its ciphertext.
+    pub fn compute_cid(&self) -> Result<Option<String>, DecodeError> {
+        let bytes = match self {
+            Data::Base64(encoded) => BASE64_URL_SAFE_NO_PAD.decode(encoded)?,
+            Data::Encrypted(jwe) => BASE64_URL_SAFE_NO_PAD.decode(&jwe.ciphertext)?,
+        };
+        Ok(compute_data_cid(&bytes))
+    }
 }
 
 /// Returns a stringified CIDv1 of the data root after unixfs encoding.
 pub fn compute_data_cid(data: &[u8]) -> Option<String> {
     let blocks = data_to_unixfs(data);
-    blocks.last().map(|(c, _)| c.to_string())
+    blocks.last().map(|(c, _)| c.clone())
 }
 
-fn data_to_unixfs(data: &[u8]) -> Vec<(Cid, Vec<u8>)> {
-    let mut adder = FileAdder::default();
+fn data_to_unixfs(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut chunker = DataChunker::default();
+    let mut blocks = chunker.push(data);
+    blocks.extend(chunker.finish());
+    blocks
+}
 
-    let mut total = 0;
-    let mut blocks = Vec::default();
+/// Incrementally splits data into unixfs blocks.
+/// Blocks are returned as (CID, block) pairs, with the root block last.
+#[derive(Default)]
+pub struct DataChunker(FileAdder);
 
-    while total < data.len() {
-        let (new_blocks, consumed) = adder.push(&data[total..]);
-        total += consumed;
+impl DataChunker {
+    /// Pushes more data, returning any blocks that were completed.
+    pub fn push(&mut self, mut data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut blocks = Vec::new();
 
-        for b in new_blocks {
-            blocks.push(b);
+        while !data.is_empty() {
+            let (new_blocks, consumed) = self.0.push(data);
+            data = &data[consumed..];
+            blocks.extend(new_blocks.map(|(c, b)| (c.to_string(), b)));
         }
+
+        blocks
     }
 
-    for b in adder.finish() {
-        blocks.push(b);
+    /// Flushes the remaining blocks.
+    /// The last block is the data root.
+    pub fn finish(self) -> Vec<(String, Vec<u8>)> {
+        self.0.finish().map(|(c, b)| (c.to_string(), b)).collect()
     }
+}
 
-    blocks
+/// Checks that a block hashes to the given CID.
+pub fn verify_block(cid: &str, block: &[u8]) -> bool {
+    let Ok(cid) = Cid::from_str(cid) else {
+        return false;
+    };
+
+    let hash = cid.hash();
+    hash.code() == SHA2_256 && hash.digest() == &Sha256::digest(block)[..]
+}
+
+const SHA2_256: u64 = 0x12;
+
+#[derive(Error, Debug)]
+#[error("invalid unixfs block: {0}")]
+pub struct UnixfsError(String);
+
+/// Returns the total file size from a unixfs root block.
+pub fn unixfs_file_size(root_block: &[u8]) -> Result<u64, UnixfsError> {
+    let (_, size, _, _) = IdleFileVisit::default()
+        .start(root_block)
+        .map_err(|e| UnixfsError(e.to_string()))?;
+    Ok(size)
+}
+
+/// Depth-first walk over the blocks of a unixfs file, in content order.
+pub struct UnixfsWalk {
+    root: Option<String>,
+    range: Option<Range<u64>>,
+    visit: Option<FileVisit>,
+    cache: Option<Cache>,
+}
+
+impl UnixfsWalk {
+    pub fn new(root: String) -> Self {
+        Self {
+            root: Some(root),
+            range: None,
+            visit: None,
+            cache: None,
+        }
+    }
+
+    /// Only walks blocks overlapping the byte range, with content trimmed to it.
+    pub fn with_range(root: String, range: Range<u64>) -> Self {
+        Self {
+            range: Some(range),
+            ..Self::new(root)
+        }
+    }
+
+    /// An empty walk.
+    pub fn finished() -> Self {
+        Self {
+            root: None,
+            range: None,
+            visit: None,
+            cache: None,
+        }
+    }
+
+    /// CID of the next block to visit, or `None` once the walk is complete.
+    pub fn next_cid(&self) -> Option<String> {
+        match &self.visit {
+            Some(visit) => Some(visit.pending_links().0.to_string()),
+            None => self.root.clone(),
+        }
+    }
+
+    /// Visits the block for [`Self::next_cid`], returning its file content.
+    pub fn visit(&mut self, block: &[u8]) -> Result<Vec<u8>, UnixfsError> {
+        let (content, visit) = match self.visit.take() {
+            Some(visit) => visit
+                .continue_walk(block, &mut self.cache)
+                .map_err(|e| UnixfsError(e.to_string()))?,
+            None => {
+                self.root = None;
+                let idle = match self.range.clone() {
+                    Some(range) => IdleFileVisit::default().with_target_range(range),
+                    None => IdleFileVisit::default(),
+                };
+                let (content, _, _, visit) =
+                    idle.start(block).map_err(|e| UnixfsError(e.to_string()))?;
+                (content, visit)
+            }
+        };
+
+        self.visit = visit;
+        Ok(content.to_vec())
+    }
 }
 
 #[cfg(test)]
@@ -46,4 +170,48 @@ mod tests {
         let data = "test data".as_bytes();
         assert!(compute_data_cid(data).is_some());
     }
+
+    #[test]
+    fn test_chunked_walk() {
+        let data = (0..1_000_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+        let mut chunker = DataChunker::default();
+        let mut blocks = Vec::new();
+        for chunk in data.chunks(100_000) {
+            blocks.extend(chunker.push(chunk));
+        }
+        blocks.extend(chunker.finish());
+        assert!(blocks.len() > 1);
+
+        let root = blocks.last().unwrap().0.clone();
+        assert_eq!(compute_data_cid(&data), Some(root.clone()));
+
+        for (cid, block) in &blocks {
+            assert!(verify_block(cid, block));
+        }
+        assert!(!verify_block(&root, b"other"));
+
+        let mut walk = UnixfsWalk::new(root);
+        let mut read = Vec::new();
+        while let Some(cid) = walk.next_cid() {
+            let (_, block) = blocks.iter().find(|(c, _)| *c == cid).unwrap();
+            read.extend(walk.visit(block).unwrap());
+        }
+        assert_eq!(read, data);
+
+        let root_block = &blocks.last().unwrap().1;
+        assert_eq!(unixfs_file_size(root_block).unwrap(), data.len() as u64);
+
+        let range = 300_000..500_000;
+        let mut walk = UnixfsWalk::with_range(blocks.last().unwrap().0.clone(), range.clone());
+        let mut read = Vec::new();
+        let mut visited = 0;
+        while let Some(cid) = walk.next_cid() {
+            let (_, block) = blocks.iter().find(|(c, _)| *c == cid).unwrap();
+            read.extend(walk.visit(block).unwrap());
+            visited += 1;
+        }
+        assert_eq!(read, data[300_000..500_000]);
+        assert!(visited < blocks.len());
+    }
 }
@@ -0,0 +1,5 @@
+mod query;
+mod read;
+
+pub use query::*;
+pub use read::*;
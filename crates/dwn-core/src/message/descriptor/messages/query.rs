@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use time::OffsetDateTime;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method},
+};
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesQuery {
+    interface: Interface,
+    method: Method,
+    pub filter: Option<MessagesFilter>,
+    /// Only return messages after this cursor.
+    pub cursor: Option<u64>,
+    /// Maximum number of messages to return.
+    /// The DWN may return fewer.
+    pub limit: Option<usize>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesFilter {
+    pub interface: Option<Interface>,
+    pub method: Option<Method>,
+    pub protocol: Option<String>,
+}
+
+#[derive(Default)]
+pub struct MessagesQueryBuilder {
+    pub filter: MessagesFilter,
+    pub cursor: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl MessagesQueryBuilder {
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::MessagesQuery(Box::new(MessagesQuery {
+            interface: Interface::Messages,
+            method: Method::Query,
+            filter: Some(self.filter),
+            cursor: self.cursor,
+            limit: self.limit,
+            message_timestamp: OffsetDateTime::now_utc(),
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
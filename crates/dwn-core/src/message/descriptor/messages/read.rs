@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesRead {
+    interface: Interface,
+    method: Method,
+    pub message_cid: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+}
+
+pub struct MessagesReadBuilder {
+    message_cid: String,
+}
+
+impl MessagesReadBuilder {
+    pub fn new(message_cid: String) -> Self {
+        Self { message_cid }
+    }
+
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::MessagesRead(Box::new(MessagesRead {
+            interface: Interface::Messages,
+            method: Method::Read,
+            message_cid: self.message_cid,
+            message_timestamp: OffsetDateTime::now_utc(),
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
@@ -3,9 +3,15 @@ use std::fmt::Display;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
+mod messages;
+mod permissions;
 mod protocols;
 mod records;
+#[cfg(test)]
+mod roundtrip;
 
+pub use messages::*;
+pub use permissions::*;
 pub use protocols::*;
 pub use records::*;
 use time::OffsetDateTime;
@@ -15,11 +21,17 @@ use super::cid::{CidGenerationError, compute_cid_cbor};
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Descriptor {
+    MessagesQuery(Box<MessagesQuery>),
+    MessagesRead(Box<MessagesRead>),
+    PermissionsGrant(Box<PermissionsGrant>),
+    PermissionsRequest(Box<PermissionsRequest>),
+    PermissionsRevoke(Box<PermissionsRevoke>),
     ProtocolsConfigure(Box<ProtocolsConfigure>),
     ProtocolsQuery(Box<ProtocolsQuery>),
     RecordsDelete(Box<RecordsDelete>),
     RecordsQuery(Box<RecordsQuery>),
     RecordsRead(Box<RecordsRead>),
+    RecordsSubscribe(Box<RecordsSubscribe>),
     RecordsSync(Box<RecordsSync>),
     RecordsWrite(Box<RecordsWrite>),
 }
@@ -39,13 +51,55 @@ impl Descriptor {
         compute_cid_cbor(&generator)
     }
 
+    pub fn interface(&self) -> Interface {
+        match self {
+            Descriptor::MessagesQuery(_) | Descriptor::MessagesRead(_) => Interface::Messages,
+            Descriptor::PermissionsGrant(_)
+            | Descriptor::PermissionsRequest(_)
+            | Descriptor::PermissionsRevoke(_) => Interface::Permissions,
+            Descriptor::ProtocolsConfigure(_) | Descriptor::ProtocolsQuery(_) => {
+                Interface::Protocols
+            }
+            Descriptor::RecordsDelete(_)
+            | Descriptor::RecordsQuery(_)
+            | Descriptor::RecordsRead(_)
+            | Descriptor::RecordsSubscribe(_)
+            | Descriptor::RecordsSync(_)
+            | Descriptor::RecordsWrite(_) => Interface::Records,
+        }
+    }
+
+    pub fn method(&self) -> Method {
+        match self {
+            Descriptor::MessagesQuery(_) => Method::Query,
+            Descriptor::MessagesRead(_) => Method::Read,
+            Descriptor::PermissionsGrant(_) => Method::Grant,
+            Descriptor::PermissionsRequest(_) => Method::Request,
+            Descriptor::PermissionsRevoke(_) => Method::Revoke,
+            Descriptor::ProtocolsConfigure(_) => Method::Configure,
+            Descriptor::ProtocolsQuery(_) => Method::Query,
+            Descriptor::RecordsDelete(_) => Method::Delete,
+            Descriptor::RecordsQuery(_) => Method::Query,
+            Descriptor::RecordsRead(_) => Method::Read,
+            Descriptor::RecordsSubscribe(_) => Method::Subscribe,
+            Descriptor::RecordsSync(_) => Method::Sync,
+            Descriptor::RecordsWrite(_) => Method::Write,
+        }
+    }
+
     pub fn message_timestamp(&self) -> Option<&OffsetDateTime> {
         match self {
+            Descriptor::MessagesQuery(d) => Some(&d.message_timestamp),
+            Descriptor::MessagesRead(d) => Some(&d.message_timestamp),
+            Descriptor::PermissionsGrant(d) => Some(&d.message_timestamp),
+            Descriptor::PermissionsRequest(d) => Some(&d.message_timestamp),
+            Descriptor::PermissionsRevoke(d) => Some(&d.message_timestamp),
             Descriptor::ProtocolsConfigure(_) => None,
             Descriptor::ProtocolsQuery(_) => None,
             Descriptor::RecordsDelete(d) => Some(&d.message_timestamp),
             Descriptor::RecordsQuery(d) => Some(&d.message_timestamp),
             Descriptor::RecordsRead(d) => Some(&d.message_timestamp),
+            Descriptor::RecordsSubscribe(d) => Some(&d.message_timestamp),
             Descriptor::RecordsSync(d) => Some(&d.message_timestamp),
             Descriptor::RecordsWrite(d) => Some(&d.message_timestamp),
         }
@@ -72,6 +126,31 @@ impl<'de> Deserialize<'de> for Descriptor {
             .map_err(|_| serde::de::Error::custom("unsupported method"))?;
 
         match (interface, method) {
+            (Interface::Messages, Method::Query) => {
+                let desc: MessagesQuery =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::MessagesQuery(Box::new(desc)))
+            }
+            (Interface::Messages, Method::Read) => {
+                let desc: MessagesRead =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::MessagesRead(Box::new(desc)))
+            }
+            (Interface::Permissions, Method::Grant) => {
+                let desc: PermissionsGrant =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::PermissionsGrant(Box::new(desc)))
+            }
+            (Interface::Permissions, Method::Request) => {
+                let desc: PermissionsRequest =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::PermissionsRequest(Box::new(desc)))
+            }
+            (Interface::Permissions, Method::Revoke) => {
+                let desc: PermissionsRevoke =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::PermissionsRevoke(Box::new(desc)))
+            }
             (Interface::Protocols, Method::Configure) => {
                 let desc: ProtocolsConfigure =
                     serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
@@ -82,6 +161,11 @@ impl<'de> Deserialize<'de> for Descriptor {
                     serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
                 Ok(Descriptor::ProtocolsQuery(Box::new(desc)))
             }
+            (Interface::Records, Method::Delete) => {
+                let desc: RecordsDelete =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::RecordsDelete(Box::new(desc)))
+            }
             (Interface::Records, Method::Query) => {
                 let desc: RecordsQuery =
                     serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
@@ -92,6 +176,11 @@ impl<'de> Deserialize<'de> for Descriptor {
                     serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
                 Ok(Descriptor::RecordsRead(Box::new(desc)))
             }
+            (Interface::Records, Method::Subscribe) => {
+                let desc: RecordsSubscribe =
+                    serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
+                Ok(Descriptor::RecordsSubscribe(Box::new(desc)))
+            }
             (Interface::Records, Method::Sync) => {
                 let desc: RecordsSync =
                     serde_json::from_value(raw).map_err(serde::de::Error::custom)?;
@@ -111,6 +200,8 @@ impl<'de> Deserialize<'de> for Descriptor {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Interface {
+    Messages,
+    Permissions,
     Protocols,
     Records,
 }
@@ -125,8 +216,12 @@ impl Display for Interface {
 pub enum Method {
     Configure,
     Delete,
+    Grant,
     Query,
     Read,
+    Request,
+    Revoke,
+    Subscribe,
     Sync,
     Write,
 }
@@ -146,6 +241,55 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_serialize_permissions_grant() {
+        let mut scope = PermissionScope::new(Interface::Records, Method::Write);
+        scope.protocol = Some("protocol".to_string());
+
+        let msg = PermissionsGrantBuilder::new(
+            "did:example:123".parse().unwrap(),
+            scope,
+            OffsetDateTime::now_utc(),
+        )
+        .build()
+        .unwrap();
+        let ser = serde_json::to_string_pretty(&msg).unwrap();
+        println!("{}", ser);
+        let des = serde_json::from_str::<Message>(&ser).unwrap();
+        assert_eq!(des, msg);
+    }
+
+    #[test]
+    fn test_serialize_protocols_query() {
+        let msg = ProtocolsQueryBuilder {
+            filter: ProtocolFilter {
+                protocol: Some("protocol".to_string()),
+                version: Some("^1.2".parse().unwrap()),
+                versions: None,
+            },
+        }
+        .build()
+        .unwrap();
+        let ser = serde_json::to_string_pretty(&msg).unwrap();
+        println!("{}", ser);
+        let des = serde_json::from_str::<Message>(&ser).unwrap();
+        assert_eq!(des, msg);
+    }
+
+    #[test]
+    fn test_deserialize_protocol_filter_versions() {
+        let filter = serde_json::from_value::<ProtocolFilter>(serde_json::json!({
+            "protocol": "protocol",
+            "versions": ["1.0.0", "1.2.0"],
+        }))
+        .unwrap();
+        assert_eq!(
+            filter.versions,
+            Some(vec![Version::new(1, 0, 0), Version::new(1, 2, 0)])
+        );
+        assert!(filter.version.is_none());
+    }
+
     #[test]
     fn test_serialize_records_query() {
         let msg = RecordsQueryBuilder {
@@ -156,6 +300,7 @@ mod test {
                 record_id: Some("record id".to_string()),
                 ..Default::default()
             },
+            ..Default::default()
         }
         .build()
         .unwrap();
@@ -178,14 +323,18 @@ mod test {
     fn test_serialize_records_write() {
         let msg = RecordsWriteBuilder {
             data: Some(vec![0, 1, 2, 3]),
+            encrypted_data: None,
+            data_cid: None,
             data_format: Some(TEXT_PLAIN),
             context_id: None,
             protocol: Some("protocol".to_string()),
             protocol_version: Some(Version::new(1, 2, 3)),
             protocol_path: Some("protocol path".to_string()),
             published: Some(true),
+            recipient: None,
             record_id: Some("record id".to_string()),
             schema: Some("schema".to_string()),
+            tags: None,
         }
         .build()
         .unwrap();
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use time::OffsetDateTime;
+use xdid::core::did::Did;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method, PermissionScope},
+};
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsGrant {
+    interface: Interface,
+    method: Method,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub date_expires: OffsetDateTime,
+    /// DID the permission is granted to.
+    pub grantee: Did,
+    pub description: Option<String>,
+    /// ID of the `PermissionsRequest` this grant responds to, if any.
+    pub permissions_request_id: Option<String>,
+    pub scope: PermissionScope,
+}
+
+pub struct PermissionsGrantBuilder {
+    grantee: Did,
+    scope: PermissionScope,
+    date_expires: OffsetDateTime,
+    pub description: Option<String>,
+    pub permissions_request_id: Option<String>,
+}
+
+impl PermissionsGrantBuilder {
+    pub fn new(grantee: Did, scope: PermissionScope, date_expires: OffsetDateTime) -> Self {
+        Self {
+            grantee,
+            scope,
+            date_expires,
+            description: None,
+            permissions_request_id: None,
+        }
+    }
+
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::PermissionsGrant(Box::new(PermissionsGrant {
+            interface: Interface::Permissions,
+            method: Method::Grant,
+            message_timestamp: OffsetDateTime::now_utc(),
+            date_expires: self.date_expires,
+            grantee: self.grantee,
+            description: self.description,
+            permissions_request_id: self.permissions_request_id,
+            scope: self.scope,
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
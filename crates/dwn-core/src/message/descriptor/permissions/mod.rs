@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::message::descriptor::{Interface, Method};
+
+mod grant;
+mod request;
+mod revoke;
+
+pub use grant::*;
+pub use request::*;
+pub use revoke::*;
+
+/// The set of messages a permission applies to.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionScope {
+    pub interface: Interface,
+    pub method: Method,
+    /// Restricts the scope to records within this protocol.
+    pub protocol: Option<String>,
+    /// Restricts the scope to records with this schema.
+    pub schema: Option<String>,
+}
+
+impl PermissionScope {
+    pub fn new(interface: Interface, method: Method) -> Self {
+        Self {
+            interface,
+            method,
+            protocol: None,
+            schema: None,
+        }
+    }
+}
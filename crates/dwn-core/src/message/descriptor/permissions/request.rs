@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use time::OffsetDateTime;
+use xdid::core::did::Did;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method, PermissionScope},
+};
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsRequest {
+    interface: Interface,
+    method: Method,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+    /// DID requesting the permission.
+    pub grantee: Did,
+    pub description: Option<String>,
+    pub scope: PermissionScope,
+}
+
+pub struct PermissionsRequestBuilder {
+    grantee: Did,
+    scope: PermissionScope,
+    pub description: Option<String>,
+}
+
+impl PermissionsRequestBuilder {
+    pub fn new(grantee: Did, scope: PermissionScope) -> Self {
+        Self {
+            grantee,
+            scope,
+            description: None,
+        }
+    }
+
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::PermissionsRequest(Box::new(PermissionsRequest {
+            interface: Interface::Permissions,
+            method: Method::Request,
+            message_timestamp: OffsetDateTime::now_utc(),
+            grantee: self.grantee,
+            description: self.description,
+            scope: self.scope,
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
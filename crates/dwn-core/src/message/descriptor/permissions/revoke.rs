@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsRevoke {
+    interface: Interface,
+    method: Method,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+    pub permissions_grant_id: String,
+}
+
+pub struct PermissionsRevokeBuilder {
+    permissions_grant_id: String,
+}
+
+impl PermissionsRevokeBuilder {
+    pub fn new(permissions_grant_id: String) -> Self {
+        Self {
+            permissions_grant_id,
+        }
+    }
+
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::PermissionsRevoke(Box::new(PermissionsRevoke {
+            interface: Interface::Permissions,
+            method: Method::Revoke,
+            message_timestamp: OffsetDateTime::now_utc(),
+            permissions_grant_id: self.permissions_grant_id,
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
@@ -1,5 +1,7 @@
 mod configure;
 mod query;
+mod validate;
 
 pub use configure::*;
 pub use query::*;
+pub use validate::*;
@@ -1,7 +1,12 @@
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-use crate::message::descriptor::{Interface, Method};
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method},
+};
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -13,8 +18,66 @@ pub struct ProtocolsQuery {
 }
 
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
 pub struct ProtocolFilter {
     pub protocol: Option<String>,
-    pub versions: Option<Vec<semver::Version>>,
+    /// Version requirement, such as `^1.2`.
+    pub version: Option<VersionReq>,
+    /// Exact versions to match.
+    /// Superseded by `version`, but still accepted from older clients.
+    pub versions: Option<Vec<Version>>,
+}
+
+impl ProtocolFilter {
+    /// Whether a `ProtocolsConfigure` message matches the filter.
+    /// Does not check whether the protocol is published.
+    pub fn matches(&self, entry: &Message) -> bool {
+        let Descriptor::ProtocolsConfigure(desc) = &entry.descriptor else {
+            return false;
+        };
+
+        if let Some(protocol) = &self.protocol
+            && desc.definition.protocol != *protocol
+        {
+            return false;
+        }
+
+        if let Some(version) = &self.version
+            && !version.matches(&desc.protocol_version)
+        {
+            return false;
+        }
+
+        if let Some(versions) = &self.versions
+            && !versions.contains(&desc.protocol_version)
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+#[derive(Default)]
+pub struct ProtocolsQueryBuilder {
+    pub filter: ProtocolFilter,
+}
+
+impl ProtocolsQueryBuilder {
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::ProtocolsQuery(Box::new(ProtocolsQuery {
+            interface: Interface::Protocols,
+            method: Method::Query,
+            filter: self.filter,
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
 }
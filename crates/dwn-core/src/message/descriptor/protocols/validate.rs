@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::message::descriptor::{ProtocolDefinition, ProtocolStructure};
+
+/// Registered top-level media types.
+const MEDIA_TYPES: &[&str] = &[
+    "application",
+    "audio",
+    "example",
+    "font",
+    "haptics",
+    "image",
+    "message",
+    "model",
+    "multipart",
+    "text",
+    "video",
+];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolValidationError {
+    #[error("structure {path} has no matching type")]
+    UnknownType { path: String },
+    #[error("rule at {path} references {of}, which is not an ancestor")]
+    InvalidOf { path: String, of: String },
+    #[error("rule at {path} has an empty can list")]
+    EmptyCan { path: String },
+    #[error("type {name} has unknown data format {data_format}")]
+    UnknownDataFormat { name: String, data_format: String },
+}
+
+impl ProtocolDefinition {
+    /// Checks the definition is internally consistent.
+    pub fn validate(&self) -> Result<(), ProtocolValidationError> {
+        for (name, ty) in &self.types {
+            for data_format in &ty.data_format {
+                if !MEDIA_TYPES.contains(&data_format.type_().as_str()) {
+                    return Err(ProtocolValidationError::UnknownDataFormat {
+                        name: name.clone(),
+                        data_format: data_format.to_string(),
+                    });
+                }
+            }
+        }
+
+        validate_structures(self, &self.structure, &mut Vec::new())
+    }
+}
+
+fn validate_structures<'a>(
+    definition: &ProtocolDefinition,
+    structures: &'a HashMap<String, ProtocolStructure>,
+    ancestors: &mut Vec<&'a str>,
+) -> Result<(), ProtocolValidationError> {
+    for (name, structure) in structures {
+        let path = ancestors
+            .iter()
+            .copied()
+            .chain([name.as_str()])
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if !definition.types.contains_key(name) {
+            return Err(ProtocolValidationError::UnknownType { path });
+        }
+
+        for rule in structure.actions.iter().flatten() {
+            if rule.can.is_empty() {
+                return Err(ProtocolValidationError::EmptyCan { path });
+            }
+
+            if let Some(of) = &rule.of
+                && !ancestors.contains(&of.as_str())
+            {
+                return Err(ProtocolValidationError::InvalidOf {
+                    path,
+                    of: of.clone(),
+                });
+            }
+        }
+
+        ancestors.push(name);
+        validate_structures(definition, &structure.children, ancestors)?;
+        ancestors.pop();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn definition(structure: Value) -> ProtocolDefinition {
+        serde_json::from_value(json!({
+            "protocol": "my-protocol",
+            "published": true,
+            "types": {
+                "thread": {
+                    "dataFormat": ["text/plain"],
+                },
+                "reply": {
+                    "dataFormat": ["text/plain"],
+                }
+            },
+            "structure": structure,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid() {
+        let def = definition(json!({
+            "thread": {
+                "$actions": [{ "who": "anyone", "can": ["create"] }],
+                "reply": {
+                    "$actions": [{ "who": "author", "of": "thread", "can": ["create"] }],
+                }
+            }
+        }));
+        assert_eq!(def.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let def = definition(json!({
+            "thread": {
+                "comment": {}
+            }
+        }));
+        assert_eq!(
+            def.validate(),
+            Err(ProtocolValidationError::UnknownType {
+                path: "thread/comment".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_of() {
+        let def = definition(json!({
+            "thread": {
+                "$actions": [{ "who": "author", "of": "thread", "can": ["update"] }],
+            }
+        }));
+        assert_eq!(
+            def.validate(),
+            Err(ProtocolValidationError::InvalidOf {
+                path: "thread".to_string(),
+                of: "thread".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_can() {
+        let def = definition(json!({
+            "thread": {
+                "$actions": [{ "who": "anyone", "can": [] }],
+            }
+        }));
+        assert_eq!(
+            def.validate(),
+            Err(ProtocolValidationError::EmptyCan {
+                path: "thread".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_data_format() {
+        let mut def = definition(json!({}));
+        def.types.get_mut("thread").unwrap().data_format = vec!["foo/bar".parse().unwrap()];
+        assert_eq!(
+            def.validate(),
+            Err(ProtocolValidationError::UnknownDataFormat {
+                name: "thread".to_string(),
+                data_format: "foo/bar".to_string()
+            })
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use time::OffsetDateTime;
 
 use crate::message::{
@@ -7,6 +8,7 @@ use crate::message::{
     descriptor::{Descriptor, Interface, Method},
 };
 
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct RecordsDelete {
@@ -15,15 +17,28 @@ pub struct RecordsDelete {
     #[serde(with = "time::serde::rfc3339")]
     pub message_timestamp: OffsetDateTime,
     pub record_id: String,
+    /// Whether to also delete descendants of the record.
+    pub prune: Option<bool>,
 }
 
 pub struct RecordsDeleteBuilder {
     record_id: String,
+    prune: bool,
 }
 
 impl RecordsDeleteBuilder {
     pub fn new(record_id: String) -> Self {
-        Self { record_id }
+        Self {
+            record_id,
+            prune: false,
+        }
+    }
+
+    /// Whether to also delete descendants of the record.
+    /// Defaults to `false`.
+    pub fn prune(mut self, value: bool) -> Self {
+        self.prune = value;
+        self
     }
 
     pub fn build(self) -> Result<Message, CidGenerationError> {
@@ -31,6 +46,7 @@ impl RecordsDeleteBuilder {
             interface: Interface::Records,
             method: Method::Delete,
             record_id: self.record_id,
+            prune: self.prune.then_some(true),
             message_timestamp: OffsetDateTime::now_utc(),
         }));
 
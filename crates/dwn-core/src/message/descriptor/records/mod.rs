@@ -1,11 +1,17 @@
 mod delete;
+mod pagination;
 mod query;
 mod read;
+mod subscribe;
 mod sync;
+mod tags;
 mod write;
 
 pub use delete::*;
+pub use pagination::*;
 pub use query::*;
 pub use read::*;
+pub use subscribe::*;
 pub use sync::*;
+pub use tags::*;
 pub use write::*;
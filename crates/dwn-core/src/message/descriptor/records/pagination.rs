@@ -0,0 +1,72 @@
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use time::OffsetDateTime;
+
+use crate::store::Record;
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    /// Maximum number of entries to return.
+    pub limit: Option<usize>,
+    /// Cursor from a previous reply, to continue after.
+    pub cursor: Option<String>,
+}
+
+/// Position of a record within query results.
+///
+/// Results are ordered by the timestamp of each record's initial entry,
+/// then record id. Neither changes when a record is updated, so a cursor
+/// stays valid as records are written.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCursor {
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+    pub record_id: String,
+}
+
+impl QueryCursor {
+    pub fn from_record(record: &Record) -> Option<Self> {
+        Some(Self {
+            message_timestamp: *record.initial_entry.descriptor.message_timestamp()?,
+            record_id: record.initial_entry.record_id.clone(),
+        })
+    }
+
+    /// Encodes the cursor as an opaque string.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("serialize cursor");
+        BASE64_URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let json = BASE64_URL_SAFE_NO_PAD.decode(value).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::descriptor::RecordsWriteBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let msg = RecordsWriteBuilder::default().build().unwrap();
+        let record = Record {
+            initial_entry: msg.clone(),
+            latest_entry: msg,
+        };
+
+        let cursor = QueryCursor::from_record(&record).unwrap();
+        let decoded = QueryCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+        assert_eq!(decoded.record_id, record.initial_entry.record_id);
+
+        assert!(QueryCursor::decode("not a cursor").is_none());
+    }
+}
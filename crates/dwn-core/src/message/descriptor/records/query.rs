@@ -1,12 +1,18 @@
+use std::collections::BTreeMap;
+
+use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as, skip_serializing_none};
 use time::OffsetDateTime;
 use xdid::core::did::Did;
 
-use crate::message::{
-    Message,
-    cid::CidGenerationError,
-    descriptor::{Descriptor, Interface, Method},
+use crate::{
+    message::{
+        Message,
+        cid::CidGenerationError,
+        descriptor::{Descriptor, Interface, Method, Pagination, TagFilter},
+    },
+    store::Record,
 };
 
 #[skip_serializing_none]
@@ -18,6 +24,7 @@ pub struct RecordsQuery {
     pub filter: Option<RecordFilter>,
     #[serde(with = "time::serde::rfc3339")]
     pub message_timestamp: OffsetDateTime,
+    pub pagination: Option<Pagination>,
 }
 
 #[serde_as]
@@ -37,6 +44,129 @@ pub struct RecordFilter {
     pub recipient: Option<Did>,
     pub record_id: Option<String>,
     pub schema: Option<String>,
+    /// Tag predicates, all of which must match.
+    pub tags: Option<BTreeMap<String, TagFilter>>,
+}
+
+impl RecordFilter {
+    /// Whether a `RecordsWrite` entry matches the filter.
+    /// Does not check whether the entry is published.
+    pub fn matches(&self, entry: &Message) -> bool {
+        let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
+            return false;
+        };
+
+        self.matches_created(&desc.message_timestamp) && self.matches_fields(entry)
+    }
+
+    /// Whether a record matches the filter.
+    /// `date_created` is compared with the record's initial entry, which is also
+    /// what records are sorted by, and every other field with its latest entry.
+    pub fn matches_record(&self, record: &Record) -> bool {
+        let Descriptor::RecordsWrite(desc) = &record.initial_entry.descriptor else {
+            return false;
+        };
+
+        self.matches_created(&desc.message_timestamp) && self.matches_fields(&record.latest_entry)
+    }
+
+    fn matches_created(&self, timestamp: &OffsetDateTime) -> bool {
+        self.date_created
+            .is_none_or(|date| *timestamp >= date.from && *timestamp <= date.to)
+    }
+
+    fn matches_fields(&self, entry: &Message) -> bool {
+        let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
+            return false;
+        };
+
+        if let Some(attester) = &self.attester {
+            match &entry.attestation {
+                Some(jws) => {
+                    if !jws.signatures.iter().any(|s| s.header.kid.did == *attester) {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+
+        if let Some(recipient) = &self.recipient
+            && desc.recipient.as_ref() != Some(recipient)
+        {
+            return false;
+        }
+
+        if let Some(schema) = self.schema.as_deref()
+            && desc.schema.as_deref() != Some(schema)
+        {
+            return false;
+        }
+
+        if let Some(record_id) = self.record_id.as_deref()
+            && entry.record_id != record_id
+        {
+            return false;
+        }
+
+        if let Some(parent_id) = self.parent_id.as_deref() {
+            let Some(context_id) = entry.context_id.as_deref() else {
+                return false;
+            };
+
+            let Some(context_parent) = context_id.split("/").last() else {
+                return false;
+            };
+
+            if context_parent != parent_id {
+                return false;
+            }
+        }
+
+        if let Some(protocol) = self.protocol.as_deref()
+            && desc.protocol.as_deref() != Some(protocol)
+        {
+            return false;
+        }
+
+        if let Some(path) = self.protocol_path.as_ref()
+            && desc.protocol_path.as_ref() != Some(path)
+        {
+            return false;
+        }
+
+        if let Some(version) = self.protocol_version.as_ref() {
+            let Some(desc_version) = &desc.protocol_version else {
+                return false;
+            };
+
+            let req = VersionReq::parse(&format!("^{version}")).expect("parse version req");
+
+            if !req.matches(desc_version) {
+                return false;
+            }
+        }
+
+        if let Some(data_format) = &self.data_format
+            && desc.data_format.as_ref() != Some(data_format)
+        {
+            return false;
+        }
+
+        if let Some(tags) = &self.tags {
+            for (name, filter) in tags {
+                let Some(value) = desc.tags.as_ref().and_then(|t| t.get(name)) else {
+                    return false;
+                };
+
+                if !filter.matches(value) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -55,9 +185,10 @@ pub enum DateSort {
     Descending,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct RecordsQueryBuilder {
     pub filter: RecordFilter,
+    pub pagination: Option<Pagination>,
 }
 
 impl RecordsQueryBuilder {
@@ -67,6 +198,7 @@ impl RecordsQueryBuilder {
             method: Method::Query,
             filter: Some(self.filter),
             message_timestamp: OffsetDateTime::now_utc(),
+            pagination: self.pagination,
         }));
 
         Ok(Message {
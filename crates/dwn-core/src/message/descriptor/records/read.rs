@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use time::OffsetDateTime;
 
 use crate::message::{
@@ -7,6 +8,7 @@ use crate::message::{
     descriptor::{Descriptor, Interface, Method},
 };
 
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct RecordsRead {
@@ -15,15 +17,37 @@ pub struct RecordsRead {
     #[serde(with = "time::serde::rfc3339")]
     pub message_timestamp: OffsetDateTime,
     pub record_id: String,
+    /// Reads a specific retained entry, instead of the latest.
+    pub entry_id: Option<String>,
+    /// Whether to include the record's retained entries in the reply.
+    pub history: Option<bool>,
 }
 
 pub struct RecordsReadBuilder {
     record_id: String,
+    entry_id: Option<String>,
+    history: Option<bool>,
 }
 
 impl RecordsReadBuilder {
     pub fn new(record_id: String) -> Self {
-        Self { record_id }
+        Self {
+            record_id,
+            entry_id: None,
+            history: None,
+        }
+    }
+
+    /// Reads a specific retained entry by its entry id.
+    pub fn entry_id(mut self, value: String) -> Self {
+        self.entry_id = Some(value);
+        self
+    }
+
+    /// Whether to include the record's retained entries in the reply.
+    pub fn history(mut self, value: bool) -> Self {
+        self.history = Some(value);
+        self
     }
 
     pub fn build(self) -> Result<Message, CidGenerationError> {
@@ -32,6 +56,8 @@ impl RecordsReadBuilder {
             method: Method::Read,
             record_id: self.record_id,
             message_timestamp: OffsetDateTime::now_utc(),
+            entry_id: self.entry_id,
+            history: self.history,
         }));
 
         Ok(Message {
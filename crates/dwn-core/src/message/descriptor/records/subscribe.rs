@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use time::OffsetDateTime;
+
+use crate::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, Interface, Method, RecordFilter},
+};
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordsSubscribe {
+    interface: Interface,
+    method: Method,
+    pub filter: Option<RecordFilter>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub message_timestamp: OffsetDateTime,
+}
+
+#[derive(Default)]
+pub struct RecordsSubscribeBuilder {
+    pub filter: RecordFilter,
+}
+
+impl RecordsSubscribeBuilder {
+    pub fn build(self) -> Result<Message, CidGenerationError> {
+        let descriptor = Descriptor::RecordsSubscribe(Box::new(RecordsSubscribe {
+            interface: Interface::Records,
+            method: Method::Subscribe,
+            filter: Some(self.filter),
+            message_timestamp: OffsetDateTime::now_utc(),
+        }));
+
+        Ok(Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        })
+    }
+}
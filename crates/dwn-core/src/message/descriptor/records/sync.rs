@@ -1,4 +1,7 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 use time::OffsetDateTime;
 
 use crate::message::descriptor::{Interface, Method};
@@ -9,6 +12,23 @@ pub struct RecordsSync {
     interface: Interface,
     method: Method,
     pub local_records: Vec<RecordId>,
+    /// Deleted records, with the entry id of their `RecordsDelete`.
+    #[serde(default)]
+    pub local_tombstones: Vec<RecordId>,
+    /// Sync tree nodes to compare.
+    /// If set, the reply contains the remote's nodes for the same prefixes,
+    /// and records are not compared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<SyncNode>,
+    /// Restricts record comparison to records under these sync tree prefixes.
+    /// If `None`, every record is compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefixes: Option<Vec<String>>,
+    /// Entry ids of the local `ProtocolsConfigure` messages.
+    /// If set, the reply contains protocol differences,
+    /// and records are not compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_protocols: Option<Vec<String>>,
     #[serde(with = "time::serde::rfc3339")]
     pub message_timestamp: OffsetDateTime,
 }
@@ -20,12 +40,192 @@ pub struct RecordId {
 }
 
 impl RecordsSync {
-    pub fn new(local_records: Vec<RecordId>) -> Self {
+    pub fn new(local_records: Vec<RecordId>, local_tombstones: Vec<RecordId>) -> Self {
         Self {
             interface: Interface::Records,
             method: Method::Sync,
             local_records,
+            local_tombstones,
+            nodes: Vec::new(),
+            prefixes: None,
+            local_protocols: None,
             message_timestamp: OffsetDateTime::now_utc(),
         }
     }
+
+    /// Compares sync tree nodes, instead of records.
+    pub fn from_nodes(nodes: Vec<SyncNode>) -> Self {
+        Self {
+            nodes,
+            ..Self::new(Vec::new(), Vec::new())
+        }
+    }
+
+    /// Compares protocol configurations, instead of records.
+    pub fn from_protocols(local_protocols: Vec<String>) -> Self {
+        Self {
+            local_protocols: Some(local_protocols),
+            ..Self::new(Vec::new(), Vec::new())
+        }
+    }
+
+    /// Restricts record comparison to records under the given prefixes.
+    pub fn with_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.prefixes = Some(prefixes);
+        self
+    }
+}
+
+/// Maximum length of a sync tree prefix.
+/// Records under a prefix of this length are compared directly.
+pub const SYNC_TREE_DEPTH: usize = 8;
+
+/// Summary of the records under a sync tree prefix.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SyncNode {
+    pub prefix: String,
+    pub hash: String,
+    pub count: usize,
+}
+
+impl SyncNode {
+    /// Creates a node from the XOR of the [sync_leaf_hash] of every record under it.
+    pub fn new(prefix: String, hash: &[u8], count: usize) -> Self {
+        Self {
+            prefix,
+            hash: to_hex(hash),
+            count,
+        }
+    }
+}
+
+/// Hash tree over a target's records, used to find differing records during sync.
+///
+/// Records are keyed by the hex SHA3-256 hash of their record id, so each node has
+/// up to 16 children, one per hex digit.
+#[derive(Debug, Default)]
+pub struct SyncTree(BTreeMap<String, RecordId>);
+
+impl SyncTree {
+    pub fn new(records: impl IntoIterator<Item = RecordId>) -> Self {
+        Self(
+            records
+                .into_iter()
+                .map(|r| (sync_key(&r.record_id), r))
+                .collect(),
+        )
+    }
+
+    /// Summarizes the records under a prefix.
+    pub fn node(&self, prefix: &str) -> SyncNode {
+        let mut hash = [0; 32];
+        let mut count = 0;
+
+        for (key, record) in self.range(prefix) {
+            xor_hash(&mut hash, &sync_leaf_hash(key, &record.latest_entry_id));
+            count += 1;
+        }
+
+        SyncNode::new(prefix.to_string(), &hash, count)
+    }
+
+    /// Returns the records under a prefix.
+    pub fn records(&self, prefix: &str) -> impl Iterator<Item = &RecordId> {
+        self.range(prefix).map(|(_, r)| r)
+    }
+
+    fn range(&self, prefix: &str) -> impl Iterator<Item = (&String, &RecordId)> {
+        self.0
+            .range(prefix.to_string()..)
+            .take_while(move |(k, _)| k.starts_with(prefix))
+    }
+}
+
+/// Returns the child prefixes of a sync tree prefix.
+pub fn sync_children(prefix: &str) -> impl Iterator<Item = String> {
+    "0123456789abcdef"
+        .chars()
+        .map(move |c| format!("{prefix}{c}"))
+}
+
+/// Returns the sync tree key of a record.
+pub fn sync_key(record_id: &str) -> String {
+    to_hex(&Sha3_256::digest(record_id.as_bytes()))
+}
+
+/// Returns the hash of a record in the sync tree.
+///
+/// Node hashes XOR the hashes of their records, so stores can keep them
+/// up to date as records change, without rehashing the whole subtree.
+pub fn sync_leaf_hash(key: &str, latest_entry_id: &str) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(key.as_bytes());
+    hasher.update(b":");
+    hasher.update(latest_entry_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Combines a record hash into a node hash.
+/// Applying the same record hash twice removes it again.
+pub fn xor_hash(hash: &mut [u8], leaf: &[u8; 32]) {
+    for (a, b) in hash.iter_mut().zip(leaf) {
+        *a ^= b;
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(i: usize, entry: &str) -> RecordId {
+        RecordId {
+            record_id: format!("record-{i}"),
+            latest_entry_id: entry.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sync_tree_diff() {
+        let a = SyncTree::new((0..100).map(|i| record(i, "a")));
+        let b = SyncTree::new((0..100).map(|i| record(i, if i == 42 { "b" } else { "a" })));
+
+        assert_eq!(a.node(""), a.node(""));
+        assert_ne!(a.node("").hash, b.node("").hash);
+        assert_eq!(a.node("").count, 100);
+
+        // Only the subtree containing the changed record differs.
+        let key = sync_key("record-42");
+        let differing = sync_children("")
+            .filter(|p| a.node(p) != b.node(p))
+            .collect::<Vec<_>>();
+        assert_eq!(differing, vec![key[..1].to_string()]);
+
+        let records = b.records(&key).collect::<Vec<_>>();
+        assert_eq!(records, vec![&record(42, "b")]);
+    }
+
+    #[test]
+    fn test_sync_tree_incremental() {
+        let a = SyncTree::new((0..10).map(|i| record(i, "a")));
+        let b = SyncTree::new((0..10).map(|i| record(i, if i == 3 { "b" } else { "a" })));
+
+        // Swapping a single record's hash turns one node into the other.
+        let key = sync_key("record-3");
+        let mut hash = [0; 32];
+        for i in 0..10 {
+            xor_hash(
+                &mut hash,
+                &sync_leaf_hash(&sync_key(&format!("record-{i}")), "a"),
+            );
+        }
+        assert_eq!(SyncNode::new(String::new(), &hash, 10), a.node(""));
+
+        xor_hash(&mut hash, &sync_leaf_hash(&key, "a"));
+        xor_hash(&mut hash, &sync_leaf_hash(&key, "b"));
+        assert_eq!(SyncNode::new(String::new(), &hash, 10), b.node(""));
+    }
 }
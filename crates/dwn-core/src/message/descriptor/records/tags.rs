@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Number;
+use serde_with::skip_serializing_none;
+
+/// Tags attached to a `RecordsWrite`, keyed by name.
+pub type Tags = BTreeMap<String, TagValue>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TagValue {
+    Bool(bool),
+    Number(Number),
+    String(String),
+}
+
+/// Predicate on a single tag value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TagFilter {
+    Equal(TagValue),
+    StartsWith {
+        #[serde(rename = "startsWith")]
+        starts_with: String,
+    },
+    Range(TagRange),
+}
+
+/// Numeric range, with all bounds optional.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TagRange {
+    pub gt: Option<Number>,
+    pub gte: Option<Number>,
+    pub lt: Option<Number>,
+    pub lte: Option<Number>,
+}
+
+impl TagFilter {
+    pub fn matches(&self, value: &TagValue) -> bool {
+        match (self, value) {
+            (TagFilter::Equal(TagValue::Number(a)), TagValue::Number(b)) => {
+                a.as_f64() == b.as_f64()
+            }
+            (TagFilter::Equal(a), b) => a == b,
+            (TagFilter::StartsWith { starts_with }, TagValue::String(s)) => {
+                s.starts_with(starts_with)
+            }
+            (TagFilter::Range(range), TagValue::Number(n)) => {
+                let Some(n) = n.as_f64() else {
+                    return false;
+                };
+
+                let bound = |b: &Option<Number>| b.as_ref().and_then(|b| b.as_f64());
+
+                if let Some(gt) = bound(&range.gt)
+                    && n <= gt
+                {
+                    return false;
+                }
+                if let Some(gte) = bound(&range.gte)
+                    && n < gte
+                {
+                    return false;
+                }
+                if let Some(lt) = bound(&range.lt)
+                    && n >= lt
+                {
+                    return false;
+                }
+                if let Some(lte) = bound(&range.lte)
+                    && n > lte
+                {
+                    return false;
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_filter() {
+        let equal = serde_json::from_value::<TagFilter>(json!("draft")).unwrap();
+        assert_eq!(
+            equal,
+            TagFilter::Equal(TagValue::String("draft".to_string()))
+        );
+
+        let starts_with =
+            serde_json::from_value::<TagFilter>(json!({ "startsWith": "dr" })).unwrap();
+        assert_eq!(
+            starts_with,
+            TagFilter::StartsWith {
+                starts_with: "dr".to_string()
+            }
+        );
+
+        let range = serde_json::from_value::<TagFilter>(json!({ "gte": 1, "lt": 5 })).unwrap();
+        assert_eq!(
+            range,
+            TagFilter::Range(TagRange {
+                gte: Some(1.into()),
+                lt: Some(5.into()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let filter = TagFilter::Range(TagRange {
+            gt: Some(1.into()),
+            lte: Some(5.into()),
+            ..Default::default()
+        });
+
+        assert!(!filter.matches(&TagValue::Number(1.into())));
+        assert!(filter.matches(&TagValue::Number(2.into())));
+        assert!(filter.matches(&TagValue::Number(5.into())));
+        assert!(!filter.matches(&TagValue::Number(6.into())));
+        assert!(!filter.matches(&TagValue::String("2".to_string())));
+    }
+}
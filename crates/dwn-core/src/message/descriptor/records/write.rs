@@ -2,12 +2,13 @@ use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as, skip_serializing_none};
 use time::OffsetDateTime;
+use xdid::core::did::Did;
 
 use crate::message::{
-    Message,
+    Jwe, Message,
     cid::CidGenerationError,
     data::{Data, compute_data_cid},
-    descriptor::{Descriptor, Interface, Method},
+    descriptor::{Descriptor, Interface, Method, Tags},
 };
 
 #[serde_as]
@@ -26,25 +27,46 @@ pub struct RecordsWrite {
     pub protocol_version: Option<semver::Version>,
     pub protocol_path: Option<String>,
     pub published: Option<bool>,
+    /// Intended recipient of the record.
+    /// Must remain unchanged across updates.
+    pub recipient: Option<Did>,
     pub schema: Option<String>,
+    pub tags: Option<Tags>,
 }
 
 #[derive(Default)]
 pub struct RecordsWriteBuilder {
     pub data: Option<Vec<u8>>,
+    /// Encrypted data.
+    /// Takes precedence over `data`, with the data CID computed over the ciphertext.
+    pub encrypted_data: Option<Jwe>,
+    /// Root CID of data uploaded separately as unixfs blocks.
+    /// Only used if no `data` or `encrypted_data` is set.
+    pub data_cid: Option<String>,
     pub data_format: Option<mime::Mime>,
     pub context_id: Option<String>,
     pub protocol: Option<String>,
     pub protocol_path: Option<String>,
     pub protocol_version: Option<semver::Version>,
     pub published: Option<bool>,
+    pub recipient: Option<Did>,
     pub record_id: Option<String>,
     pub schema: Option<String>,
+    pub tags: Option<Tags>,
 }
 
 impl RecordsWriteBuilder {
     pub fn build(self) -> Result<Message, CidGenerationError> {
-        let data_cid = self.data.as_ref().and_then(|d| compute_data_cid(d));
+        let data_cid = match &self.encrypted_data {
+            Some(jwe) => BASE64_URL_SAFE_NO_PAD
+                .decode(&jwe.ciphertext)
+                .ok()
+                .and_then(|c| compute_data_cid(&c)),
+            None => match &self.data {
+                Some(d) => compute_data_cid(d),
+                None => self.data_cid,
+            },
+        };
 
         let descriptor = Descriptor::RecordsWrite(Box::new(RecordsWrite {
             interface: Interface::Records,
@@ -52,10 +74,12 @@ impl RecordsWriteBuilder {
             data_cid,
             data_format: self.data_format,
             schema: self.schema,
+            tags: self.tags,
             protocol: self.protocol,
             protocol_version: self.protocol_version,
             protocol_path: self.protocol_path,
             published: self.published,
+            recipient: self.recipient,
             message_timestamp: OffsetDateTime::now_utc(),
         }));
 
@@ -64,10 +88,13 @@ impl RecordsWriteBuilder {
             None => descriptor.compute_entry_id()?,
         };
 
-        let data = self
-            .data
-            .as_ref()
-            .map(|d| Data::Base64(BASE64_URL_SAFE_NO_PAD.encode(d)));
+        let data = match self.encrypted_data {
+            Some(jwe) => Some(Data::Encrypted(jwe)),
+            None => self
+                .data
+                .as_ref()
+                .map(|d| Data::Base64(BASE64_URL_SAFE_NO_PAD.encode(d))),
+        };
 
         Ok(Message {
             attestation: None,
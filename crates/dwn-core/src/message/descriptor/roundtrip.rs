@@ -0,0 +1,490 @@
+//! Property tests that every descriptor variant survives a JSON round trip.
+
+use std::collections::{BTreeMap, HashMap};
+
+use mime::{APPLICATION_JSON, TEXT_PLAIN};
+use proptest::{collection, option, prelude::*};
+use semver::{Version, VersionReq};
+use xdid::core::did::Did;
+
+use crate::message::{Message, descriptor::*};
+
+fn text() -> impl Strategy<Value = String> {
+    "[a-z0-9]{1,12}"
+}
+
+fn did() -> impl Strategy<Value = Did> {
+    text().prop_map(|id| format!("did:example:{id}").parse().unwrap())
+}
+
+fn version() -> impl Strategy<Value = Version> {
+    (0..5u64, 0..5u64, 0..5u64).prop_map(|(a, b, c)| Version::new(a, b, c))
+}
+
+fn mime() -> impl Strategy<Value = mime::Mime> {
+    prop_oneof![Just(TEXT_PLAIN), Just(APPLICATION_JSON)]
+}
+
+fn interface() -> impl Strategy<Value = Interface> {
+    prop_oneof![
+        Just(Interface::Messages),
+        Just(Interface::Permissions),
+        Just(Interface::Protocols),
+        Just(Interface::Records),
+    ]
+}
+
+fn method() -> impl Strategy<Value = Method> {
+    prop_oneof![
+        Just(Method::Configure),
+        Just(Method::Delete),
+        Just(Method::Grant),
+        Just(Method::Query),
+        Just(Method::Read),
+        Just(Method::Request),
+        Just(Method::Revoke),
+        Just(Method::Subscribe),
+        Just(Method::Sync),
+        Just(Method::Write),
+    ]
+}
+
+fn scope() -> impl Strategy<Value = PermissionScope> {
+    (
+        interface(),
+        method(),
+        option::of(text()),
+        option::of(text()),
+    )
+        .prop_map(|(interface, method, protocol, schema)| {
+            let mut scope = PermissionScope::new(interface, method);
+            scope.protocol = protocol;
+            scope.schema = schema;
+            scope
+        })
+}
+
+fn tag_value() -> impl Strategy<Value = TagValue> {
+    prop_oneof![
+        any::<bool>().prop_map(TagValue::Bool),
+        any::<i64>().prop_map(|n| TagValue::Number(n.into())),
+        text().prop_map(TagValue::String),
+    ]
+}
+
+fn tag_filter() -> impl Strategy<Value = TagFilter> {
+    prop_oneof![
+        tag_value().prop_map(TagFilter::Equal),
+        text().prop_map(|starts_with| TagFilter::StartsWith { starts_with }),
+        (option::of(any::<i64>()), option::of(any::<i64>()))
+            .prop_filter("empty range", |(gte, lt)| gte.is_some() || lt.is_some())
+            .prop_map(|(gte, lt)| TagFilter::Range(TagRange {
+                gte: gte.map(Into::into),
+                lt: lt.map(Into::into),
+                ..Default::default()
+            })),
+    ]
+}
+
+fn record_filter() -> impl Strategy<Value = RecordFilter> {
+    (
+        option::of(did()),
+        option::of(mime()),
+        option::of(prop_oneof![
+            Just(DateSort::Ascending),
+            Just(DateSort::Descending)
+        ]),
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of(version()),
+        option::of(did()),
+        option::of(text()),
+        option::of(text()),
+        option::of(collection::btree_map(text(), tag_filter(), 0..3)),
+    )
+        .prop_map(
+            |(
+                attester,
+                data_format,
+                date_sort,
+                parent_id,
+                protocol,
+                protocol_path,
+                protocol_version,
+                recipient,
+                record_id,
+                schema,
+                tags,
+            )| RecordFilter {
+                attester,
+                data_format,
+                date_sort,
+                parent_id,
+                protocol,
+                protocol_path,
+                protocol_version,
+                recipient,
+                record_id,
+                schema,
+                tags,
+                ..Default::default()
+            },
+        )
+}
+
+fn protocol_rule() -> impl Strategy<Value = ProtocolRule> {
+    (
+        prop_oneof![Just(Who::Anyone), Just(Who::Author), Just(Who::Recipient)],
+        collection::vec(
+            prop_oneof![
+                Just(Can::Create),
+                Just(Can::Delete),
+                Just(Can::Query),
+                Just(Can::Read),
+                Just(Can::Subscribe),
+                Just(Can::Update),
+            ],
+            1..4,
+        ),
+        option::of(text()),
+    )
+        .prop_map(|(who, can, of)| ProtocolRule { who, can, of })
+}
+
+fn protocol_definition() -> impl Strategy<Value = ProtocolDefinition> {
+    (
+        text(),
+        any::<bool>(),
+        collection::hash_map(
+            text(),
+            (collection::vec(mime(), 1..3), option::of(text())).prop_map(
+                |(data_format, schema)| ProtocolType {
+                    data_format,
+                    schema,
+                },
+            ),
+            0..3,
+        ),
+        collection::hash_map(
+            text(),
+            option::of(collection::vec(protocol_rule(), 0..3)).prop_map(|actions| {
+                ProtocolStructure {
+                    actions,
+                    children: HashMap::new(),
+                }
+            }),
+            0..3,
+        ),
+    )
+        .prop_map(
+            |(protocol, published, types, structure)| ProtocolDefinition {
+                protocol,
+                published,
+                types,
+                structure,
+            },
+        )
+}
+
+fn messages_query() -> impl Strategy<Value = Message> {
+    (
+        option::of(interface()),
+        option::of(method()),
+        option::of(text()),
+        option::of(any::<u64>()),
+        option::of(any::<usize>()),
+    )
+        .prop_map(|(interface, method, protocol, cursor, limit)| {
+            MessagesQueryBuilder {
+                filter: MessagesFilter {
+                    interface,
+                    method,
+                    protocol,
+                },
+                cursor,
+                limit,
+            }
+            .build()
+            .unwrap()
+        })
+}
+
+fn messages_read() -> impl Strategy<Value = Message> {
+    text().prop_map(|cid| MessagesReadBuilder::new(cid).build().unwrap())
+}
+
+fn permissions_grant() -> impl Strategy<Value = Message> {
+    (did(), scope(), option::of(text()), option::of(text())).prop_map(
+        |(grantee, scope, description, permissions_request_id)| {
+            let mut builder =
+                PermissionsGrantBuilder::new(grantee, scope, OffsetDateTime::now_utc());
+            builder.description = description;
+            builder.permissions_request_id = permissions_request_id;
+            builder.build().unwrap()
+        },
+    )
+}
+
+fn permissions_request() -> impl Strategy<Value = Message> {
+    (did(), scope(), option::of(text())).prop_map(|(grantee, scope, description)| {
+        let mut builder = PermissionsRequestBuilder::new(grantee, scope);
+        builder.description = description;
+        builder.build().unwrap()
+    })
+}
+
+fn permissions_revoke() -> impl Strategy<Value = Message> {
+    text().prop_map(|id| PermissionsRevokeBuilder::new(id).build().unwrap())
+}
+
+fn protocols_configure() -> impl Strategy<Value = Message> {
+    (version(), protocol_definition()).prop_map(|(version, definition)| {
+        ProtocolsConfigureBuilder::new(version, definition)
+            .build()
+            .unwrap()
+    })
+}
+
+fn protocols_query() -> impl Strategy<Value = Message> {
+    (
+        option::of(text()),
+        option::of(version()),
+        option::of(collection::vec(version(), 0..3)),
+    )
+        .prop_map(|(protocol, version, versions)| {
+            ProtocolsQueryBuilder {
+                filter: ProtocolFilter {
+                    protocol,
+                    version: version.map(|v| VersionReq::parse(&format!("^{v}")).unwrap()),
+                    versions,
+                },
+            }
+            .build()
+            .unwrap()
+        })
+}
+
+fn records_delete() -> impl Strategy<Value = Message> {
+    (text(), any::<bool>()).prop_map(|(record_id, prune)| {
+        RecordsDeleteBuilder::new(record_id)
+            .prune(prune)
+            .build()
+            .unwrap()
+    })
+}
+
+fn records_query() -> impl Strategy<Value = Message> {
+    (
+        record_filter(),
+        option::of((option::of(0..100usize), option::of(text()))),
+    )
+        .prop_map(|(filter, pagination)| {
+            RecordsQueryBuilder {
+                filter,
+                pagination: pagination.map(|(limit, cursor)| Pagination { limit, cursor }),
+            }
+            .build()
+            .unwrap()
+        })
+}
+
+fn records_read() -> impl Strategy<Value = Message> {
+    (text(), option::of(text()), option::of(any::<bool>())).prop_map(
+        |(record_id, entry_id, history)| {
+            let mut builder = RecordsReadBuilder::new(record_id);
+            if let Some(entry_id) = entry_id {
+                builder = builder.entry_id(entry_id);
+            }
+            if let Some(history) = history {
+                builder = builder.history(history);
+            }
+            builder.build().unwrap()
+        },
+    )
+}
+
+fn records_subscribe() -> impl Strategy<Value = Message> {
+    record_filter().prop_map(|filter| RecordsSubscribeBuilder { filter }.build().unwrap())
+}
+
+fn records_sync() -> impl Strategy<Value = Message> {
+    let record_id = || {
+        (text(), text()).prop_map(|(record_id, latest_entry_id)| RecordId {
+            record_id,
+            latest_entry_id,
+        })
+    };
+
+    let node = (text(), text(), any::<usize>()).prop_map(|(prefix, hash, count)| SyncNode {
+        prefix,
+        hash,
+        count,
+    });
+
+    (
+        collection::vec(record_id(), 0..3),
+        collection::vec(record_id(), 0..3),
+        collection::vec(node, 0..3),
+        option::of(collection::vec(text(), 0..3)),
+        option::of(collection::vec(text(), 0..3)),
+    )
+        .prop_map(
+            |(local_records, local_tombstones, nodes, prefixes, local_protocols)| {
+                let mut sync = RecordsSync::new(local_records, local_tombstones);
+                sync.nodes = nodes;
+                sync.prefixes = prefixes;
+                sync.local_protocols = local_protocols;
+
+                let descriptor = Descriptor::RecordsSync(Box::new(sync));
+
+                Message {
+                    record_id: descriptor.compute_entry_id().unwrap(),
+                    context_id: None,
+                    data: None,
+                    descriptor,
+                    attestation: None,
+                    authorization: None,
+                }
+            },
+        )
+}
+
+fn records_write() -> impl Strategy<Value = Message> {
+    (
+        (
+            option::of(collection::vec(any::<u8>(), 0..32)),
+            option::of(mime()),
+            option::of(text()),
+            option::of(text()),
+            option::of(version()),
+            option::of(text()),
+        ),
+        (
+            option::of(any::<bool>()),
+            option::of(did()),
+            option::of(text()),
+            option::of(text()),
+            option::of(collection::btree_map(text(), tag_value(), 0..3)),
+        ),
+    )
+        .prop_map(
+            |(
+                (data, data_format, context_id, protocol, protocol_version, protocol_path),
+                (published, recipient, record_id, schema, tags),
+            )| {
+                RecordsWriteBuilder {
+                    data,
+                    encrypted_data: None,
+                    data_cid: None,
+                    data_format,
+                    context_id,
+                    protocol,
+                    protocol_path,
+                    protocol_version,
+                    published,
+                    recipient,
+                    record_id,
+                    schema,
+                    tags: tags.map(BTreeMap::from_iter),
+                }
+                .build()
+                .unwrap()
+            },
+        )
+}
+
+fn assert_roundtrip(msg: &Message) -> Result<(), TestCaseError> {
+    let ser = serde_json::to_string(msg).unwrap();
+    let des = serde_json::from_str::<Message>(&ser);
+    prop_assert!(des.is_ok(), "failed to deserialize {ser}: {des:?}");
+    prop_assert_eq!(&des.unwrap(), msg);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn roundtrip_messages_query(msg in messages_query()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_messages_read(msg in messages_read()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_permissions_grant(msg in permissions_grant()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_permissions_request(msg in permissions_request()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_permissions_revoke(msg in permissions_revoke()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_protocols_configure(msg in protocols_configure()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_protocols_query(msg in protocols_query()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_delete(msg in records_delete()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_query(msg in records_query()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_read(msg in records_read()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_subscribe(msg in records_subscribe()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_sync(msg in records_sync()) {
+        assert_roundtrip(&msg)?;
+    }
+
+    #[test]
+    fn roundtrip_records_write(msg in records_write()) {
+        assert_roundtrip(&msg)?;
+    }
+}
+
+/// Fails to compile when a variant is added, as a reminder to cover it above.
+#[allow(dead_code)]
+fn covered(desc: &Descriptor) {
+    match desc {
+        Descriptor::MessagesQuery(_)
+        | Descriptor::MessagesRead(_)
+        | Descriptor::PermissionsGrant(_)
+        | Descriptor::PermissionsRequest(_)
+        | Descriptor::PermissionsRevoke(_)
+        | Descriptor::ProtocolsConfigure(_)
+        | Descriptor::ProtocolsQuery(_)
+        | Descriptor::RecordsDelete(_)
+        | Descriptor::RecordsQuery(_)
+        | Descriptor::RecordsRead(_)
+        | Descriptor::RecordsSubscribe(_)
+        | Descriptor::RecordsSync(_)
+        | Descriptor::RecordsWrite(_) => {}
+    }
+}
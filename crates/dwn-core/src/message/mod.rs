@@ -2,8 +2,9 @@ use jose_jwa::Signing;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+use jose_jwk::Jwk;
 pub use mime;
-pub use semver::Version;
+pub use semver::{Version, VersionReq};
 pub use time::OffsetDateTime;
 use xdid::core::did_url::DidUrl;
 
@@ -23,6 +24,37 @@ pub struct Message {
     pub authorization: Option<Jws>,
 }
 
+impl Message {
+    /// Whether this entry is a descendant of the record `ancestor`, within
+    /// the same context.
+    pub fn is_descendant_of(&self, ancestor: &Message) -> bool {
+        let prefix = match &ancestor.context_id {
+            Some(c) => format!("{c}/{}", ancestor.record_id),
+            None => ancestor.record_id.clone(),
+        };
+
+        self.context_id.as_deref().is_some_and(|c| {
+            c.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// Whether the attached data hashes to the descriptor's `data_cid`.
+    /// Messages without data always match.
+    pub fn data_matches_cid(&self) -> bool {
+        let Some(data) = &self.data else {
+            return true;
+        };
+
+        let data_cid = match &self.descriptor {
+            descriptor::Descriptor::RecordsWrite(desc) => desc.data_cid.as_ref(),
+            _ => None,
+        };
+
+        data.compute_cid().ok().flatten().as_ref() == data_cid
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Jws {
     /// Base64 encoded payload.
@@ -51,3 +83,50 @@ pub struct AuthPayload {
     pub permissions_grant_cid: Option<String>,
     pub attestation_cid: Option<String>,
 }
+
+/// A [JWE](https://www.rfc-editor.org/rfc/rfc7516) in general JSON serialization.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Jwe {
+    /// Base64 encoded [JweProtectedHeader].
+    pub protected: String,
+    pub recipients: Vec<JweRecipient>,
+    /// Base64 encoded initialization vector.
+    pub iv: String,
+    /// Base64 encoded ciphertext.
+    pub ciphertext: String,
+    /// Base64 encoded authentication tag.
+    pub tag: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JweProtectedHeader {
+    pub enc: ContentEncryption,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncryption {
+    #[serde(rename = "A256GCM")]
+    A256Gcm,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JweRecipient {
+    pub header: JweHeader,
+    /// Base64 encoded content encryption key, wrapped for the recipient.
+    pub encrypted_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JweHeader {
+    pub alg: KeyEncryption,
+    /// Key agreement key the content encryption key is wrapped for.
+    pub kid: DidUrl,
+    /// Ephemeral public key.
+    pub epk: Jwk,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEncryption {
+    #[serde(rename = "ECDH-ES+A256KW")]
+    EcdhEsA256Kw,
+}
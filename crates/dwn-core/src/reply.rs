@@ -1,24 +1,52 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{message::Message, store::Record};
+use crate::{
+    message::{Message, descriptor::SyncNode},
+    store::{MessageLogEntry, Record},
+};
 
+/// Variants are tried in order during deserialization,
+/// so [Reply::RecordsRead] must remain last.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Reply {
     ProtocolsQuery(Vec<Message>),
+    MessagesQuery(MessagesQueryReply),
+    MessagesRead(Box<MessagesReadReply>),
     RecordsSync(Box<RecordsSyncReply>),
     RecordsQuery(RecordsQueryReply),
     RecordsRead(Box<RecordsReadReply>),
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct MessagesQueryReply {
+    pub messages: Vec<MessageLogEntry>,
+    /// Cursor to fetch the next page, if more messages remain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MessagesReadReply {
+    pub message: Option<MessageLogEntry>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct RecordsQueryReply {
     pub entries: Vec<Message>,
+    /// Cursor to fetch the next page, if more entries remain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct RecordsReadReply {
     pub entry: Option<Message>,
+    /// Retained entries of the record, in timestamp order, without data.
+    /// Only included if requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Message>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -29,4 +57,19 @@ pub struct RecordsSyncReply {
     pub local_only: Vec<String>,
     /// Records only the remote has.
     pub remote_only: Vec<Record>,
+    /// `RecordsDelete` messages for records the local has not deleted.
+    #[serde(default)]
+    pub tombstones: Vec<Message>,
+    /// Deleted records only the local has a tombstone for.
+    #[serde(default)]
+    pub local_tombstones: Vec<String>,
+    /// The remote's sync tree nodes, for the requested prefixes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<SyncNode>,
+    /// `ProtocolsConfigure` messages the local does not have.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protocols: Vec<Message>,
+    /// Entry ids of `ProtocolsConfigure` messages only the local has.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub local_protocols: Vec<String>,
 }
@@ -1,6 +1,9 @@
+use std::{ops::Range, sync::Arc};
+
+use time::OffsetDateTime;
 use xdid::core::did::Did;
 
-use crate::message::data::Data;
+use crate::message::data::{Data, UnixfsWalk, unixfs_file_size};
 
 use super::StoreError;
 
@@ -9,8 +12,158 @@ pub trait DataStore: Send + Sync {
     fn read(&self, target: &Did, cid: &str) -> Result<Option<Data>, StoreError>;
 
     /// Adds a reference to a CID.
+    /// `data` is only stored if the CID has no data yet.
+    /// The first reference to a unixfs root also references each of its stored blocks.
     fn add_ref(&self, target: &Did, cid: &str, data: Option<Data>) -> Result<(), StoreError>;
 
     /// Removes a reference to a CID.
+    /// Blocks are removed once no data root references them.
     fn remove_ref(&self, target: &Did, cid: &str) -> Result<(), StoreError>;
+
+    /// Stores a unixfs block.
+    /// The block must already be verified against its CID.
+    /// Blocks are staged until a data root references them.
+    fn put_block(&self, target: &Did, cid: &str, block: Vec<u8>) -> Result<(), StoreError>;
+
+    fn get_block(&self, target: &Did, cid: &str) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Returns whether a block is staged, uploaded but not yet referenced by a data root.
+    fn is_staged(&self, target: &Did, cid: &str) -> Result<bool, StoreError>;
+
+    /// Removes staged blocks last uploaded before `before`, that are still unreferenced.
+    fn prune_blocks(&self, before: OffsetDateTime) -> Result<(), StoreError>;
+}
+
+/// Returns whether every block of a unixfs file is stored.
+pub fn has_blocks(ds: &dyn DataStore, target: &Did, root: &str) -> Result<bool, StoreError> {
+    walk_blocks(ds, target, root, |_| {})
+}
+
+/// Reads the full contents of a unixfs file.
+/// Returns `None` if any block is missing.
+pub fn read_blocks(
+    ds: &dyn DataStore,
+    target: &Did,
+    root: &str,
+) -> Result<Option<Vec<u8>>, StoreError> {
+    let mut data = Vec::new();
+
+    if walk_blocks(ds, target, root, |content| data.extend(content))? {
+        Ok(Some(data))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Walks every block of a unixfs file, passing each block's content to `f`.
+/// Returns `false` if a block is missing.
+fn walk_blocks(
+    ds: &dyn DataStore,
+    target: &Did,
+    root: &str,
+    mut f: impl FnMut(Vec<u8>),
+) -> Result<bool, StoreError> {
+    let mut walk = UnixfsWalk::new(root.to_string());
+
+    while let Some(cid) = walk.next_cid() {
+        let Some(block) = ds.get_block(target, &cid)? else {
+            return Ok(false);
+        };
+
+        let content = walk
+            .visit(&block)
+            .map_err(|e| StoreError::InvalidInput(e.to_string()))?;
+
+        f(content);
+    }
+
+    Ok(true)
+}
+
+/// Streams the contents of a unixfs file from a [`DataStore`].
+/// Only the blocks overlapping the requested range are loaded.
+pub struct DataReader {
+    ds: Arc<dyn DataStore>,
+    target: Did,
+    walk: UnixfsWalk,
+    range: Range<u64>,
+    size: u64,
+}
+
+impl DataReader {
+    /// Returns `None` if the root block is not stored.
+    /// The range is clamped to the file size.
+    pub fn new(
+        ds: Arc<dyn DataStore>,
+        target: Did,
+        root: String,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<Self>, StoreError> {
+        let Some(root_block) = ds.get_block(&target, &root)? else {
+            return Ok(None);
+        };
+
+        let size =
+            unixfs_file_size(&root_block).map_err(|e| StoreError::InvalidInput(e.to_string()))?;
+
+        let (walk, range) = match range {
+            Some(range) => {
+                let end = range.end.min(size);
+                let range = range.start.min(end)..end;
+                (UnixfsWalk::with_range(root, range.clone()), range)
+            }
+            None => (UnixfsWalk::new(root), 0..size),
+        };
+
+        Ok(Some(Self {
+            ds,
+            target,
+            walk,
+            range,
+            size,
+        }))
+    }
+
+    /// Total size of the file, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Byte range of the file being read.
+    pub fn range(&self) -> &Range<u64> {
+        &self.range
+    }
+}
+
+impl Iterator for DataReader {
+    type Item = Result<Vec<u8>, StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let cid = self.walk.next_cid()?;
+
+            let block = match self.ds.get_block(&self.target, &cid) {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    self.walk = UnixfsWalk::finished();
+                    return Some(Err(StoreError::InvalidInput(format!(
+                        "missing block {cid}"
+                    ))));
+                }
+                Err(e) => {
+                    self.walk = UnixfsWalk::finished();
+                    return Some(Err(e));
+                }
+            };
+
+            match self.walk.visit(&block) {
+                Ok(content) if content.is_empty() => continue,
+                Ok(content) => return Some(Ok(content)),
+                Err(e) => {
+                    self.walk = UnixfsWalk::finished();
+                    return Some(Err(StoreError::InvalidInput(e.to_string())));
+                }
+            }
+        }
+    }
 }
@@ -1,10 +1,14 @@
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use xdid::core::did::Did;
 
 use crate::message::{
     Message,
-    descriptor::{ProtocolDefinition, RecordFilter, RecordsSync},
+    descriptor::{
+        MessagesFilter, Pagination, ProtocolDefinition, ProtocolFilter, RecordFilter, RecordsSync,
+        SyncNode,
+    },
 };
 
 use super::{DataStore, StoreError};
@@ -15,9 +19,64 @@ pub struct Record {
     pub latest_entry: Message,
 }
 
+/// Maximum number of messages returned by a single messages query.
+pub const MAX_MESSAGES_QUERY_LIMIT: usize = 1000;
+
+/// An entry in a target's message log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageLogEntry {
+    /// Position in the log.
+    /// Strictly increases with each appended message.
+    pub cursor: u64,
+    pub message_cid: String,
+    pub message: Message,
+}
+
+/// A message queued for delivery to a remote DWN.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxItem {
+    /// Position in the outbox.
+    /// Strictly increases with each queued message.
+    pub id: u64,
+    pub target: Did,
+    /// URL of the remote DWN.
+    pub remote: String,
+    /// Queued message, including its data.
+    pub message: Message,
+    pub status: OutboxStatus,
+    /// Number of failed delivery attempts.
+    pub attempts: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_attempt: OffsetDateTime,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OutboxStatus {
+    /// Waiting for delivery.
+    Pending,
+    /// Rejected by the remote, or out of attempts.
+    /// Failed items are no longer retried.
+    Failed,
+}
+
+/// Stores records and protocols.
+///
+/// Every accepted `Permissions*`, `ProtocolsConfigure`, `RecordsWrite`, and
+/// `RecordsDelete` is appended to the target's message log.
+/// Each accepted `RecordsWrite` is also kept in its record's history until pruned.
+/// Messages awaiting delivery to remote DWNs are kept in an outbox.
 pub trait RecordStore: Send + Sync {
+    /// Stores a protocol definition.
+    /// Each version is kept separately, so records written under older
+    /// versions can still be validated.
     fn configure_protocol(&self, target: &Did, message: Message) -> Result<(), StoreError>;
 
+    /// Returns the configured versions of a protocol, in ascending order.
+    /// If `versions` is empty, all versions are returned.
     fn query_protocol(
         &self,
         target: &Did,
@@ -26,16 +85,86 @@ pub trait RecordStore: Send + Sync {
         authorized: bool,
     ) -> Result<Vec<(Version, ProtocolDefinition)>, StoreError>;
 
+    /// Returns the stored `ProtocolsConfigure` messages matching the filter.
+    /// Unpublished protocols are only returned if `authorized`.
+    fn query_protocols(
+        &self,
+        target: &Did,
+        filter: &ProtocolFilter,
+        authorized: bool,
+    ) -> Result<Vec<Message>, StoreError>;
+
+    /// Returns messages from the log after the given cursor, in log order.
+    /// At most `limit` messages are returned, capped at [MAX_MESSAGES_QUERY_LIMIT],
+    /// along with the cursor of the last one if more may remain.
+    fn query_messages(
+        &self,
+        target: &Did,
+        filter: &MessagesFilter,
+        cursor: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<MessageLogEntry>, Option<u64>), StoreError>;
+
+    /// Reads a message from the log by its CID.
+    fn read_message(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        message_cid: &str,
+    ) -> Result<Option<MessageLogEntry>, StoreError>;
+
+    /// Stores a `PermissionsRequest`, `PermissionsGrant`, or `PermissionsRevoke`.
+    fn write_permission(&self, target: &Did, message: Message) -> Result<(), StoreError>;
+
+    /// Reads a `PermissionsRequest` or `PermissionsGrant` by its record id.
+    fn read_permission(
+        &self,
+        target: &Did,
+        permission_id: &str,
+    ) -> Result<Option<Message>, StoreError>;
+
+    /// Reads the `PermissionsRevoke` for a grant, if it has been revoked.
+    fn read_permission_revocation(
+        &self,
+        target: &Did,
+        permissions_grant_id: &str,
+    ) -> Result<Option<Message>, StoreError>;
+
     fn prepare_sync(&self, target: &Did, authorized: bool) -> Result<RecordsSync, StoreError>;
 
+    /// Reads the sync tree nodes for the given prefixes,
+    /// each at most [SYNC_TREE_DEPTH](crate::message::descriptor::SYNC_TREE_DEPTH) long.
+    /// Nodes cover the same records and tombstones as [Self::prepare_sync].
+    fn sync_nodes(
+        &self,
+        target: &Did,
+        prefixes: &[String],
+        authorized: bool,
+    ) -> Result<Vec<SyncNode>, StoreError>;
+
+    /// Deletes a record, keeping the `RecordsDelete` as a tombstone.
     fn delete(&self, ds: &dyn DataStore, target: &Did, message: Message) -> Result<(), StoreError>;
 
+    /// Reads the `RecordsDelete` tombstone of a deleted record.
+    fn read_tombstone(&self, target: &Did, record_id: &str) -> Result<Option<Message>, StoreError>;
+
+    /// Reads every record below the given initial entry in its protocol context,
+    /// without data.
+    fn read_descendants(&self, target: &Did, ancestor: &Message)
+    -> Result<Vec<Record>, StoreError>;
+
+    /// Queries records matching the filter, without data.
+    /// See [RecordFilter::matches_record].
+    /// Records are ordered by the timestamp of their initial entry then record id,
+    /// in the filter's sort direction.
+    /// If `pagination` has a cursor, only records after it are returned.
     fn query(
         &self,
         target: &Did,
         filter: &RecordFilter,
+        pagination: Option<&Pagination>,
         authorized: bool,
-    ) -> Result<Vec<Message>, StoreError>;
+    ) -> Result<Vec<Record>, StoreError>;
 
     fn read(
         &self,
@@ -45,4 +174,40 @@ pub trait RecordStore: Send + Sync {
     ) -> Result<Option<Record>, StoreError>;
 
     fn write(&self, ds: &dyn DataStore, target: &Did, message: Message) -> Result<(), StoreError>;
+
+    /// Returns the retained entries of a record, in timestamp order.
+    /// Entries are returned without data.
+    fn read_history(&self, target: &Did, record_id: &str) -> Result<Vec<Message>, StoreError>;
+
+    /// Reads a retained entry of a record by its entry id, including its data.
+    fn read_entry(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        record_id: &str,
+        entry_id: &str,
+    ) -> Result<Option<Message>, StoreError>;
+
+    /// Removes the oldest entries of a record, keeping the latest entry and at
+    /// most `max_history` past entries.
+    fn prune_history(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        record_id: &str,
+        max_history: usize,
+    ) -> Result<(), StoreError>;
+
+    /// Queues a message for delivery to a remote DWN.
+    /// Returns the id of the new pending item.
+    fn push_outbox(&self, target: &Did, remote: &str, message: Message) -> Result<u64, StoreError>;
+
+    /// Returns every outbox item, in queue order.
+    fn read_outbox(&self) -> Result<Vec<OutboxItem>, StoreError>;
+
+    /// Replaces a stored outbox item with the same id.
+    fn update_outbox(&self, item: &OutboxItem) -> Result<(), StoreError>;
+
+    /// Removes an outbox item.
+    fn remove_outbox(&self, id: u64) -> Result<(), StoreError>;
 }
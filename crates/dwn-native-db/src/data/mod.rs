@@ -3,8 +3,10 @@ use std::sync::LazyLock;
 use native_db::Models;
 
 mod v1;
+mod v2;
 
 pub use v1::*;
+pub use v2::Protocol;
 
 pub static MODELS: LazyLock<Models> = LazyLock::new(|| {
     let mut models = Models::new();
@@ -13,5 +15,22 @@ pub static MODELS: LazyLock<Models> = LazyLock::new(|| {
     models.define::<v1::CidData>().unwrap();
     models.define::<v1::RefCount>().unwrap();
     models.define::<v1::Protocol>().unwrap();
+    models.define::<v2::Protocol>().unwrap();
+    models.define::<v1::MessageLog>().unwrap();
+    models.define::<v1::MessageCid>().unwrap();
+    models.define::<v1::MessageLogHead>().unwrap();
+    models.define::<v1::Permission>().unwrap();
+    models.define::<v1::PermissionRevocation>().unwrap();
+    models.define::<v1::RecordTag>().unwrap();
+    models.define::<v1::Tombstone>().unwrap();
+    models.define::<v1::Block>().unwrap();
+    models.define::<v1::RecordEntry>().unwrap();
+    models.define::<v1::OutboxEntry>().unwrap();
+    models.define::<v1::RecordOrder>().unwrap();
+    models.define::<v1::StagedBlock>().unwrap();
+    models.define::<v1::BlockExpiry>().unwrap();
+    models.define::<v1::EntryRecipients>().unwrap();
+    models.define::<v1::SyncTreeNode>().unwrap();
+    models.define::<v1::RecordContext>().unwrap();
     models
 });
@@ -58,6 +58,188 @@ pub struct Protocol {
     pub definition: Vec<u8>,
 }
 
+impl From<super::v2::Protocol> for Protocol {
+    fn from(value: super::v2::Protocol) -> Self {
+        Self {
+            key: (value.key.0, value.key.1),
+            version: value.version,
+            definition: value.definition,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 6, version = 1)]
+pub struct MessageLog {
+    /// (target, cursor)
+    #[primary_key]
+    pub key: (String, u64),
+    pub message_cid: String,
+    /// Protocol the message belongs to, if any.
+    pub protocol: Option<String>,
+    /// Message, without data.
+    pub entry: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 7, version = 1)]
+pub struct MessageCid {
+    /// (target, message cid)
+    #[primary_key]
+    pub key: (String, String),
+    pub cursor: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 8, version = 1)]
+pub struct MessageLogHead {
+    /// target
+    #[primary_key]
+    pub key: String,
+    /// Cursor of the most recently appended message.
+    pub cursor: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 9, version = 1)]
+pub struct Permission {
+    /// (target, record id)
+    #[primary_key]
+    pub key: (String, String),
+    /// `PermissionsRequest` or `PermissionsGrant` message.
+    pub entry: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 10, version = 1)]
+pub struct PermissionRevocation {
+    /// (target, grant id)
+    #[primary_key]
+    pub key: (String, String),
+    /// `PermissionsRevoke` message.
+    pub entry: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 11, version = 1)]
+pub struct RecordTag {
+    /// (target, tag name, encoded tag value, record id)
+    #[primary_key]
+    pub key: (String, String, String, String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 12, version = 1)]
+pub struct Tombstone {
+    /// (target, record id)
+    #[primary_key]
+    pub key: (String, String),
+    /// `RecordsDelete` message.
+    pub entry: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[native_db]
+#[native_model(id = 13, version = 1)]
+pub struct Block {
+    /// (target, cid)
+    #[primary_key]
+    pub key: (String, String),
+    pub data: Vec<u8>,
+    /// Number of data root references.
+    pub refs: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 14, version = 1)]
+pub struct RecordEntry {
+    /// (target, record id, entry id)
+    #[primary_key]
+    pub key: (String, String, String),
+    /// `RecordsWrite` message, without data.
+    pub entry: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 15, version = 1)]
+pub struct OutboxEntry {
+    /// Outbox item id.
+    #[primary_key]
+    pub key: u64,
+    /// `OutboxItem`.
+    pub item: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 16, version = 1)]
+pub struct RecordOrder {
+    /// (target, encoded initial entry timestamp, record id)
+    #[primary_key]
+    pub key: (String, String, String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 17, version = 1)]
+pub struct StagedBlock {
+    /// (target, cid)
+    #[primary_key]
+    pub key: (String, String),
+    /// Encoded timestamp of the latest upload.
+    pub uploaded: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 18, version = 1)]
+pub struct BlockExpiry {
+    /// (encoded upload timestamp, target, cid)
+    #[primary_key]
+    pub key: (String, String, String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 19, version = 1)]
+pub struct EntryRecipients {
+    /// (target, record id, entry id)
+    #[primary_key]
+    pub key: (String, String, String),
+    /// `JweRecipient` list of the entry's encrypted data.
+    pub recipients: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 20, version = 1)]
+pub struct SyncTreeNode {
+    /// (target, view, sync tree prefix)
+    #[primary_key]
+    pub key: (String, String, String),
+    /// XOR of the sync hashes of every record under the prefix.
+    pub hash: Vec<u8>,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 21, version = 1)]
+pub struct RecordContext {
+    /// (target, context id, record id)
+    #[primary_key]
+    pub key: (String, String, String),
+}
+
 #[cfg(test)]
 mod tests {
     use dwn_core::message::descriptor::RecordsWriteBuilder;
@@ -0,0 +1,84 @@
+use native_db::*;
+use native_model::{Model, native_model};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::v1;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[native_db]
+#[native_model(id = 5, version = 2, from = v1::Protocol)]
+pub struct Protocol {
+    /// (target, protocol, version)
+    #[primary_key]
+    pub key: (String, String, String),
+    pub version: Version,
+    /// Serialized [ProtocolDefinition].
+    /// Cannot be stored directly because of non-deteministic maps.
+    pub definition: Vec<u8>,
+    /// `ProtocolsConfigure` message.
+    /// Not kept for protocols configured before version 2.
+    pub entry: Option<Vec<u8>>,
+}
+
+impl From<v1::Protocol> for Protocol {
+    fn from(value: v1::Protocol) -> Self {
+        Self {
+            key: (value.key.0, value.key.1, value.version.to_string()),
+            version: value.version,
+            definition: value.definition,
+            entry: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dwn_core::{message::descriptor::ProtocolDefinition, store::RecordStore};
+    use xdid::core::did::{Did, MethodId, MethodName};
+
+    use super::*;
+    use crate::NativeDbStore;
+
+    #[test]
+    fn test_migrate_protocol() {
+        let path = std::env::temp_dir().join(format!("dwn-migrate-{}.db", std::process::id()));
+
+        let target = Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId("test".to_string()),
+        };
+        let definition = serde_json::from_value::<ProtocolDefinition>(serde_json::json!({
+            "protocol": "my-protocol",
+            "published": true,
+            "types": {},
+            "structure": {},
+        }))
+        .unwrap();
+        let version = Version::new(1, 2, 0);
+
+        {
+            let mut models = Models::new();
+            models.define::<v1::Protocol>().unwrap();
+            let db = Builder::new().create(&models, &path).unwrap();
+
+            let tx = db.rw_transaction().unwrap();
+            tx.insert(v1::Protocol {
+                key: (target.to_string(), definition.protocol.clone()),
+                version: version.clone(),
+                definition: serde_json::to_vec(&definition).unwrap(),
+            })
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let store = NativeDbStore::new(&path).unwrap();
+        let found = store
+            .query_protocol(&target, definition.protocol.clone(), Vec::new(), true)
+            .unwrap();
+        assert_eq!(found, vec![(version, definition)]);
+
+        drop(store);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,12 +1,17 @@
 use dwn_core::{
-    message::data::Data,
+    message::{
+        OffsetDateTime,
+        data::{Data, UnixfsWalk},
+    },
     store::{DataStore, StoreError},
 };
+use native_db::transaction::RwTransaction;
 use xdid::core::did::Did;
 
 use crate::{
     NativeDbStore,
-    data::{CidData, RefCount},
+    data::{Block, BlockExpiry, CidData, RefCount, StagedBlock},
+    record_store::encode_order_timestamp,
 };
 
 impl DataStore for NativeDbStore<'_> {
@@ -38,8 +43,15 @@ impl DataStore for NativeDbStore<'_> {
             .map_err(|e| StoreError::BackendError(e.to_string()))?
         {
             Some(data_ref) => {
-                // Update data, if provided.
-                if let Some(data) = data {
+                // Store data, if not yet stored.
+                // Existing data is never replaced, as other records reference it.
+                if let Some(data) = data
+                    && tx
+                        .get()
+                        .primary::<CidData>(key.clone())
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?
+                        .is_none_or(|found| found.data.is_none())
+                {
                     tx.upsert(CidData {
                         key: key.clone(),
                         data: Some(serde_json::to_vec(&data).unwrap()),
@@ -65,6 +77,30 @@ impl DataStore for NativeDbStore<'_> {
                 // Insert ref count,
                 tx.insert(RefCount { key, count: 1 })
                     .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                // Reference the root's blocks, ending their staging.
+                for block_cid in walk_blocks(&tx, target, cid)? {
+                    let key = (target.to_string(), block_cid);
+
+                    if let Some(staged) = tx
+                        .get()
+                        .primary::<StagedBlock>(key.clone())
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    {
+                        tx.remove(staged)
+                            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                    }
+
+                    if let Some(mut block) = tx
+                        .get()
+                        .primary::<Block>(key)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    {
+                        block.refs += 1;
+                        tx.upsert(block)
+                            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                    }
+                }
             }
         }
 
@@ -103,6 +139,30 @@ impl DataStore for NativeDbStore<'_> {
                 tx.remove(found_data)
                     .map_err(|e| StoreError::BackendError(e.to_string()))?;
             }
+
+            // Dereference the root's blocks, removing unused ones.
+            for block_cid in walk_blocks(&tx, target, cid)? {
+                let key = (target.to_string(), block_cid);
+
+                let Some(block) = tx
+                    .get()
+                    .primary::<Block>(key)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?
+                else {
+                    continue;
+                };
+
+                if block.refs <= 1 {
+                    tx.remove(block)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                } else {
+                    let mut new_block = block.clone();
+                    new_block.refs -= 1;
+
+                    tx.upsert(new_block)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                }
+            }
         } else {
             // Decrement ref count.
             let mut new_found = found.clone();
@@ -117,10 +177,165 @@ impl DataStore for NativeDbStore<'_> {
 
         Ok(())
     }
+
+    fn put_block(&self, target: &Did, cid: &str, block: Vec<u8>) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let key = (target.to_string(), cid.to_string());
+
+        let refs = match tx
+            .get()
+            .primary::<Block>(key.clone())
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            Some(found) => found.refs,
+            None => {
+                tx.insert(Block {
+                    key: key.clone(),
+                    data: block,
+                    refs: 0,
+                })
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                0
+            }
+        };
+
+        // Stage unreferenced blocks, restarting their expiry.
+        // Earlier expiry rows are skipped once they no longer match the staged upload.
+        if refs == 0 {
+            let uploaded = encode_order_timestamp(&OffsetDateTime::now_utc());
+
+            tx.upsert(BlockExpiry {
+                key: (uploaded.clone(), key.0.clone(), key.1.clone()),
+            })
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            tx.upsert(StagedBlock { key, uploaded })
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn get_block(&self, target: &Did, cid: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let res = tx
+            .get()
+            .primary::<Block>((target.to_string(), cid.to_string()))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(res.map(|b| b.data))
+    }
+
+    fn is_staged(&self, target: &Did, cid: &str) -> Result<bool, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let res = tx
+            .get()
+            .primary::<StagedBlock>((target.to_string(), cid.to_string()))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(res.is_some())
+    }
+
+    fn prune_blocks(&self, before: OffsetDateTime) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let before = encode_order_timestamp(&before);
+
+        let expired = tx
+            .scan()
+            .primary::<BlockExpiry>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .range(..(before.clone(), "".to_string(), "".to_string()))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .filter(|res| res.as_ref().is_err() || res.as_ref().is_ok_and(|e| e.key.0 < before))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        for expiry in expired {
+            let (uploaded, target, cid) = expiry.key.clone();
+
+            tx.remove(expiry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            // Skip blocks that were referenced or uploaded again since.
+            let Some(staged) = tx
+                .get()
+                .primary::<StagedBlock>((target, cid))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .filter(|s| s.uploaded == uploaded)
+            else {
+                continue;
+            };
+
+            if let Some(block) = tx
+                .get()
+                .primary::<Block>(staged.key.clone())
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                && block.refs == 0
+            {
+                tx.remove(block)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+            }
+
+            tx.remove(staged)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Returns the CIDs of every stored block under a unixfs root, in walk order.
+/// Blocks shared within the file are returned once per occurrence.
+fn walk_blocks(tx: &RwTransaction, target: &Did, root: &str) -> Result<Vec<String>, StoreError> {
+    let mut walk = UnixfsWalk::new(root.to_string());
+    let mut cids = Vec::new();
+
+    while let Some(cid) = walk.next_cid() {
+        let Some(block) = tx
+            .get()
+            .primary::<Block>((target.to_string(), cid.clone()))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        else {
+            break;
+        };
+
+        if walk.visit(&block.data).is_err() {
+            break;
+        }
+
+        cids.push(cid);
+    }
+
+    Ok(cids)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use dwn_core::{message::data::DataChunker, store::DataReader};
     use xdid::core::did::{MethodId, MethodName};
 
     use super::*;
@@ -148,4 +363,86 @@ mod tests {
         assert!(tx.get().primary::<RefCount>(key.clone()).unwrap().is_none());
         assert!(tx.get().primary::<CidData>(key).unwrap().is_none());
     }
+
+    #[test]
+    fn test_block_cleanup() {
+        let ds = NativeDbStore::new_in_memory().unwrap();
+
+        let target = Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId("test".to_string()),
+        };
+
+        let data = vec![7; 1_000_000];
+        let mut chunker = DataChunker::default();
+        let mut blocks = chunker.push(&data);
+        blocks.extend(chunker.finish());
+        let root = blocks.last().unwrap().0.clone();
+
+        for (cid, block) in blocks.clone() {
+            ds.put_block(&target, &cid, block).unwrap();
+        }
+
+        ds.add_ref(&target, &root, None).unwrap();
+        ds.add_ref(&target, &root, None).unwrap();
+
+        let read = DataReader::new(Arc::new(ds.clone()), target.clone(), root.clone(), None)
+            .unwrap()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .concat();
+        assert_eq!(read, data);
+
+        ds.remove_ref(&target, &root).unwrap();
+        for (cid, _) in &blocks {
+            assert!(ds.get_block(&target, cid).unwrap().is_some());
+        }
+
+        ds.remove_ref(&target, &root).unwrap();
+        for (cid, _) in &blocks {
+            assert!(ds.get_block(&target, cid).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_prune_staged_blocks() {
+        let ds = NativeDbStore::new_in_memory().unwrap();
+
+        let target = Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId("test".to_string()),
+        };
+
+        let mut chunker = DataChunker::default();
+        let mut referenced = chunker.push(&[1; 1_000_000]);
+        referenced.extend(chunker.finish());
+        let root = referenced.last().unwrap().0.clone();
+
+        let mut chunker = DataChunker::default();
+        let mut staged = chunker.push(&[2; 1_000]);
+        staged.extend(chunker.finish());
+
+        for (cid, block) in referenced.iter().chain(&staged) {
+            ds.put_block(&target, cid, block.clone()).unwrap();
+        }
+
+        ds.add_ref(&target, &root, None).unwrap();
+
+        // Recently uploaded blocks are kept.
+        ds.prune_blocks(OffsetDateTime::now_utc() - Duration::from_secs(60))
+            .unwrap();
+        for (cid, _) in &staged {
+            assert!(ds.get_block(&target, cid).unwrap().is_some());
+        }
+
+        ds.prune_blocks(OffsetDateTime::now_utc() + Duration::from_secs(60))
+            .unwrap();
+        for (cid, _) in &staged {
+            assert!(ds.get_block(&target, cid).unwrap().is_none());
+        }
+        for (cid, _) in &referenced {
+            assert!(ds.get_block(&target, cid).unwrap().is_some());
+        }
+    }
 }
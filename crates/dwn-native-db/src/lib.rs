@@ -15,7 +15,18 @@ pub struct NativeDbStore<'a>(Arc<Database<'a>>);
 impl NativeDbStore<'_> {
     pub fn new(path: impl AsRef<Path>) -> Result<Self, Box<db_type::Error>> {
         let db = Builder::new().create(&data::MODELS, path)?;
-        Ok(Self(Arc::new(db)))
+
+        let tx = db.rw_transaction()?;
+        tx.migrate::<data::Protocol>()?;
+        tx.commit()?;
+
+        let store = Self(Arc::new(db));
+        store.index_record_order()?;
+        store.index_record_context()?;
+        store
+            .index_sync_tree()
+            .map_err(|e| db_type::Error::Io(std::io::Error::other(e.to_string())))?;
+        Ok(store)
     }
 
     pub fn new_in_memory() -> Result<Self, Box<db_type::Error>> {
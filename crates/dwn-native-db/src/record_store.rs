@@ -1,24 +1,144 @@
+use std::collections::{BTreeMap, HashSet};
+
 use dwn_core::{
     message::{
-        Message,
+        Message, OffsetDateTime,
+        data::Data,
         descriptor::{
-            DateSort, Descriptor, ProtocolDefinition, RecordFilter, RecordId, RecordsSync,
+            DateSort, Descriptor, MessagesFilter, Pagination, ProtocolDefinition, ProtocolFilter,
+            QueryCursor, RecordFilter, RecordId, RecordsSync, SYNC_TREE_DEPTH, SyncNode, TagFilter,
+            TagValue, sync_key, sync_leaf_hash, xor_hash,
         },
     },
-    store::{DataStore, Record, RecordStore, StoreError},
+    store::{
+        DataStore, MAX_MESSAGES_QUERY_LIMIT, MessageLogEntry, OutboxItem, OutboxStatus, Record,
+        RecordStore, StoreError,
+    },
 };
-use semver::VersionReq;
+use native_db::transaction::{RTransaction, RwTransaction};
 use tracing::{debug, error, warn};
 use xdid::core::did::Did;
 
 use crate::{
     NativeDbStore,
-    data::{InitialEntry, LatestEntry, Protocol},
+    data::{
+        EntryRecipients, InitialEntry, LatestEntry, MessageCid, MessageLog, MessageLogHead,
+        OutboxEntry, Permission, PermissionRevocation, Protocol, RecordContext, RecordEntry,
+        RecordOrder, RecordTag, SyncTreeNode, Tombstone,
+    },
 };
 
+impl NativeDbStore<'_> {
+    /// Adds query order rows for records stored before the index existed.
+    pub(crate) fn index_record_order(&self) -> Result<(), Box<native_db::db_type::Error>> {
+        let tx = self.0.rw_transaction()?;
+
+        if tx.len().primary::<RecordOrder>()? > 0 {
+            return Ok(());
+        }
+
+        let rows = tx
+            .scan()
+            .primary::<InitialEntry>()?
+            .all()?
+            .filter_map(|res| {
+                let initial = res.ok()?;
+                let entry = serde_json::from_slice::<Message>(&initial.entry).ok()?;
+                Some(order_row(initial.key.0, &entry))
+            })
+            .collect::<Vec<_>>();
+
+        for row in rows {
+            tx.upsert(row)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Adds context rows for records stored before the index existed.
+    pub(crate) fn index_record_context(&self) -> Result<(), Box<native_db::db_type::Error>> {
+        let tx = self.0.rw_transaction()?;
+
+        if tx.len().primary::<RecordContext>()? > 0 {
+            return Ok(());
+        }
+
+        let rows = tx
+            .scan()
+            .primary::<InitialEntry>()?
+            .all()?
+            .filter_map(|res| {
+                let initial = res.ok()?;
+                let entry = serde_json::from_slice::<Message>(&initial.entry).ok()?;
+                context_row(initial.key.0, &entry)
+            })
+            .collect::<Vec<_>>();
+
+        for row in rows {
+            tx.upsert(row)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Builds the sync tree for records stored before it was persisted.
+    pub(crate) fn index_sync_tree(&self) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        if tx
+            .len()
+            .primary::<SyncTreeNode>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            > 0
+        {
+            return Ok(());
+        }
+
+        let mut keys = HashSet::new();
+
+        for res in tx
+            .scan()
+            .primary::<InitialEntry>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .all()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            let initial: InitialEntry = res.map_err(|e| StoreError::BackendError(e.to_string()))?;
+            keys.insert(initial.key);
+        }
+
+        for res in tx
+            .scan()
+            .primary::<Tombstone>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .all()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            let tombstone: Tombstone = res.map_err(|e| StoreError::BackendError(e.to_string()))?;
+            keys.insert(tombstone.key);
+        }
+
+        for (target, record_id) in keys {
+            update_sync_tree(&tx, &target, &record_id, SyncState::default())?;
+        }
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
 impl RecordStore for NativeDbStore<'_> {
     fn configure_protocol(&self, target: &Did, message: Message) -> Result<(), StoreError> {
-        let Descriptor::ProtocolsConfigure(desc) = message.descriptor else {
+        let Descriptor::ProtocolsConfigure(desc) = &message.descriptor else {
             panic!("invalid message descriptor: {:?}", message.descriptor)
         };
 
@@ -30,13 +150,28 @@ impl RecordStore for NativeDbStore<'_> {
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
         tx.upsert(Protocol {
-            key: (target.to_string(), desc.definition.protocol.clone()),
-            version: desc.protocol_version,
+            key: (
+                target.to_string(),
+                desc.definition.protocol.clone(),
+                desc.protocol_version.to_string(),
+            ),
+            version: desc.protocol_version.clone(),
             definition: serde_json::to_vec(&desc.definition)
                 .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            entry: Some(
+                serde_json::to_vec(&message)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            ),
         })
         .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
+        append_message(
+            &tx,
+            target,
+            &message,
+            Some(desc.definition.protocol.clone()),
+        )?;
+
         tx.commit()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
@@ -55,15 +190,18 @@ impl RecordStore for NativeDbStore<'_> {
             .r_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
+        let target = target.to_string();
+
         let mut found = Vec::new();
 
-        for res in tx
-            .scan()
-            .primary::<Protocol>()
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .start_with((target.to_string(), protocol))
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-        {
+        for res in filter_keys(
+            tx.scan()
+                .primary::<Protocol>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .start_with((target.clone(), protocol.clone(), "".to_string()))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            |prot| prot.key.0 == target && prot.key.1 == protocol,
+        ) {
             let Ok(prot) = res.as_ref() else {
                 warn!("Failed to read protocol during scan");
                 continue;
@@ -84,11 +222,66 @@ impl RecordStore for NativeDbStore<'_> {
             found.push((version.clone(), def));
         }
 
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(found)
+    }
+
+    fn query_protocols(
+        &self,
+        target: &Did,
+        filter: &ProtocolFilter,
+        authorized: bool,
+    ) -> Result<Vec<Message>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let target = target.to_string();
+        let prefix = filter.protocol.clone().unwrap_or_default();
+
+        let mut found = Vec::new();
+
+        for res in filter_keys(
+            tx.scan()
+                .primary::<Protocol>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .start_with((target.clone(), prefix, "".to_string()))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            |prot| prot.key.0 == target,
+        ) {
+            let Ok(prot) = res else {
+                warn!("Failed to read protocol during scan");
+                continue;
+            };
+
+            let Some(entry) = &prot.entry else {
+                debug!("No configure message kept for protocol {}", prot.key.1);
+                continue;
+            };
+
+            let entry: Message = serde_json::from_slice(entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            let Descriptor::ProtocolsConfigure(desc) = &entry.descriptor else {
+                continue;
+            };
+
+            if !authorized && !desc.definition.published {
+                continue;
+            }
+
+            if filter.matches(&entry) {
+                found.push(entry);
+            }
+        }
+
         Ok(found)
     }
 
     fn delete(&self, ds: &dyn DataStore, target: &Did, message: Message) -> Result<(), StoreError> {
-        let Descriptor::RecordsDelete(desc) = message.descriptor else {
+        let Descriptor::RecordsDelete(desc) = &message.descriptor else {
             panic!("invalid message descriptor: {:?}", message.descriptor)
         };
 
@@ -99,43 +292,46 @@ impl RecordStore for NativeDbStore<'_> {
             .rw_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
+        let target_str = target.to_string();
         let mut data_cids = Vec::new();
 
-        if let Some(initial_entry) = tx
-            .get()
-            .primary::<InitialEntry>((target.to_string(), desc.record_id.clone()))
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-        {
-            let entry: Message = serde_json::from_slice(&initial_entry.entry)
-                .map_err(|e| StoreError::BackendError(e.to_string()))?;
-
-            if let Descriptor::RecordsWrite(desc) = entry.descriptor
-                && let Some(cid) = &desc.data_cid
-            {
-                data_cids.push(cid.clone());
-            };
+        let sync_before = sync_state(&tx, &target_str, &desc.record_id)?;
+        let initial_entry = remove_record(&tx, target, &desc.record_id, &mut data_cids)?;
 
-            tx.remove(initial_entry)
-                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        let protocol = match &initial_entry {
+            Some(Message {
+                descriptor: Descriptor::RecordsWrite(desc),
+                ..
+            }) => desc.protocol.clone(),
+            _ => None,
         };
 
-        if let Some(latest_entry) = tx
-            .get()
-            .primary::<LatestEntry>((target.to_string(), desc.record_id))
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        if desc.prune == Some(true)
+            && let Some(initial_entry) = &initial_entry
         {
-            let entry: Message = serde_json::from_slice(&latest_entry.entry)
-                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+            let descendants = descendant_ids(
+                tx.scan()
+                    .primary::<RecordContext>()
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    .start_with((target_str.clone(), descendant_prefix(initial_entry), ""))
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                &target_str,
+                initial_entry,
+            )?;
+
+            for record_id in descendants {
+                debug!("pruning {}", record_id);
+                let before = sync_state(&tx, &target_str, &record_id)?;
+                remove_record(&tx, target, &record_id, &mut data_cids)?;
+                insert_tombstone(&tx, target, &record_id, &message)?;
+                update_sync_tree(&tx, &target_str, &record_id, before)?;
+            }
+        }
 
-            if let Descriptor::RecordsWrite(desc) = &entry.descriptor
-                && let Some(cid) = &desc.data_cid
-            {
-                data_cids.push(cid.clone());
-            };
+        insert_tombstone(&tx, target, &desc.record_id, &message)?;
+        update_sync_tree(&tx, &target_str, &desc.record_id, sync_before)?;
 
-            tx.remove(latest_entry)
-                .map_err(|e| StoreError::BackendError(e.to_string()))?;
-        };
+        append_message(&tx, target, &message, protocol)?;
 
         tx.commit()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
@@ -147,308 +343,570 @@ impl RecordStore for NativeDbStore<'_> {
         Ok(())
     }
 
-    fn prepare_sync(&self, target: &Did, authorized: bool) -> Result<RecordsSync, StoreError> {
-        debug!("syncing {}", target);
-
+    fn read_tombstone(&self, target: &Did, record_id: &str) -> Result<Option<Message>, StoreError> {
         let tx = self
             .0
             .r_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        let initial_entries = tx
-            .scan()
-            .primary::<InitialEntry>()
+        tx.get()
+            .primary::<Tombstone>((target.to_string(), record_id))
             .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .start_with((target.to_string(), "".to_string()))
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .filter(|res| {
-                let Ok(entry) = res.as_ref().map(|r| &r.entry) else {
-                    warn!("Failed to read record during scan {}", target);
-                    return false;
-                };
-
-                let entry: Message = serde_json::from_slice(entry)
-                    .map_err(|e| StoreError::BackendError(e.to_string()))
-                    .unwrap();
-
-                let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
-                    panic!("invalid descriptor: {:?}", entry.descriptor);
-                };
-
-                if !authorized && (desc.published != Some(true)) {
-                    return false;
-                }
-
-                true
-            })
-            .map(|r| r.unwrap().entry)
-            .collect::<Vec<_>>();
-
-        let records = initial_entries
-            .into_iter()
-            .flat_map(|initial_entry| {
-                let initial_entry: Message = serde_json::from_slice(&initial_entry)
-                    .map_err(|e| StoreError::BackendError(e.to_string()))
-                    .unwrap();
-
-                tx.get()
-                    .primary::<LatestEntry>((target.to_string(), initial_entry.record_id.clone()))
-                    .map(|res| {
-                        let Some(latest_entry) = res.map(|r| r.entry) else {
-                            warn!(
-                                "Latest entry not found for initial entry: {}",
-                                initial_entry.record_id
-                            );
-                            return Err(StoreError::BackendError(
-                                "Missing latest entry".to_string(),
-                            ));
-                        };
-
-                        let latest_entry: Message = serde_json::from_slice(&latest_entry)
-                            .map_err(|e| StoreError::BackendError(e.to_string()))
-                            .unwrap();
-
-                        Ok(RecordId {
-                            record_id: initial_entry.record_id,
-                            latest_entry_id: latest_entry
-                                .descriptor
-                                .compute_entry_id()
-                                .map_err(|e| StoreError::BackendError(e.to_string()))?,
-                        })
-                    })
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(RecordsSync::new(records))
+            .map(|v| serde_json::from_slice(&v.entry))
+            .transpose()
+            .map_err(|e| StoreError::BackendError(e.to_string()))
     }
 
-    fn query(
+    fn read_descendants(
         &self,
         target: &Did,
-        filter: &RecordFilter,
-        authorized: bool,
-    ) -> Result<Vec<Message>, StoreError> {
-        debug!("querying {}", target);
-
+        ancestor: &Message,
+    ) -> Result<Vec<Record>, StoreError> {
         let tx = self
             .0
             .r_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        let mut found = tx
-            .scan()
-            .primary::<LatestEntry>()
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .start_with((target.to_string(), "".to_string()))
-            .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .filter(|res| {
-                let Ok(entry) = res.as_ref().map(|r| &r.entry) else {
-                    warn!("Failed to read record during scan {}", target);
-                    return false;
-                };
-
-                let entry: Message = serde_json::from_slice(entry)
-                    .map_err(|e| StoreError::BackendError(e.to_string()))
-                    .unwrap();
-
-                let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
-                    panic!("invalid descriptor: {:?}", entry.descriptor);
-                };
-
-                if !authorized && (desc.published != Some(true)) {
-                    return false;
-                }
-
-                if let Some(attester) = &filter.attester {
-                    match &entry.attestation {
-                        Some(jws) => {
-                            if !jws.signatures.iter().any(|s| s.header.kid.did == *attester) {
-                                return false;
-                            }
-                        }
-                        None => return false,
-                    }
-                }
-
-                if let Some(_recipient) = &filter.recipient {
-                    // TODO
-                }
+        let target_str = target.to_string();
 
-                if let Some(schema) = filter.schema.as_deref()
-                    && desc.schema.as_deref() != Some(schema)
-                {
-                    return false;
-                }
+        let ids = descendant_ids(
+            tx.scan()
+                .primary::<RecordContext>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .start_with((target_str.clone(), descendant_prefix(ancestor), ""))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            &target_str,
+            ancestor,
+        )?;
 
-                if let Some(record_id) = filter.record_id.as_deref()
-                    && entry.record_id != record_id
-                {
-                    return false;
-                }
+        let mut found = Vec::new();
 
-                if let Some(parent_id) = filter.parent_id.as_deref() {
-                    let Some(context_id) = entry.context_id.as_deref() else {
-                        return false;
-                    };
+        for record_id in ids {
+            if let Some(record) = read_record(&tx, target, &record_id)? {
+                found.push(record);
+            }
+        }
 
-                    let Some(context_parent) = context_id.split("/").last() else {
-                        return false;
-                    };
+        Ok(found)
+    }
 
-                    if context_parent != parent_id {
-                        return false;
-                    }
-                }
+    fn query_messages(
+        &self,
+        target: &Did,
+        filter: &MessagesFilter,
+        cursor: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<MessageLogEntry>, Option<u64>), StoreError> {
+        debug!("querying messages {}", target);
 
-                if let Some(protocol) = filter.protocol.as_deref()
-                    && desc.protocol.as_deref() != Some(protocol)
-                {
-                    return false;
-                }
+        let limit = limit
+            .unwrap_or(MAX_MESSAGES_QUERY_LIMIT)
+            .clamp(1, MAX_MESSAGES_QUERY_LIMIT);
 
-                if let Some(path) = filter.protocol_path.as_ref()
-                    && desc.protocol_path.as_ref() != Some(path)
-                {
-                    return false;
-                }
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-                if let Some(version) = filter.protocol_version.as_ref() {
-                    let Some(desc_version) = &desc.protocol_version else {
-                        return false;
-                    };
+        let start = cursor.map(|c| c.saturating_add(1)).unwrap_or_default();
+        let target = target.to_string();
 
-                    let req = VersionReq::parse(&format!("^{version}")).expect("parse version req");
+        let mut found: Vec<MessageLogEntry> = Vec::new();
 
-                    if !req.matches(desc_version) {
-                        return false;
-                    }
-                }
+        for res in filter_keys(
+            tx.scan()
+                .primary::<MessageLog>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .range((target.clone(), start)..=(target.clone(), u64::MAX))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            |log| log.key.0 == target,
+        ) {
+            let Ok(log) = res else {
+                warn!("Failed to read message during scan {}", target);
+                continue;
+            };
 
-                if let Some(data_format) = &filter.data_format
-                    && desc.data_format.as_ref() != Some(data_format)
-                {
-                    return false;
-                }
+            // A further message remains, so the page is full.
+            if found.len() >= limit {
+                let next = found.last().map(|m| m.cursor);
+                return Ok((found, next));
+            }
 
-                if let Some(date_created) = &filter.date_created {
-                    if desc.message_timestamp < date_created.from {
-                        return false;
-                    }
-                    if desc.message_timestamp > date_created.to {
-                        return false;
-                    }
-                }
+            if let Some(protocol) = filter.protocol.as_deref()
+                && log.protocol.as_deref() != Some(protocol)
+            {
+                continue;
+            }
 
-                true
-            })
-            .map(|r| r.unwrap().entry)
-            .collect::<Vec<_>>();
+            let message: Message = serde_json::from_slice(&log.entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        found.sort_by(|a, b| {
-            let a: Message = serde_json::from_slice(a)
-                .map_err(|e| StoreError::BackendError(e.to_string()))
-                .unwrap();
-            let b: Message = serde_json::from_slice(b)
-                .map_err(|e| StoreError::BackendError(e.to_string()))
-                .unwrap();
+            if let Some(interface) = &filter.interface
+                && message.descriptor.interface() != *interface
+            {
+                continue;
+            }
 
-            match filter.date_sort.unwrap_or_default() {
-                DateSort::Ascending => a
-                    .descriptor
-                    .message_timestamp()
-                    .cmp(&b.descriptor.message_timestamp()),
-                DateSort::Descending => b
-                    .descriptor
-                    .message_timestamp()
-                    .cmp(&a.descriptor.message_timestamp()),
+            if let Some(method) = &filter.method
+                && message.descriptor.method() != *method
+            {
+                continue;
             }
-        });
 
-        let found = found
-            .into_iter()
-            .map(|x| {
-                let x: Message = serde_json::from_slice(&x)
-                    .map_err(|e| StoreError::BackendError(e.to_string()))
-                    .unwrap();
-                x
-            })
-            .collect();
+            found.push(MessageLogEntry {
+                cursor: log.key.1,
+                message_cid: log.message_cid,
+                message,
+            });
+        }
 
-        Ok(found)
+        Ok((found, None))
     }
 
-    fn read(
+    fn read_message(
         &self,
         ds: &dyn DataStore,
         target: &Did,
-        record_id: &str,
-    ) -> Result<Option<Record>, StoreError> {
-        debug!("reading {}", record_id);
+        message_cid: &str,
+    ) -> Result<Option<MessageLogEntry>, StoreError> {
+        debug!("reading message {}", message_cid);
 
         let tx = self
             .0
             .r_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        let Some(initial_entry) = tx
+        let Some(cursor) = tx
             .get()
-            .primary::<InitialEntry>((target.to_string(), record_id))
+            .primary::<MessageCid>((target.to_string(), message_cid))
             .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .map(|v| v.entry)
+            .map(|v| v.cursor)
         else {
             return Ok(None);
         };
 
-        let initial_entry: Message = serde_json::from_slice(&initial_entry)
-            .map_err(|e| StoreError::BackendError(e.to_string()))
-            .unwrap();
-
-        let Some(latest_entry) = tx
+        let Some(log) = tx
             .get()
-            .primary::<LatestEntry>((target.to_string(), record_id))
+            .primary::<MessageLog>((target.to_string(), cursor))
             .map_err(|e| StoreError::BackendError(e.to_string()))?
-            .map(|v| v.entry)
         else {
-            error!("Found initial entry with no latest entry.");
+            error!("Found message cid with no log entry.");
             return Ok(None);
         };
 
-        let mut latest_entry: Message = serde_json::from_slice(&latest_entry)
-            .map_err(|e| StoreError::BackendError(e.to_string()))
-            .unwrap();
+        let mut message: Message = serde_json::from_slice(&log.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        if let Descriptor::RecordsWrite(desc) = &latest_entry.descriptor
-            && let Some(cid) = &desc.data_cid
-        {
-            latest_entry.data = ds.read(target, cid)?;
-        }
+        message.data = read_entry_data(&tx, ds, target, &message)?;
 
-        Ok(Some(Record {
-            initial_entry,
-            latest_entry,
+        Ok(Some(MessageLogEntry {
+            cursor,
+            message_cid: log.message_cid,
+            message,
         }))
     }
 
-    fn write(
-        &self,
-        ds: &dyn DataStore,
-        target: &Did,
-        mut message: Message,
-    ) -> Result<(), StoreError> {
-        debug!("writing {}", message.record_id);
+    fn write_permission(&self, target: &Did, message: Message) -> Result<(), StoreError> {
+        debug!("writing permission {}", message.record_id);
 
         let tx = self
             .0
             .rw_transaction()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
-        let cid = if let Descriptor::RecordsWrite(desc) = &message.descriptor {
-            desc.data_cid.clone()
-        } else {
-            None
+        let protocol = match &message.descriptor {
+            Descriptor::PermissionsRequest(desc) => {
+                tx.insert(Permission {
+                    key: (target.to_string(), message.record_id.clone()),
+                    entry: serde_json::to_vec(&message)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                })
+                .map_err(insert_error)?;
+
+                desc.scope.protocol.clone()
+            }
+            Descriptor::PermissionsGrant(desc) => {
+                tx.insert(Permission {
+                    key: (target.to_string(), message.record_id.clone()),
+                    entry: serde_json::to_vec(&message)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                })
+                .map_err(insert_error)?;
+
+                desc.scope.protocol.clone()
+            }
+            Descriptor::PermissionsRevoke(desc) => {
+                tx.insert(PermissionRevocation {
+                    key: (target.to_string(), desc.permissions_grant_id.clone()),
+                    entry: serde_json::to_vec(&message)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                })
+                .map_err(insert_error)?;
+
+                let grant = tx
+                    .get()
+                    .primary::<Permission>((target.to_string(), desc.permissions_grant_id.clone()))
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    .map(|v| serde_json::from_slice::<Message>(&v.entry))
+                    .transpose()
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                match grant.map(|m| m.descriptor) {
+                    Some(Descriptor::PermissionsGrant(grant)) => grant.scope.protocol,
+                    _ => None,
+                }
+            }
+            _ => panic!("invalid message descriptor: {:?}", message.descriptor),
         };
 
-        let data = message.data.take();
+        append_message(&tx, target, &message, protocol)?;
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn read_permission(
+        &self,
+        target: &Did,
+        permission_id: &str,
+    ) -> Result<Option<Message>, StoreError> {
+        debug!("reading permission {}", permission_id);
+
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.get()
+            .primary::<Permission>((target.to_string(), permission_id))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .map(|v| serde_json::from_slice(&v.entry))
+            .transpose()
+            .map_err(|e| StoreError::BackendError(e.to_string()))
+    }
+
+    fn read_permission_revocation(
+        &self,
+        target: &Did,
+        permissions_grant_id: &str,
+    ) -> Result<Option<Message>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.get()
+            .primary::<PermissionRevocation>((target.to_string(), permissions_grant_id))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .map(|v| serde_json::from_slice(&v.entry))
+            .transpose()
+            .map_err(|e| StoreError::BackendError(e.to_string()))
+    }
+
+    fn prepare_sync(&self, target: &Did, authorized: bool) -> Result<RecordsSync, StoreError> {
+        debug!("syncing {}", target);
+
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let target_str = target.to_string();
+        let mut records = Vec::new();
+
+        for res in filter_keys(
+            tx.scan()
+                .primary::<InitialEntry>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .start_with((target_str.clone(), "".to_string()))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            |initial| initial.key.0 == target_str,
+        ) {
+            let Ok(initial) = res else {
+                warn!("Failed to read record during scan {}", target);
+                continue;
+            };
+
+            let initial_entry: Message = serde_json::from_slice(&initial.entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            let Descriptor::RecordsWrite(desc) = &initial_entry.descriptor else {
+                panic!("invalid descriptor: {:?}", initial_entry.descriptor);
+            };
+
+            if !authorized && (desc.published != Some(true)) {
+                continue;
+            }
+
+            let Some(latest_entry) = tx
+                .get()
+                .primary::<LatestEntry>((target_str.clone(), initial_entry.record_id.clone()))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .map(|r| r.entry)
+            else {
+                warn!(
+                    "Latest entry not found for initial entry: {}",
+                    initial_entry.record_id
+                );
+                return Err(StoreError::BackendError("Missing latest entry".to_string()));
+            };
+
+            let latest_entry: Message = serde_json::from_slice(&latest_entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            records.push(RecordId {
+                record_id: initial_entry.record_id,
+                latest_entry_id: latest_entry
+                    .descriptor
+                    .compute_entry_id()
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            });
+        }
+
+        // Tombstones reveal record ids, so are only shared when authorized.
+        let mut tombstones = Vec::new();
+
+        if authorized {
+            for res in filter_keys(
+                tx.scan()
+                    .primary::<Tombstone>()
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    .start_with((target_str.clone(), "".to_string()))
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                |tombstone| tombstone.key.0 == target_str,
+            ) {
+                let Ok(tombstone) = res else {
+                    warn!("Failed to read tombstone during scan {}", target);
+                    continue;
+                };
+
+                let entry: Message = serde_json::from_slice(&tombstone.entry)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                tombstones.push(RecordId {
+                    record_id: tombstone.key.1,
+                    latest_entry_id: entry
+                        .descriptor
+                        .compute_entry_id()
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?,
+                });
+            }
+        }
+
+        Ok(RecordsSync::new(records, tombstones))
+    }
+
+    fn sync_nodes(
+        &self,
+        target: &Did,
+        prefixes: &[String],
+        authorized: bool,
+    ) -> Result<Vec<SyncNode>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let view = if authorized {
+            SYNC_VIEW_ALL
+        } else {
+            SYNC_VIEW_PUBLISHED
+        };
+
+        prefixes
+            .iter()
+            .map(|prefix| {
+                let node = tx
+                    .get()
+                    .primary::<SyncTreeNode>((target.to_string(), view.to_string(), prefix.clone()))
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                Ok(match node {
+                    Some(node) => SyncNode::new(prefix.clone(), &node.hash, node.count),
+                    None => SyncNode::new(prefix.clone(), &[0; 32], 0),
+                })
+            })
+            .collect()
+    }
+
+    fn query(
+        &self,
+        target: &Did,
+        filter: &RecordFilter,
+        pagination: Option<&Pagination>,
+        authorized: bool,
+    ) -> Result<Vec<Record>, StoreError> {
+        debug!("querying {}", target);
+
+        let cursor = match pagination.and_then(|p| p.cursor.as_deref()) {
+            Some(c) => Some(
+                QueryCursor::decode(c)
+                    .ok_or_else(|| StoreError::InvalidInput(format!("invalid cursor: {c}")))?,
+            ),
+            None => None,
+        };
+
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let target_str = target.to_string();
+        let sort = filter.date_sort.unwrap_or_default();
+        let limit = pagination.and_then(|p| p.limit);
+        let cursor = cursor.map(|c| {
+            (
+                target_str.clone(),
+                encode_order_timestamp(&c.message_timestamp),
+                c.record_id,
+            )
+        });
+
+        let order_scan = tx
+            .scan()
+            .primary::<RecordOrder>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        // Candidate order keys, in the sort direction.
+        let keys: Box<dyn Iterator<Item = (String, String, String)>> = match &filter.tags {
+            Some(tags) if !tags.is_empty() => {
+                let mut keys = Vec::new();
+
+                for record_id in query_tag_index(&tx, target, tags)? {
+                    if let Some(key) = read_order_key(&tx, target, &record_id)? {
+                        keys.push(key);
+                    }
+                }
+
+                keys.sort();
+
+                if sort == DateSort::Descending {
+                    keys.reverse();
+                }
+
+                let keys = keys.into_iter().filter(|key| match &cursor {
+                    Some(cursor) if sort == DateSort::Ascending => key > cursor,
+                    Some(cursor) => key < cursor,
+                    None => true,
+                });
+
+                Box::new(keys)
+            }
+            _ => {
+                // Encoded timestamps are hex, so sort before "g".
+                let (start, end) = match (&cursor, sort) {
+                    (Some(cursor), DateSort::Ascending) => (
+                        cursor.clone(),
+                        (target_str.clone(), "g".to_string(), "".to_string()),
+                    ),
+                    (Some(cursor), DateSort::Descending) => (
+                        (target_str.clone(), "".to_string(), "".to_string()),
+                        cursor.clone(),
+                    ),
+                    (None, _) => (
+                        (target_str.clone(), "".to_string(), "".to_string()),
+                        (target_str.clone(), "g".to_string(), "".to_string()),
+                    ),
+                };
+
+                let rows = order_scan
+                    .range(start..end)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                let rows: Box<dyn Iterator<Item = _>> = match sort {
+                    DateSort::Ascending => Box::new(rows),
+                    DateSort::Descending => Box::new(rows.rev()),
+                };
+
+                let keys = filter_keys(rows, |row| {
+                    row.key.0 == target_str && Some(&row.key) != cursor.as_ref()
+                })
+                .filter_map(|res| match res {
+                    Ok(row) => Some(row.key),
+                    Err(_) => {
+                        warn!("Failed to read record order during scan {}", target);
+                        None
+                    }
+                });
+
+                Box::new(keys)
+            }
+        };
+
+        let mut found = Vec::new();
+
+        for (_, _, record_id) in keys {
+            if limit.is_some_and(|l| found.len() >= l) {
+                break;
+            }
+
+            let Some(record) = read_record(&tx, target, &record_id)? else {
+                continue;
+            };
+
+            let Descriptor::RecordsWrite(desc) = &record.latest_entry.descriptor else {
+                panic!("invalid descriptor: {:?}", record.latest_entry.descriptor);
+            };
+
+            if !authorized && (desc.published != Some(true)) {
+                continue;
+            }
+
+            if filter.matches_record(&record) {
+                found.push(record);
+            }
+        }
+
+        Ok(found)
+    }
+
+    fn read(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        record_id: &str,
+    ) -> Result<Option<Record>, StoreError> {
+        debug!("reading {}", record_id);
+
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let Some(mut record) = read_record(&tx, target, record_id)? else {
+            return Ok(None);
+        };
+
+        record.latest_entry.data = read_entry_data(&tx, ds, target, &record.latest_entry)?;
+
+        Ok(Some(record))
+    }
+
+    fn write(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        mut message: Message,
+    ) -> Result<(), StoreError> {
+        debug!("writing {}", message.record_id);
+
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let cid = if let Descriptor::RecordsWrite(desc) = &message.descriptor {
+            desc.data_cid.clone()
+        } else {
+            None
+        };
+
+        let mut data = message.data.take();
+
+        let target_str = target.to_string();
+        let sync_before = sync_state(&tx, &target_str, &message.record_id)?;
 
         let prev = tx
             .upsert(LatestEntry {
@@ -457,6 +915,16 @@ impl RecordStore for NativeDbStore<'_> {
             })
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
+        let prev = prev
+            .map(|p| serde_json::from_slice::<Message>(&p.entry))
+            .transpose()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        if let Some(prev) = &prev {
+            remove_tags(&tx, target, prev)?;
+        }
+        insert_tags(&tx, target, &message)?;
+
         if prev.is_none() {
             debug_assert_eq!(
                 message.record_id,
@@ -468,8 +936,57 @@ impl RecordStore for NativeDbStore<'_> {
                 entry: serde_json::to_vec(&message).unwrap(),
             })
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            tx.insert(order_row(target.to_string(), &message))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            if let Some(row) = context_row(target.to_string(), &message) {
+                tx.insert(row)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+            }
+        }
+
+        let protocol = match &message.descriptor {
+            Descriptor::RecordsWrite(desc) => desc.protocol.clone(),
+            _ => None,
+        };
+        append_message(&tx, target, &message, protocol)?;
+
+        // Keep the entry in the record's history.
+        let entry_id = message
+            .descriptor
+            .compute_entry_id()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        let history_key = (target.to_string(), message.record_id.clone(), entry_id);
+
+        let new_history = tx
+            .get()
+            .primary::<RecordEntry>(history_key.clone())
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .is_none();
+
+        if new_history {
+            // Encrypted data is shared by CID, but its key is wrapped per entry.
+            if let Some(Data::Encrypted(jwe)) = &mut data {
+                tx.insert(EntryRecipients {
+                    key: history_key.clone(),
+                    recipients: serde_json::to_vec(&jwe.recipients).unwrap(),
+                })
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                jwe.recipients.clear();
+            }
+
+            tx.insert(RecordEntry {
+                key: history_key,
+                entry: serde_json::to_vec(&message)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            })
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
         }
 
+        update_sync_tree(&tx, &target_str, &message.record_id, sync_before)?;
+
         tx.commit()
             .map_err(|e| StoreError::BackendError(e.to_string()))?;
 
@@ -479,20 +996,1069 @@ impl RecordStore for NativeDbStore<'_> {
             // Add a reference for LatestEntry.
             ds.add_ref(target, &cid, data)?;
 
-            // Remove previous reference.
-            if let Some(prev) = prev {
-                let prev: Message = serde_json::from_slice(&prev.entry)
-                    .map_err(|e| StoreError::BackendError(e.to_string()))
-                    .unwrap();
+            // Add a reference for the history entry.
+            if new_history {
+                ds.add_ref(target, &cid, None)?;
+            }
 
-                if let Descriptor::RecordsWrite(desc) = prev.descriptor
-                    && let Some(prev_cid) = &desc.data_cid
-                {
-                    ds.remove_ref(target, prev_cid)?;
-                }
+            // Remove previous reference.
+            if let Some(prev) = prev
+                && let Descriptor::RecordsWrite(desc) = prev.descriptor
+                && let Some(prev_cid) = &desc.data_cid
+            {
+                ds.remove_ref(target, prev_cid)?;
             }
         }
 
         Ok(())
     }
+
+    fn read_history(&self, target: &Did, record_id: &str) -> Result<Vec<Message>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let target = target.to_string();
+        let mut found = Vec::new();
+
+        for res in filter_keys(
+            tx.scan()
+                .primary::<RecordEntry>()
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .start_with((target.clone(), record_id.to_string(), "".to_string()))
+                .map_err(|e| StoreError::BackendError(e.to_string()))?,
+            |entry| entry.key.0 == target && entry.key.1 == record_id,
+        ) {
+            let Ok(entry) = res else {
+                warn!("Failed to read record entry during scan");
+                continue;
+            };
+
+            let message: Message = serde_json::from_slice(&entry.entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+            found.push((entry, message));
+        }
+
+        sort_history(&mut found);
+
+        Ok(found.into_iter().map(|(_, m)| m).collect())
+    }
+
+    fn read_entry(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        record_id: &str,
+        entry_id: &str,
+    ) -> Result<Option<Message>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let Some(entry) = tx
+            .get()
+            .primary::<RecordEntry>((target.to_string(), record_id, entry_id))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        let mut message: Message = serde_json::from_slice(&entry.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        message.data = read_entry_data(&tx, ds, target, &message)?;
+
+        Ok(Some(message))
+    }
+
+    fn prune_history(
+        &self,
+        ds: &dyn DataStore,
+        target: &Did,
+        record_id: &str,
+        max_history: usize,
+    ) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let Some(latest_entry) = tx
+            .get()
+            .primary::<LatestEntry>((target.to_string(), record_id))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        else {
+            return Ok(());
+        };
+
+        let latest_id = serde_json::from_slice::<Message>(&latest_entry.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .descriptor
+            .compute_entry_id()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let mut past = scan_history(&tx, target, record_id)?;
+        past.retain(|(entry, _)| entry.key.2 != latest_id);
+
+        let excess = past.len().saturating_sub(max_history);
+        let mut data_cids = Vec::new();
+
+        for (entry, message) in past.into_iter().take(excess) {
+            if let Descriptor::RecordsWrite(desc) = &message.descriptor
+                && let Some(cid) = &desc.data_cid
+            {
+                data_cids.push(cid.clone());
+            }
+
+            remove_entry(&tx, entry)?;
+        }
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        for cid in data_cids {
+            ds.remove_ref(target, &cid)?;
+        }
+
+        Ok(())
+    }
+
+    fn push_outbox(&self, target: &Did, remote: &str, message: Message) -> Result<u64, StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let id = tx
+            .scan()
+            .primary::<OutboxEntry>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .all()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .next_back()
+            .transpose()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .map(|entry| entry.key + 1)
+            .unwrap_or_default();
+
+        let item = OutboxItem {
+            id,
+            target: target.clone(),
+            remote: remote.to_string(),
+            message,
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            next_attempt: OffsetDateTime::now_utc(),
+            last_error: None,
+        };
+
+        tx.insert(OutboxEntry {
+            key: id,
+            item: serde_json::to_vec(&item).map_err(|e| StoreError::BackendError(e.to_string()))?,
+        })
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(id)
+    }
+
+    fn read_outbox(&self) -> Result<Vec<OutboxItem>, StoreError> {
+        let tx = self
+            .0
+            .r_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.scan()
+            .primary::<OutboxEntry>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .all()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .map(|res| {
+                let entry = res.map_err(|e| StoreError::BackendError(e.to_string()))?;
+                serde_json::from_slice(&entry.item)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))
+            })
+            .collect()
+    }
+
+    fn update_outbox(&self, item: &OutboxItem) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.upsert(OutboxEntry {
+            key: item.id,
+            item: serde_json::to_vec(item).map_err(|e| StoreError::BackendError(e.to_string()))?,
+        })
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn remove_outbox(&self, id: u64) -> Result<(), StoreError> {
+        let tx = self
+            .0
+            .rw_transaction()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        if let Some(entry) = tx
+            .get()
+            .primary::<OutboxEntry>(id)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            tx.remove(entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Maps an insert error, rejecting rows that already exist rather than
+/// overwriting them.
+fn insert_error(e: native_db::db_type::Error) -> StoreError {
+    match e {
+        native_db::db_type::Error::DuplicateKey { key_name } => {
+            StoreError::InvalidInput(format!("duplicate key: {key_name}"))
+        }
+        e => StoreError::BackendError(e.to_string()),
+    }
+}
+
+/// Filters scanned rows to those whose key fields `matches`.
+///
+/// Keys are concatenated, so a prefix or range scan can also return rows whose
+/// fields only share a prefix with the scanned ones (e.g. `did:a` and `did:ab`).
+/// Rows that fail to decode are passed through for the caller to handle.
+fn filter_keys<T>(
+    scan: impl Iterator<Item = native_db::db_type::Result<T>>,
+    matches: impl Fn(&T) -> bool,
+) -> impl Iterator<Item = native_db::db_type::Result<T>> {
+    scan.filter(move |res| res.as_ref().is_err() || res.as_ref().is_ok_and(&matches))
+}
+
+/// Reads the initial and latest entries of a record, without data.
+fn read_record(
+    tx: &RTransaction,
+    target: &Did,
+    record_id: &str,
+) -> Result<Option<Record>, StoreError> {
+    let Some(initial_entry) = tx
+        .get()
+        .primary::<InitialEntry>((target.to_string(), record_id))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+        .map(|v| v.entry)
+    else {
+        return Ok(None);
+    };
+
+    let initial_entry: Message = serde_json::from_slice(&initial_entry)
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    let Some(latest_entry) = tx
+        .get()
+        .primary::<LatestEntry>((target.to_string(), record_id))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+        .map(|v| v.entry)
+    else {
+        error!("Found initial entry with no latest entry.");
+        return Ok(None);
+    };
+
+    let latest_entry: Message = serde_json::from_slice(&latest_entry)
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    Ok(Some(Record {
+        initial_entry,
+        latest_entry,
+    }))
+}
+
+/// Query order row of a record, keyed by its initial entry.
+fn order_row(target: String, initial_entry: &Message) -> RecordOrder {
+    let timestamp = initial_entry
+        .descriptor
+        .message_timestamp()
+        .map(encode_order_timestamp)
+        .unwrap_or_default();
+
+    RecordOrder {
+        key: (target, timestamp, initial_entry.record_id.clone()),
+    }
+}
+
+/// Context index row of a record, keyed by its initial entry.
+/// Records outside a protocol context are not indexed.
+fn context_row(target: String, initial_entry: &Message) -> Option<RecordContext> {
+    Some(RecordContext {
+        key: (
+            target,
+            initial_entry.context_id.clone()?,
+            initial_entry.record_id.clone(),
+        ),
+    })
+}
+
+/// Context id prefix shared by every descendant of a record.
+fn descendant_prefix(ancestor: &Message) -> String {
+    match &ancestor.context_id {
+        Some(c) => format!("{c}/{}", ancestor.record_id),
+        None => ancestor.record_id.clone(),
+    }
+}
+
+/// Returns the ids of the descendants of a record,
+/// from a context index scan starting with its [descendant_prefix].
+fn descendant_ids(
+    scan: impl Iterator<Item = native_db::db_type::Result<RecordContext>>,
+    target: &str,
+    ancestor: &Message,
+) -> Result<Vec<String>, StoreError> {
+    let prefix = descendant_prefix(ancestor);
+
+    filter_keys(scan, |row| {
+        row.key.0 == target
+            && row
+                .key
+                .1
+                .strip_prefix(&prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+    .map(|res| {
+        res.map(|row| row.key.2)
+            .map_err(|e| StoreError::BackendError(e.to_string()))
+    })
+    .collect()
+}
+
+/// Reads the query order key of a record.
+fn read_order_key(
+    tx: &RTransaction,
+    target: &Did,
+    record_id: &str,
+) -> Result<Option<(String, String, String)>, StoreError> {
+    let Some(initial_entry) = tx
+        .get()
+        .primary::<InitialEntry>((target.to_string(), record_id))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let initial_entry: Message = serde_json::from_slice(&initial_entry.entry)
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    Ok(Some(order_row(target.to_string(), &initial_entry).key))
+}
+
+/// Encodes a timestamp as fixed-width hex, so keys sort in time order.
+pub(crate) fn encode_order_timestamp(timestamp: &OffsetDateTime) -> String {
+    let nanos = timestamp.unix_timestamp_nanos() as u128 ^ (1 << 127);
+    format!("{nanos:032x}")
+}
+
+/// Returns the history entries of a record, in timestamp order.
+fn scan_history(
+    tx: &RwTransaction,
+    target: &Did,
+    record_id: &str,
+) -> Result<Vec<(RecordEntry, Message)>, StoreError> {
+    let target = target.to_string();
+    let mut found = Vec::new();
+
+    for res in filter_keys(
+        tx.scan()
+            .primary::<RecordEntry>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .start_with((target.clone(), record_id.to_string(), "".to_string()))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?,
+        |entry| entry.key.0 == target && entry.key.1 == record_id,
+    ) {
+        let entry = res.map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        let message: Message = serde_json::from_slice(&entry.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        found.push((entry, message));
+    }
+
+    sort_history(&mut found);
+
+    Ok(found)
+}
+
+/// Sorts history entries by message timestamp, then entry id.
+fn sort_history(entries: &mut [(RecordEntry, Message)]) {
+    entries.sort_by(|(a_entry, a), (b_entry, b)| {
+        a.descriptor
+            .message_timestamp()
+            .cmp(&b.descriptor.message_timestamp())
+            .then_with(|| a_entry.key.2.cmp(&b_entry.key.2))
+    });
+}
+
+/// Removes the initial and latest entries and history of a record, returning the
+/// initial entry if it existed. Referenced data CIDs are added to `data_cids`.
+fn remove_record(
+    tx: &RwTransaction,
+    target: &Did,
+    record_id: &str,
+    data_cids: &mut Vec<String>,
+) -> Result<Option<Message>, StoreError> {
+    let mut found = None;
+
+    if let Some(initial_entry) = tx
+        .get()
+        .primary::<InitialEntry>((target.to_string(), record_id.to_string()))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+    {
+        let entry: Message = serde_json::from_slice(&initial_entry.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.remove(initial_entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        tx.remove(order_row(target.to_string(), &entry))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        if let Some(row) = context_row(target.to_string(), &entry) {
+            tx.remove(row)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+
+        found = Some(entry);
+    };
+
+    if let Some(latest_entry) = tx
+        .get()
+        .primary::<LatestEntry>((target.to_string(), record_id.to_string()))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+    {
+        let entry: Message = serde_json::from_slice(&latest_entry.entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        if let Descriptor::RecordsWrite(desc) = &entry.descriptor
+            && let Some(cid) = &desc.data_cid
+        {
+            data_cids.push(cid.clone());
+        };
+
+        remove_tags(tx, target, &entry)?;
+
+        tx.remove(latest_entry)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+    };
+
+    for (entry, message) in scan_history(tx, target, record_id)? {
+        if let Descriptor::RecordsWrite(desc) = &message.descriptor
+            && let Some(cid) = &desc.data_cid
+        {
+            data_cids.push(cid.clone());
+        }
+
+        remove_entry(tx, entry)?;
+    }
+
+    Ok(found)
+}
+
+/// Removes a history entry, along with its data recipients.
+fn remove_entry(tx: &RwTransaction, entry: RecordEntry) -> Result<(), StoreError> {
+    if let Some(recipients) = tx
+        .get()
+        .primary::<EntryRecipients>(entry.key.clone())
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+    {
+        tx.remove(recipients)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+    }
+
+    tx.remove(entry)
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Reads the data of a `RecordsWrite` entry.
+/// Encrypted data is returned with the entry's own recipients.
+fn read_entry_data(
+    tx: &RTransaction,
+    ds: &dyn DataStore,
+    target: &Did,
+    message: &Message,
+) -> Result<Option<Data>, StoreError> {
+    let Descriptor::RecordsWrite(desc) = &message.descriptor else {
+        return Ok(None);
+    };
+
+    let Some(cid) = &desc.data_cid else {
+        return Ok(None);
+    };
+
+    let mut data = ds.read(target, cid)?;
+
+    if let Some(Data::Encrypted(jwe)) = &mut data {
+        let entry_id = message
+            .descriptor
+            .compute_entry_id()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        // Data stored before recipients were kept per entry still includes them.
+        if let Some(found) = tx
+            .get()
+            .primary::<EntryRecipients>((target.to_string(), message.record_id.clone(), entry_id))
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            jwe.recipients = serde_json::from_slice(&found.recipients)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+    }
+
+    Ok(data)
+}
+
+/// Sync tree view of every record and tombstone, for authorized syncs.
+const SYNC_VIEW_ALL: &str = "all";
+/// Sync tree view of published records only.
+const SYNC_VIEW_PUBLISHED: &str = "published";
+
+/// Latest entry id of a record in each sync tree view, if it is included.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SyncState {
+    all: Option<String>,
+    published: Option<String>,
+}
+
+/// Reads how a record is currently included in the sync tree.
+/// Matches [RecordStore::prepare_sync]: records take precedence over tombstones,
+/// and unauthorized views only include records with a published initial entry.
+fn sync_state(tx: &RwTransaction, target: &str, record_id: &str) -> Result<SyncState, StoreError> {
+    let key = (target.to_string(), record_id.to_string());
+
+    let latest = tx
+        .get()
+        .primary::<LatestEntry>(key.clone())
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+        .map(|latest| entry_id(&latest.entry))
+        .transpose()?;
+
+    let Some(latest) = latest else {
+        let tombstone = tx
+            .get()
+            .primary::<Tombstone>(key)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+            .map(|tombstone| entry_id(&tombstone.entry))
+            .transpose()?;
+
+        return Ok(SyncState {
+            all: tombstone,
+            published: None,
+        });
+    };
+
+    let published = match tx
+        .get()
+        .primary::<InitialEntry>(key)
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+    {
+        Some(initial) => {
+            let initial: Message = serde_json::from_slice(&initial.entry)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+            matches!(
+                &initial.descriptor,
+                Descriptor::RecordsWrite(desc) if desc.published == Some(true)
+            )
+        }
+        None => false,
+    };
+
+    Ok(SyncState {
+        published: published.then(|| latest.clone()),
+        all: Some(latest),
+    })
+}
+
+fn entry_id(entry: &[u8]) -> Result<String, StoreError> {
+    let message: Message =
+        serde_json::from_slice(entry).map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    message
+        .descriptor
+        .compute_entry_id()
+        .map_err(|e| StoreError::BackendError(e.to_string()))
+}
+
+/// Updates the sync tree nodes above a record, given its state before the change.
+fn update_sync_tree(
+    tx: &RwTransaction,
+    target: &str,
+    record_id: &str,
+    before: SyncState,
+) -> Result<(), StoreError> {
+    let after = sync_state(tx, target, record_id)?;
+    let key = sync_key(record_id);
+
+    for (view, before, after) in [
+        (SYNC_VIEW_ALL, before.all, after.all),
+        (SYNC_VIEW_PUBLISHED, before.published, after.published),
+    ] {
+        if before == after {
+            continue;
+        }
+
+        for len in 0..=SYNC_TREE_DEPTH {
+            let node_key = (target.to_string(), view.to_string(), key[..len].to_string());
+
+            let mut node = tx
+                .get()
+                .primary::<SyncTreeNode>(node_key.clone())
+                .map_err(|e| StoreError::BackendError(e.to_string()))?
+                .unwrap_or(SyncTreeNode {
+                    key: node_key,
+                    hash: vec![0; 32],
+                    count: 0,
+                });
+
+            if let Some(entry_id) = &before {
+                xor_hash(&mut node.hash, &sync_leaf_hash(&key, entry_id));
+                node.count = node.count.saturating_sub(1);
+            }
+
+            if let Some(entry_id) = &after {
+                xor_hash(&mut node.hash, &sync_leaf_hash(&key, entry_id));
+                node.count += 1;
+            }
+
+            if node.count == 0 {
+                if tx
+                    .get()
+                    .primary::<SyncTreeNode>(node.key.clone())
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?
+                    .is_some()
+                {
+                    tx.remove(node)
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+                }
+            } else {
+                tx.upsert(node)
+                    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn insert_tombstone(
+    tx: &RwTransaction,
+    target: &Did,
+    record_id: &str,
+    message: &Message,
+) -> Result<(), StoreError> {
+    tx.upsert(Tombstone {
+        key: (target.to_string(), record_id.to_string()),
+        entry: serde_json::to_vec(message).map_err(|e| StoreError::BackendError(e.to_string()))?,
+    })
+    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Encodes a tag value for the tag index.
+/// Values are prefixed by type, so string prefixes can be scanned directly,
+/// and numbers are encoded so they sort in numeric order.
+fn encode_tag_value(value: &TagValue) -> String {
+    match value {
+        TagValue::Bool(b) => format!("b:{b}"),
+        TagValue::Number(n) => encode_tag_number(sortable_bits(n.as_f64().unwrap_or_default())),
+        TagValue::String(s) => format!("s:{s}"),
+    }
+}
+
+fn encode_tag_number(bits: u64) -> String {
+    format!("n:{bits:016x}")
+}
+
+/// Maps a number to bits that sort in numeric order.
+/// Non-negative numbers have the sign bit set, negative numbers are inverted.
+fn sortable_bits(n: f64) -> u64 {
+    // Adding zero normalizes -0.0 to 0.0.
+    let bits = (n + 0.0).to_bits();
+
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+/// Inclusive range of sortable bits matching a numeric tag filter.
+/// Returns `None` for non-numeric filters.
+fn tag_number_bounds(filter: &TagFilter) -> Option<(u64, u64)> {
+    let bits = |n: &serde_json::Number| n.as_f64().map(sortable_bits);
+
+    match filter {
+        TagFilter::Equal(TagValue::Number(n)) => Some(bits(n).map_or((1, 0), |b| (b, b))),
+        TagFilter::Range(range) => {
+            let mut start = Some(0);
+            let mut end = Some(u64::MAX);
+
+            if let Some(gt) = range.gt.as_ref().and_then(bits) {
+                start = start.max(gt.checked_add(1));
+            }
+            if let Some(gte) = range.gte.as_ref().and_then(bits) {
+                start = start.max(Some(gte));
+            }
+            if let Some(lt) = range.lt.as_ref().and_then(bits) {
+                end = end.min(lt.checked_sub(1));
+            }
+            if let Some(lte) = range.lte.as_ref().and_then(bits) {
+                end = end.min(Some(lte));
+            }
+
+            // An overflowing bound matches nothing.
+            match (start, end) {
+                (Some(start), Some(end)) => Some((start, end)),
+                _ => Some((1, 0)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Index rows for the tags of a `RecordsWrite`.
+fn tag_rows(target: &Did, message: &Message) -> Vec<RecordTag> {
+    let Descriptor::RecordsWrite(desc) = &message.descriptor else {
+        return Vec::new();
+    };
+
+    desc.tags
+        .iter()
+        .flatten()
+        .map(|(name, value)| RecordTag {
+            key: (
+                target.to_string(),
+                name.clone(),
+                encode_tag_value(value),
+                message.record_id.clone(),
+            ),
+        })
+        .collect()
+}
+
+fn insert_tags(tx: &RwTransaction, target: &Did, message: &Message) -> Result<(), StoreError> {
+    for row in tag_rows(target, message) {
+        tx.upsert(row)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn remove_tags(tx: &RwTransaction, target: &Did, message: &Message) -> Result<(), StoreError> {
+    for row in tag_rows(target, message) {
+        if let Some(row) = tx
+            .get()
+            .primary::<RecordTag>(row.key)
+            .map_err(|e| StoreError::BackendError(e.to_string()))?
+        {
+            tx.remove(row)
+                .map_err(|e| StoreError::BackendError(e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the ids of records matching every tag predicate, using the tag index.
+/// Candidates must still be checked against the full filter.
+fn query_tag_index(
+    tx: &RTransaction,
+    target: &Did,
+    tags: &BTreeMap<String, TagFilter>,
+) -> Result<HashSet<String>, StoreError> {
+    let target = target.to_string();
+    let mut found: Option<HashSet<String>> = None;
+
+    for (name, filter) in tags {
+        let scan = tx
+            .scan()
+            .primary::<RecordTag>()
+            .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+        // Numbers are scanned as a range of encoded values, everything else by prefix.
+        let (rows, start, end): (Box<dyn Iterator<Item = _>>, _, _) =
+            match tag_number_bounds(filter) {
+                Some((start, end)) if start > end => {
+                    found = Some(HashSet::new());
+                    continue;
+                }
+                Some((start, end)) => {
+                    let start = encode_tag_number(start);
+                    let end = end
+                        .checked_add(1)
+                        .map(encode_tag_number)
+                        .unwrap_or_else(|| "n;".to_string());
+
+                    let rows = scan
+                        .range(
+                            (target.clone(), name.clone(), start.clone(), "".to_string())
+                                ..(target.clone(), name.clone(), end.clone(), "".to_string()),
+                        )
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                    (Box::new(rows), start, Some(end))
+                }
+                None => {
+                    let prefix = match filter {
+                        TagFilter::StartsWith { starts_with } => format!("s:{starts_with}"),
+                        TagFilter::Equal(value) => encode_tag_value(value),
+                        TagFilter::Range(_) => unreachable!("ranges are numeric"),
+                    };
+
+                    let rows = scan
+                        .start_with((target.clone(), name.clone(), prefix.clone(), "".to_string()))
+                        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+                    (Box::new(rows), prefix, None)
+                }
+            };
+
+        let mut ids = HashSet::new();
+
+        for res in filter_keys(rows, |row| {
+            row.key.0 == target
+                && row.key.1 == *name
+                && match &end {
+                    Some(end) => row.key.2 >= start && row.key.2 < *end,
+                    None => row.key.2.starts_with(&start),
+                }
+        }) {
+            let Ok(row) = res else {
+                warn!("Failed to read tag during scan {}", target);
+                continue;
+            };
+
+            if found.as_ref().is_none_or(|f| f.contains(&row.key.3)) {
+                ids.insert(row.key.3);
+            }
+        }
+
+        found = Some(ids);
+    }
+
+    Ok(found.unwrap_or_default())
+}
+
+/// Appends a message to the target's message log.
+fn append_message(
+    tx: &RwTransaction,
+    target: &Did,
+    message: &Message,
+    protocol: Option<String>,
+) -> Result<(), StoreError> {
+    let target = target.to_string();
+
+    let message_cid = message
+        .descriptor
+        .compute_entry_id()
+        .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    // Replayed messages are already in the log.
+    if tx
+        .get()
+        .primary::<MessageCid>((target.clone(), message_cid.clone()))
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let cursor = tx
+        .get()
+        .primary::<MessageLogHead>(target.clone())
+        .map_err(|e| StoreError::BackendError(e.to_string()))?
+        .map(|head| head.cursor + 1)
+        .unwrap_or_default();
+
+    let mut message = message.clone();
+    message.data = None;
+
+    tx.upsert(MessageLogHead {
+        key: target.clone(),
+        cursor,
+    })
+    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    tx.upsert(MessageCid {
+        key: (target.clone(), message_cid.clone()),
+        cursor,
+    })
+    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    tx.insert(MessageLog {
+        key: (target, cursor),
+        message_cid,
+        protocol,
+        entry: serde_json::to_vec(&message).map_err(|e| StoreError::BackendError(e.to_string()))?,
+    })
+    .map_err(|e| StoreError::BackendError(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use dwn_core::message::{
+        Version,
+        descriptor::{
+            ProtocolDefinition, ProtocolsConfigureBuilder, RecordsDeleteBuilder,
+            RecordsWriteBuilder, SyncTree,
+        },
+        mime::TEXT_PLAIN,
+    };
+    use serde_json::json;
+    use xdid::core::did::{MethodId, MethodName};
+
+    use super::*;
+
+    #[test]
+    fn test_prepare_sync_target_prefix() {
+        let store = NativeDbStore::new_in_memory().unwrap();
+
+        let target = |id: &str| Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId(id.to_string()),
+        };
+
+        let msg = RecordsWriteBuilder {
+            data_format: Some(TEXT_PLAIN),
+            data: Some("Hello, world!".as_bytes().to_vec()),
+            published: Some(true),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        store.write(&store, &target("ab"), msg).unwrap();
+
+        // Records of a target whose DID extends another's are not included.
+        let sync = store.prepare_sync(&target("a"), true).unwrap();
+        assert!(sync.local_records.is_empty());
+
+        let sync = store.prepare_sync(&target("ab"), true).unwrap();
+        assert_eq!(sync.local_records.len(), 1);
+    }
+
+    #[test]
+    fn test_sync_nodes_match_tree() {
+        let store = NativeDbStore::new_in_memory().unwrap();
+
+        let target = Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId("a".to_string()),
+        };
+
+        let write = |record_id: Option<String>, published: bool, data: &str| {
+            let msg = RecordsWriteBuilder {
+                record_id,
+                data_format: Some(TEXT_PLAIN),
+                data: Some(data.as_bytes().to_vec()),
+                published: Some(published),
+                ..Default::default()
+            }
+            .build()
+            .unwrap();
+            store.write(&store, &target, msg.clone()).unwrap();
+            msg.record_id
+        };
+
+        let published = write(None, true, "a");
+        let private = write(None, false, "b");
+        write(Some(published.clone()), true, "c");
+        write(None, true, "d");
+
+        let delete = RecordsDeleteBuilder::new(private).build().unwrap();
+        store.delete(&store, &target, delete).unwrap();
+
+        // Incrementally updated nodes match a tree built from scratch.
+        for authorized in [true, false] {
+            let sync = store.prepare_sync(&target, authorized).unwrap();
+            let tree = SyncTree::new(sync.local_tombstones.into_iter().chain(sync.local_records));
+
+            let key = sync_key(&published);
+            let prefixes = (0..=SYNC_TREE_DEPTH)
+                .map(|len| key[..len].to_string())
+                .collect::<Vec<_>>();
+
+            let nodes = store.sync_nodes(&target, &prefixes, authorized).unwrap();
+            let expected = prefixes.iter().map(|p| tree.node(p)).collect::<Vec<_>>();
+            assert_eq!(nodes, expected);
+        }
+
+        let nodes = store.sync_nodes(&target, &[String::new()], true).unwrap();
+        assert_eq!(nodes[0].count, 3);
+
+        let nodes = store.sync_nodes(&target, &[String::new()], false).unwrap();
+        assert_eq!(nodes[0].count, 2);
+    }
+
+    #[test]
+    fn test_replayed_messages_logged_once() {
+        let store = NativeDbStore::new_in_memory().unwrap();
+
+        let target = Did {
+            method_name: MethodName("test".into()),
+            method_id: MethodId("a".to_string()),
+        };
+
+        let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+            "protocol": "my-protocol",
+            "published": true,
+            "types": {},
+            "structure": {}
+        }))
+        .unwrap();
+        let configure = ProtocolsConfigureBuilder::new(Version::new(1, 0, 0), definition)
+            .build()
+            .unwrap();
+
+        let write = RecordsWriteBuilder {
+            data_format: Some(TEXT_PLAIN),
+            data: Some("Hello, world!".as_bytes().to_vec()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        let delete = RecordsDeleteBuilder::new(write.record_id.clone())
+            .build()
+            .unwrap();
+
+        store.write(&store, &target, write).unwrap();
+
+        for _ in 0..2 {
+            store
+                .configure_protocol(&target, configure.clone())
+                .unwrap();
+            store.delete(&store, &target, delete.clone()).unwrap();
+        }
+
+        let (found, cursor) = store
+            .query_messages(&target, &MessagesFilter::default(), None, None)
+            .unwrap();
+        assert_eq!(found.len(), 3);
+        assert!(cursor.is_none());
+    }
 }
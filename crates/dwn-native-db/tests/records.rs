@@ -13,3 +13,42 @@ fn test_nativedb_write_read() {
     assert_eq!(found.initial_entry, msg);
     assert_eq!(found.latest_entry, msg);
 }
+
+#[test]
+fn test_nativedb_read_descendants() {
+    let did = P256KeyPair::generate().public().to_did();
+    let store = dwn_native_db::NativeDbStore::new_in_memory().unwrap();
+
+    let write = |context_id: Option<String>| {
+        let msg = RecordsWriteBuilder {
+            context_id,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        store.write(&store, &did, msg.clone()).unwrap();
+        msg
+    };
+
+    let root = write(None);
+    let other = write(None);
+    let child = write(Some(root.record_id.clone()));
+    let grandchild = write(Some(format!("{}/{}", root.record_id, child.record_id)));
+    write(Some(other.record_id.clone()));
+
+    let mut found = store
+        .read_descendants(&did, &root)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.initial_entry.record_id)
+        .collect::<Vec<_>>();
+    found.sort();
+
+    let mut expected = vec![child.record_id.clone(), grandchild.record_id.clone()];
+    expected.sort();
+    assert_eq!(found, expected);
+
+    let found = store.read_descendants(&did, &child).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].latest_entry, grandchild);
+}
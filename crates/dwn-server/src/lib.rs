@@ -5,21 +5,32 @@
 //! crate.
 
 use std::{
+    convert::Infallible,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    ops::Range,
     str::FromStr,
     sync::LazyLock,
 };
 
 use axum::{
     Json, Router,
+    body::{Body, Bytes},
     extract::{Path, State},
-    http::StatusCode,
+    http::{
+        HeaderMap, StatusCode,
+        header::{CONTENT_RANGE, RANGE},
+    },
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
     routing::put,
 };
 use axum_macros::debug_handler;
 use directories::ProjectDirs;
 use dwn::{Dwn, core::message::Message};
-use tokio::net::TcpListener;
+use futures_util::{Stream, stream};
+use tokio::{net::TcpListener, sync::mpsc};
 use tracing::{debug, error, info};
 use xdid::core::did::Did;
 
@@ -62,15 +73,158 @@ pub async fn run_server(opts: DwnServerOptions) -> anyhow::Result<()> {
 pub fn create_router(dwn: Dwn) -> Router {
     Router::new()
         .route("/{target}", put(handle_put))
+        .route("/{target}/subscribe", put(handle_subscribe))
+        .route("/{target}/blocks/{cid}", put(handle_put_block))
+        .route("/{target}/data", put(handle_read_data))
         .with_state(dwn)
 }
 
 #[debug_handler]
 async fn handle_put(
-    Path(mut target): Path<String>,
+    Path(target): Path<String>,
     State(dwn): State<Dwn>,
     Json(msg): Json<Message>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
+    // debug!("-> PUT {target}");
+
+    let target = parse_target(target)?;
+
+    let reply = dwn.process_message(&target, msg).await?;
+
+    let res = serde_json::to_value(reply).map_err(|e| {
+        error!("Error serializing response: {e:?}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // debug!("<- {res}");
+
+    Ok(Json(res))
+}
+
+/// Streams messages matching a `RecordsSubscribe` as server-sent events.
+#[debug_handler]
+async fn handle_subscribe(
+    Path(target): Path<String>,
+    State(dwn): State<Dwn>,
+    Json(msg): Json<Message>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let target = parse_target(target)?;
+
+    let subscription = dwn.subscribe(&target, msg).await?;
+
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        loop {
+            let msg = subscription.recv().await?;
+
+            match Event::default().json_data(&msg) {
+                Ok(event) => return Some((Ok(event), subscription)),
+                Err(e) => error!("Error serializing event: {e:?}"),
+            }
+        }
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Stores a unixfs block of record data.
+/// Uploads are unauthenticated, so blocks are only kept once a record references them,
+/// or until [`Dwn::block_ttl`] expires.
+#[debug_handler]
+async fn handle_put_block(
+    Path((target, cid)): Path<(String, String)>,
+    State(dwn): State<Dwn>,
+    block: Bytes,
+) -> Result<(), StatusCode> {
+    let target = parse_target(target)?;
+
+    dwn.write_block(&target, &cid, block.to_vec())
+}
+
+/// Streams the data of the record read by a `RecordsRead`.
+/// A single byte range may be requested with the `Range` header.
+#[debug_handler]
+async fn handle_read_data(
+    Path(target): Path<String>,
+    State(dwn): State<Dwn>,
+    headers: HeaderMap,
+    Json(msg): Json<Message>,
+) -> Result<Response, StatusCode> {
+    let target = parse_target(target)?;
+
+    let range = parse_range(&headers);
+
+    let reader = dwn
+        .read_data(&target, msg, range.clone())
+        .await?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if range.is_none() {
+        return Ok(Body::from_stream(read_blocking(reader)).into_response());
+    }
+
+    let size = reader.size();
+    let read = reader.range().clone();
+
+    if read.is_empty() {
+        return Ok((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(CONTENT_RANGE, format!("bytes */{size}"))],
+        )
+            .into_response());
+    }
+
+    Ok((
+        StatusCode::PARTIAL_CONTENT,
+        [(
+            CONTENT_RANGE,
+            format!("bytes {}-{}/{size}", read.start, read.end - 1),
+        )],
+        Body::from_stream(read_blocking(reader)),
+    )
+        .into_response())
+}
+
+/// Number of data chunks read ahead of the response body.
+const READ_AHEAD_CHUNKS: usize = 4;
+
+/// Streams an iterator from a blocking thread, as data store reads block.
+/// Reading stops once the receiving body is dropped.
+fn read_blocking<T: Send + 'static>(
+    iter: impl Iterator<Item = T> + Send + 'static,
+) -> impl Stream<Item = T> {
+    let (tx, mut rx) = mpsc::channel(READ_AHEAD_CHUNKS);
+
+    tokio::task::spawn_blocking(move || {
+        for item in iter {
+            if tx.blocking_send(item).is_err() {
+                break;
+            }
+        }
+    });
+
+    stream::poll_fn(move |cx| rx.poll_recv(cx))
+}
+
+/// Parses a single `bytes=start-end` or `bytes=start-` range.
+/// Other forms are ignored, and the full data is returned.
+fn parse_range(headers: &HeaderMap) -> Option<Range<u64>> {
+    let value = headers.get(RANGE)?.to_str().ok()?;
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+
+    let start = start.trim().parse::<u64>().ok()?;
+    let end = match end.trim() {
+        "" => u64::MAX,
+        end => end.parse::<u64>().ok()?.checked_add(1)?,
+    };
+
+    if end <= start {
+        return None;
+    }
+
+    Some(start..end)
+}
+
+fn parse_target(mut target: String) -> Result<Did, StatusCode> {
     if target.starts_with("did:web:") {
         // Axum automatically decodes percent-encoded paths.
         // However, for did:web if a port is included the colon must remain percent-encoded.
@@ -96,21 +250,8 @@ async fn handle_put(
         }
     }
 
-    // debug!("-> PUT {target}");
-
-    let target = Did::from_str(&target).map_err(|e| {
+    Did::from_str(&target).map_err(|e| {
         debug!("Failed to parse DID: {:?}", e);
         StatusCode::BAD_REQUEST
-    })?;
-
-    let reply = dwn.process_message(&target, msg).await?;
-
-    let res = serde_json::to_value(reply).map_err(|e| {
-        error!("Error serializing response: {e:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    // debug!("<- {res}");
-
-    Ok(Json(res))
+    })
 }
@@ -0,0 +1,91 @@
+use std::convert::Infallible;
+
+use dwn::core::message::mime::APPLICATION_OCTET_STREAM;
+use futures_util::{TryStreamExt, stream};
+use tracing_test::traced_test;
+use utils::init_remote_test;
+
+mod utils;
+
+#[tokio::test]
+#[traced_test]
+async fn test_upload_read_remote() {
+    let (actor, ..) = init_remote_test().await;
+
+    let data = (0..1_000_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let chunks = data
+        .chunks(64 * 1024)
+        .map(|c| Ok::<_, Infallible>(c.to_vec()))
+        .collect::<Vec<_>>();
+
+    let cid = actor
+        .upload(stream::iter(chunks))
+        .send_remote()
+        .await
+        .unwrap();
+
+    let record_id = actor
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .send_remote()
+        .await
+        .unwrap();
+
+    let read = actor
+        .read(record_id)
+        .send_remote_data()
+        .await
+        .unwrap()
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap()
+        .concat();
+    assert_eq!(read, data);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_read_range_remote() {
+    let (actor, ..) = init_remote_test().await;
+
+    let data = (0..1_000_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let chunks = data
+        .chunks(64 * 1024)
+        .map(|c| Ok::<_, Infallible>(c.to_vec()))
+        .collect::<Vec<_>>();
+
+    let cid = actor
+        .upload(stream::iter(chunks))
+        .send_remote()
+        .await
+        .unwrap();
+
+    let record_id = actor
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .send_remote()
+        .await
+        .unwrap();
+
+    let read = actor
+        .read(record_id.clone())
+        .range(300_000..500_000)
+        .send_remote_data()
+        .await
+        .unwrap()
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap()
+        .concat();
+    assert_eq!(read, data[300_000..500_000]);
+
+    // Range starts after the data.
+    let res = actor
+        .read(record_id)
+        .range(2_000_000..3_000_000)
+        .send_remote_data()
+        .await;
+    assert!(res.is_err());
+}
@@ -0,0 +1,150 @@
+use std::{net::SocketAddr, time::Duration};
+
+use dwn::{
+    Dwn,
+    core::{
+        message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN},
+        store::{OutboxStatus, RecordStore},
+    },
+    remote::Remote,
+    stores::NativeDbStore,
+};
+use serde_json::json;
+use tokio::net::TcpListener;
+use tracing_test::traced_test;
+use utils::init_remote_test;
+use xdid::methods::web::reqwest::Url;
+
+mod utils;
+
+#[tokio::test]
+#[traced_test]
+async fn test_outbox_offline_write() {
+    let (mut actor, dwn, _) = init_remote_test().await;
+
+    let port = port_check::free_local_port().unwrap();
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let url = Url::parse(&format!("http://{addr}")).unwrap();
+    actor.remotes = vec![Remote::primary(url)];
+
+    // Writes succeed while the remote is offline.
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+
+    // Processing only queues the message.
+    let outbox = actor.outbox().unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].status, OutboxStatus::Pending);
+    assert_eq!(outbox[0].attempts, 0);
+    assert!(outbox[0].last_error.is_none());
+
+    actor.flush_outbox().await.unwrap();
+
+    let outbox = actor.outbox().unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].status, OutboxStatus::Pending);
+    assert_eq!(outbox[0].attempts, 1);
+    assert!(outbox[0].last_error.is_some());
+
+    // Bring the remote online.
+    let remote = NativeDbStore::new_in_memory().unwrap();
+    let router = dwn_server::create_router(Dwn::from(remote.clone()));
+    let listener = TcpListener::bind(addr).await.unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let task = actor.spawn_outbox();
+
+    for _ in 0..100 {
+        if actor.outbox().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    task.abort();
+
+    assert!(actor.outbox().unwrap().is_empty());
+
+    let found = remote
+        .read(&remote, &actor.did, &record_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        found.latest_entry.data,
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .unwrap()
+            .latest_entry
+            .data
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_outbox_rejected() {
+    let (actor, _, _) = init_remote_test().await;
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    actor
+        .configure_protocol(version.clone(), definition.clone())
+        .sync(false)
+        .process()
+        .await
+        .unwrap();
+
+    // The remote has not configured the protocol, so rejects the record.
+    actor
+        .write()
+        .protocol(definition.protocol, version, "my-value".to_string())
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    actor.flush_outbox().await.unwrap();
+
+    let outbox = actor.outbox().unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].status, OutboxStatus::Failed);
+    assert_eq!(outbox[0].attempts, 1);
+
+    // Failed items are not retried.
+    actor.flush_outbox().await.unwrap();
+    assert_eq!(actor.outbox().unwrap(), outbox);
+
+    actor.discard_outbox_item(outbox[0].id).unwrap();
+    assert!(actor.outbox().unwrap().is_empty());
+}
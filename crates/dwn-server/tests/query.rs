@@ -0,0 +1,65 @@
+use dwn::core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use futures_util::TryStreamExt;
+use serde_json::json;
+use tracing_test::traced_test;
+use utils::init_remote_test;
+
+mod utils;
+
+#[tokio::test]
+#[traced_test]
+async fn test_stream_remote() {
+    let (actor, ..) = init_remote_test().await;
+
+    for i in 0..3 {
+        actor
+            .write()
+            .data(TEXT_PLAIN, format!("record {i}").into_bytes())
+            .process()
+            .await
+            .unwrap();
+    }
+
+    actor.flush_outbox().await.unwrap();
+
+    let found = actor
+        .query()
+        .limit(2)
+        .stream_remote()
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 3);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_protocols_remote() {
+    let (actor, ..) = init_remote_test().await;
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {},
+        "structure": {}
+    }))
+    .unwrap();
+
+    actor
+        .configure_protocol(Version::new(1, 0, 0), definition)
+        .process()
+        .await
+        .unwrap();
+
+    actor.flush_outbox().await.unwrap();
+
+    let found = actor
+        .query_protocols()
+        .auth(false)
+        .send_remote()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].definition.protocol, "my-protocol");
+}
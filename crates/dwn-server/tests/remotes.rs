@@ -0,0 +1,165 @@
+use dwn::{
+    Actor, Dwn,
+    core::{
+        message::{descriptor::RecordsWriteBuilder, mime::TEXT_PLAIN},
+        store::{OutboxStatus, RecordStore},
+    },
+    remote::{Remote, RemoteRole},
+    stores::NativeDbStore,
+};
+use tracing_test::traced_test;
+use utils::{init_remote_test, start_dwn_server};
+use xdid::methods::web::reqwest::Url;
+
+mod utils;
+
+/// Adds a mirror remote to the actor.
+async fn add_mirror(actor: &mut Actor) -> NativeDbStore<'static> {
+    let store = NativeDbStore::new_in_memory().unwrap();
+    let url = start_dwn_server(Dwn::from(store.clone())).await;
+    actor.remotes.push(Remote::mirror(url));
+    store
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_write_fan_out() {
+    let (mut actor, _, primary) = init_remote_test().await;
+    let mirror = add_mirror(&mut actor).await;
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    actor.flush_outbox().await.unwrap();
+
+    for store in [&primary, &mirror] {
+        assert!(store.read(store, &actor.did, &record_id).unwrap().is_some());
+    }
+
+    actor.delete(record_id.clone()).process().await.unwrap();
+    actor.flush_outbox().await.unwrap();
+
+    for store in [&primary, &mirror] {
+        assert!(store.read(store, &actor.did, &record_id).unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_write_mirror_unavailable() {
+    let (mut actor, dwn, primary) = init_remote_test().await;
+
+    let port = port_check::free_local_port().unwrap();
+    let url = Url::parse(&format!("http://127.0.0.1:{port}")).unwrap();
+    actor.remotes.push(Remote::mirror(url.clone()));
+
+    let (record_id, results) = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .send_remotes()
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].remote.role, RemoteRole::Primary);
+    assert!(results[0].result.is_ok());
+    assert_eq!(results[1].remote.url, url);
+    assert!(results[1].result.is_err());
+
+    assert!(
+        primary
+            .read(&primary, &actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+
+    // Local processing succeeds while the mirror is down.
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_write_primary_unavailable() {
+    let (mut actor, dwn, _) = init_remote_test().await;
+
+    let port = port_check::free_local_port().unwrap();
+    let url = Url::parse(&format!("http://127.0.0.1:{port}")).unwrap();
+    actor.remotes.insert(0, Remote::primary(url.clone()));
+
+    // The write succeeds locally, and is queued for the unavailable remote.
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+
+    actor.flush_outbox().await.unwrap();
+
+    let outbox = actor.outbox().unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].remote, url.as_str());
+    assert_eq!(outbox[0].status, OutboxStatus::Pending);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_remotes_converge() {
+    let (mut actor, dwn, primary) = init_remote_test().await;
+    let mirror = add_mirror(&mut actor).await;
+
+    // Each remote has a record the other is missing.
+    let mut record_ids = Vec::new();
+
+    for store in [&primary, &mirror] {
+        let mut msg = RecordsWriteBuilder {
+            data_format: Some(TEXT_PLAIN),
+            data: Some("Hello, world!".as_bytes().to_vec()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        actor.authorize(&mut msg).unwrap();
+        record_ids.push(msg.record_id.clone());
+
+        store.write(store, &actor.did, msg).unwrap();
+    }
+
+    actor.sync().await.unwrap();
+
+    for record_id in &record_ids {
+        assert!(
+            dwn.record_store
+                .read(dwn.data_store.as_ref(), &actor.did, record_id)
+                .unwrap()
+                .is_some()
+        );
+
+        for store in [&primary, &mirror] {
+            assert!(store.read(store, &actor.did, record_id).unwrap().is_some());
+        }
+    }
+}
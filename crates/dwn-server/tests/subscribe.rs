@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use dwn::core::message::mime::TEXT_PLAIN;
+use tokio::time::timeout;
+use tracing_test::traced_test;
+use utils::init_remote_test;
+
+mod utils;
+
+#[tokio::test]
+#[traced_test]
+async fn test_remote_subscribe() {
+    let (actor, ..) = init_remote_test().await;
+
+    let mut subscription = actor.subscribe().send_remote().await.unwrap();
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    actor.flush_outbox().await.unwrap();
+
+    let found = timeout(Duration::from_secs(5), subscription.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.record_id, record_id);
+}
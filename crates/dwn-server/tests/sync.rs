@@ -1,7 +1,18 @@
-use dwn::core::{
-    message::{descriptor::RecordsWriteBuilder, mime::TEXT_PLAIN},
-    store::RecordStore,
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use dwn::{
+    Actor, Dwn,
+    core::{
+        message::{
+            Version,
+            data::Data,
+            descriptor::{ProtocolDefinition, RecordsDeleteBuilder, RecordsWriteBuilder, SyncTree},
+            mime::TEXT_PLAIN,
+        },
+        store::RecordStore,
+    },
+    stores::NativeDbStore,
 };
+use serde_json::json;
 use tracing_test::traced_test;
 use utils::init_remote_test;
 
@@ -19,6 +30,8 @@ async fn test_auto_sync_local_write() {
         .await
         .unwrap();
 
+    actor.flush_outbox().await.unwrap();
+
     let found = remote
         .read(dwn.data_store.as_ref(), &actor.did, &record_id)
         .unwrap()
@@ -76,6 +89,8 @@ async fn test_auto_sync_local_update() {
         .await
         .unwrap();
 
+    actor.flush_outbox().await.unwrap();
+
     let found = remote
         .read(dwn.data_store.as_ref(), &actor.did, &record_id)
         .unwrap()
@@ -98,9 +113,52 @@ async fn test_sync_remote_write() {
     actor.authorize(&mut msg).unwrap();
     let record_id = msg.record_id.clone();
 
-    remote
-        .write(dwn.data_store.as_ref(), &actor.did, msg.clone())
+    remote.write(&remote, &actor.did, msg.clone()).unwrap();
+
+    actor.sync().await.unwrap();
+
+    let found = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+        .unwrap()
         .unwrap();
+    assert_eq!(found.latest_entry, msg);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_tree_remote_change() {
+    let (actor, dwn, remote) = init_remote_test().await;
+
+    // Shared records.
+    for i in 0..100 {
+        let mut msg = RecordsWriteBuilder {
+            data_format: Some(TEXT_PLAIN),
+            data: Some(format!("record {i}").into_bytes()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        actor.authorize(&mut msg).unwrap();
+
+        dwn.record_store
+            .write(dwn.data_store.as_ref(), &actor.did, msg.clone())
+            .unwrap();
+        remote.write(&remote, &actor.did, msg).unwrap();
+    }
+
+    // Remote-only record.
+    let mut msg = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("Hello, world!".as_bytes().to_vec()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    actor.authorize(&mut msg).unwrap();
+    let record_id = msg.record_id.clone();
+
+    remote.write(&remote, &actor.did, msg.clone()).unwrap();
 
     actor.sync().await.unwrap();
 
@@ -110,4 +168,205 @@ async fn test_sync_remote_write() {
         .unwrap()
         .unwrap();
     assert_eq!(found.latest_entry, msg);
+
+    // Trees now match.
+    let local = dwn.record_store.prepare_sync(&actor.did, true).unwrap();
+    let remote = remote.prepare_sync(&actor.did, true).unwrap();
+    assert_eq!(
+        SyncTree::new(local.local_records).node(""),
+        SyncTree::new(remote.local_records).node("")
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_remote_data_cid_mismatch() {
+    let (actor, dwn, remote) = init_remote_test().await;
+
+    let mut msg = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("Hello, world!".as_bytes().to_vec()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    msg.data = Some(Data::Base64(
+        BASE64_URL_SAFE_NO_PAD.encode("Goodbye, world!".as_bytes()),
+    ));
+    actor.authorize(&mut msg).unwrap();
+    let record_id = msg.record_id.clone();
+
+    // Bypass validation on the remote.
+    remote.write(&remote, &actor.did, msg).unwrap();
+
+    actor.sync().await.unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_local_delete() {
+    let (actor, dwn, remote) = init_remote_test().await;
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    actor
+        .delete(record_id.clone())
+        .sync(false)
+        .process()
+        .await
+        .unwrap();
+
+    actor.sync().await.unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        remote
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        remote
+            .read_tombstone(&actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_remote_delete() {
+    let (actor, dwn, remote) = init_remote_test().await;
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let mut msg = RecordsDeleteBuilder::new(record_id.clone())
+        .build()
+        .unwrap();
+    actor.authorize(&mut msg).unwrap();
+
+    remote
+        .delete(dwn.data_store.as_ref(), &actor.did, msg)
+        .unwrap();
+
+    actor.sync().await.unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        remote
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_sync_protocol_records() {
+    let (actor, _, remote) = init_remote_test().await;
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    actor
+        .configure_protocol(version.clone(), definition.clone())
+        .sync(false)
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = actor
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "my-value".to_string(),
+        )
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .sync(false)
+        .process()
+        .await
+        .unwrap();
+
+    actor.sync().await.unwrap();
+
+    let found = remote
+        .query_protocol(&actor.did, definition.protocol.clone(), Vec::new(), true)
+        .unwrap();
+    assert_eq!(found, vec![(version.clone(), definition.clone())]);
+    assert!(
+        remote
+            .read(&remote, &actor.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+
+    // A new device syncs from the remote.
+    let dwn_2 = Dwn::from(NativeDbStore::new_in_memory().unwrap());
+    let mut actor_2 = Actor::new(actor.did.clone(), dwn_2.clone());
+    actor_2.remotes = actor.remotes.clone();
+    actor_2.auth_key = actor.auth_key.clone();
+    actor_2.sign_key = actor.sign_key.clone();
+
+    actor_2.sync().await.unwrap();
+
+    let found = dwn_2
+        .record_store
+        .query_protocol(&actor.did, definition.protocol.clone(), Vec::new(), true)
+        .unwrap();
+    assert_eq!(found, vec![(version, definition)]);
+
+    let found = dwn_2
+        .record_store
+        .read(dwn_2.data_store.as_ref(), &actor.did, &record_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        found.latest_entry.data,
+        Some(Data::Base64(BASE64_URL_SAFE_NO_PAD.encode("Hello, world!")))
+    );
 }
@@ -1,13 +1,13 @@
 use std::{net::SocketAddr, sync::Arc};
 
-use dwn::{Actor, Dwn, core::store::RecordStore, document_key::DocumentKey, stores::NativeDbStore};
+use dwn::{Actor, Dwn, document_key::DocumentKey, remote::Remote, stores::NativeDbStore};
 use tokio::net::TcpListener;
 use xdid::methods::{
     key::{DidKeyPair, PublicKey, p256::P256KeyPair},
     web::reqwest::Url,
 };
 
-pub async fn init_remote_test() -> (Actor, Dwn, impl RecordStore) {
+pub async fn init_remote_test() -> (Actor, Dwn, NativeDbStore<'static>) {
     let remote_store = NativeDbStore::new_in_memory().unwrap();
     let remote_dwn = Dwn::from(remote_store.clone());
     let remote = start_dwn_server(remote_dwn).await;
@@ -19,7 +19,7 @@ pub async fn init_remote_test() -> (Actor, Dwn, impl RecordStore) {
     let did = key.public().to_did();
 
     let mut actor = Actor::new(did, dwn.clone());
-    actor.remote = Some(remote);
+    actor.remotes = vec![Remote::primary(remote)];
 
     let key = Arc::<DocumentKey>::new(key.into());
     actor.auth_key = Some(key.clone());
@@ -0,0 +1,355 @@
+//! Record data encryption.
+//!
+//! Data is encrypted with `A256GCM` using a random content encryption key,
+//! which is wrapped for each recipient using `ECDH-ES+A256KW`.
+//! Recipients can be added later without re-encrypting the data.
+
+use aes_kw::{KeyInit, KwAes256};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use dwn_core::message::{
+    ContentEncryption, Jwe, JweHeader, JweProtectedHeader, JweRecipient, KeyEncryption,
+};
+use jose_jwk::{Ec, Jwk, Key, Parameters};
+use p256::{
+    PublicKey, SecretKey, ecdh::diffie_hellman, elliptic_curve::rand_core::OsRng,
+    pkcs8::DecodePrivateKey,
+};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    digest::{SHA256, digest},
+    rand::{SecureRandom, SystemRandom},
+};
+use thiserror::Error;
+use xdid::{
+    core::{ResolutionError, did::Did, did_url::DidUrl, document::VerificationMethodMap},
+    methods::key::{DidKeyPair, p256::P256KeyPair},
+    resolver::{DidResolver, MethodError},
+};
+
+const KEY_LEN: usize = 32;
+const WRAPPED_KEY_LEN: usize = KEY_LEN + 8;
+
+/// A [DID key agreement](https://www.w3.org/TR/did-core/#key-agreement) key,
+/// used to decrypt record data.
+pub struct EncryptionKey {
+    secret: SecretKey,
+    /// URL to the key.
+    pub url: DidUrl,
+}
+
+impl EncryptionKey {
+    pub fn new(url: DidUrl, secret: SecretKey) -> Self {
+        Self { secret, url }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.secret.public_key()
+    }
+}
+
+impl From<P256KeyPair> for EncryptionKey {
+    fn from(value: P256KeyPair) -> Self {
+        let pem = value.to_pkcs8_pem().expect("valid p256 key");
+        let secret = SecretKey::from_pkcs8_pem(&pem).expect("valid p256 key");
+
+        let did = xdid::methods::key::PublicKey::to_did(&value.public());
+        let fragment = did.to_string().strip_prefix("did:key:").unwrap().into();
+
+        let url = DidUrl {
+            did,
+            fragment: Some(fragment),
+            path_abempty: None,
+            query: None,
+        };
+
+        Self::new(url, secret)
+    }
+}
+
+/// Encrypts data for each recipient key.
+pub fn encrypt(data: &[u8], recipients: &[(DidUrl, PublicKey)]) -> Result<Jwe, EncryptionError> {
+    let rng = SystemRandom::new();
+
+    let mut cek = [0; KEY_LEN];
+    rng.fill(&mut cek).map_err(|_| EncryptionError::Random)?;
+
+    let mut iv = [0; NONCE_LEN];
+    rng.fill(&mut iv).map_err(|_| EncryptionError::Random)?;
+
+    let protected = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&JweProtectedHeader {
+        enc: ContentEncryption::A256Gcm,
+    })?);
+
+    let key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, &cek).map_err(|_| EncryptionError::Encrypt)?,
+    );
+
+    let mut ciphertext = data.to_vec();
+    let tag = key
+        .seal_in_place_separate_tag(
+            Nonce::assume_unique_for_key(iv),
+            Aad::from(protected.as_bytes()),
+            &mut ciphertext,
+        )
+        .map_err(|_| EncryptionError::Encrypt)?;
+
+    let recipients = recipients
+        .iter()
+        .map(|(kid, public)| wrap_key(&cek, kid.clone(), public))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Jwe {
+        protected,
+        recipients,
+        iv: BASE64_URL_SAFE_NO_PAD.encode(iv),
+        ciphertext: BASE64_URL_SAFE_NO_PAD.encode(ciphertext),
+        tag: BASE64_URL_SAFE_NO_PAD.encode(tag.as_ref()),
+    })
+}
+
+/// Decrypts data using the given key.
+/// Returns `None` if the key is not a recipient of the data.
+pub fn decrypt(jwe: &Jwe, key: &EncryptionKey) -> Result<Option<Vec<u8>>, EncryptionError> {
+    let Some(cek) = unwrap_cek(jwe, key)? else {
+        return Ok(None);
+    };
+
+    let header: JweProtectedHeader =
+        serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(&jwe.protected)?)?;
+    let ContentEncryption::A256Gcm = header.enc;
+
+    let iv: [u8; NONCE_LEN] = BASE64_URL_SAFE_NO_PAD
+        .decode(&jwe.iv)?
+        .try_into()
+        .map_err(|_| EncryptionError::Decrypt)?;
+
+    let mut in_out = BASE64_URL_SAFE_NO_PAD.decode(&jwe.ciphertext)?;
+    in_out.extend(BASE64_URL_SAFE_NO_PAD.decode(&jwe.tag)?);
+
+    let key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, &cek).map_err(|_| EncryptionError::Decrypt)?,
+    );
+
+    let plaintext = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(iv),
+            Aad::from(jwe.protected.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| EncryptionError::Decrypt)?;
+
+    Ok(Some(plaintext.to_vec()))
+}
+
+/// Wraps the content encryption key for a new recipient.
+/// `key` must already be a recipient of the data.
+pub fn add_recipient(
+    jwe: &mut Jwe,
+    key: &EncryptionKey,
+    kid: DidUrl,
+    public: &PublicKey,
+) -> Result<(), EncryptionError> {
+    if jwe.recipients.iter().any(|r| r.header.kid == kid) {
+        return Ok(());
+    }
+
+    let Some(cek) = unwrap_cek(jwe, key)? else {
+        return Err(EncryptionError::NotRecipient);
+    };
+
+    jwe.recipients.push(wrap_key(&cek, kid, public)?);
+
+    Ok(())
+}
+
+/// Resolves the [key agreement](https://www.w3.org/TR/did-core/#key-agreement) key of a DID.
+///
+/// `did:key` documents do not list key agreement methods,
+/// so the DID's own key is used instead.
+pub async fn resolve_key_agreement(did: &Did) -> Result<(DidUrl, PublicKey), EncryptionError> {
+    let resolver = DidResolver::new()?;
+    let document = resolver.resolve(did).await?;
+
+    let mut methods = document
+        .key_agreement
+        .iter()
+        .flatten()
+        .filter_map(|m| document.resolve_verification_method(m))
+        .collect::<Vec<_>>();
+
+    if methods.is_empty() && did.method_name.0 == "key" {
+        methods = document.verification_method.clone().unwrap_or_default();
+    }
+
+    methods
+        .into_iter()
+        .find_map(|m| {
+            let public = parse_public_key(&m)?;
+            Some((m.id, public))
+        })
+        .ok_or(EncryptionError::MissingKeyAgreement)
+}
+
+fn parse_public_key(method: &VerificationMethodMap) -> Option<PublicKey> {
+    let Key::Ec(ec) = &method.public_key_jwk.as_ref()?.key else {
+        return None;
+    };
+    PublicKey::try_from(ec).ok()
+}
+
+fn unwrap_cek(jwe: &Jwe, key: &EncryptionKey) -> Result<Option<[u8; KEY_LEN]>, EncryptionError> {
+    match jwe.recipients.iter().find(|r| r.header.kid == key.url) {
+        Some(recipient) => unwrap_key(recipient, &key.secret).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn wrap_key(
+    cek: &[u8; KEY_LEN],
+    kid: DidUrl,
+    public: &PublicKey,
+) -> Result<JweRecipient, EncryptionError> {
+    let alg = KeyEncryption::EcdhEsA256Kw;
+
+    let ephemeral = SecretKey::random(&mut OsRng);
+    let kek = derive_kek(&ephemeral, public, alg)?;
+
+    let mut buf = [0; WRAPPED_KEY_LEN];
+    let wrapped = KwAes256::new(&kek.into())
+        .wrap_key(cek, &mut buf)
+        .map_err(|_| EncryptionError::Encrypt)?;
+
+    Ok(JweRecipient {
+        header: JweHeader {
+            alg,
+            kid,
+            epk: Jwk {
+                key: Key::Ec(Ec::from(ephemeral.public_key())),
+                prm: Parameters::default(),
+            },
+        },
+        encrypted_key: BASE64_URL_SAFE_NO_PAD.encode(wrapped),
+    })
+}
+
+fn unwrap_key(
+    recipient: &JweRecipient,
+    secret: &SecretKey,
+) -> Result<[u8; KEY_LEN], EncryptionError> {
+    let Key::Ec(epk) = &recipient.header.epk.key else {
+        return Err(EncryptionError::UnsupportedKey);
+    };
+    let epk = PublicKey::try_from(epk).map_err(|_| EncryptionError::UnsupportedKey)?;
+
+    let kek = derive_kek(secret, &epk, recipient.header.alg)?;
+
+    let wrapped = BASE64_URL_SAFE_NO_PAD.decode(&recipient.encrypted_key)?;
+
+    let mut cek = [0; KEY_LEN];
+    KwAes256::new(&kek.into())
+        .unwrap_key(&wrapped, &mut cek)
+        .map_err(|_| EncryptionError::Decrypt)?;
+
+    Ok(cek)
+}
+
+/// Derives a key encryption key using ECDH and the
+/// [Concat KDF](https://www.rfc-editor.org/rfc/rfc7518#section-4.6.2).
+fn derive_kek(
+    secret: &SecretKey,
+    public: &PublicKey,
+    alg: KeyEncryption,
+) -> Result<[u8; KEY_LEN], EncryptionError> {
+    let shared = diffie_hellman(secret.to_nonzero_scalar(), public.as_affine());
+
+    let alg = serde_json::to_value(alg)?;
+    let alg = alg.as_str().unwrap_or_default().as_bytes();
+
+    let mut input = Vec::new();
+    // Round number.
+    input.extend(1u32.to_be_bytes());
+    input.extend(shared.raw_secret_bytes());
+    // AlgorithmID
+    input.extend((alg.len() as u32).to_be_bytes());
+    input.extend(alg);
+    // PartyUInfo, PartyVInfo
+    input.extend(0u32.to_be_bytes());
+    input.extend(0u32.to_be_bytes());
+    // SuppPubInfo (key length in bits)
+    input.extend(((KEY_LEN * 8) as u32).to_be_bytes());
+
+    let mut kek = [0; KEY_LEN];
+    kek.copy_from_slice(digest(&SHA256, &input).as_ref());
+
+    Ok(kek)
+}
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("failed to decode base64: {0}")]
+    Decode(#[from] base64::DecodeError),
+    #[error("failed to construct DID resolver: {0}")]
+    DidResolver(#[from] MethodError),
+    #[error("failed to decrypt data")]
+    Decrypt,
+    #[error("failed to encrypt data")]
+    Encrypt,
+    #[error("no key agreement key found")]
+    MissingKeyAgreement,
+    #[error("key is not a recipient of the data")]
+    NotRecipient,
+    #[error("failed to generate random bytes")]
+    Random,
+    #[error("failed to resolve DID: {0}")]
+    ResolutionError(#[from] ResolutionError),
+    #[error("Error during serialization / deserialization: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("unsupported key")]
+    UnsupportedKey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = EncryptionKey::from(P256KeyPair::generate());
+        let data = "Hello, world!".as_bytes();
+
+        let jwe = encrypt(data, &[(key.url.clone(), key.public_key())]).unwrap();
+        assert_ne!(
+            BASE64_URL_SAFE_NO_PAD.decode(&jwe.ciphertext).unwrap(),
+            data
+        );
+
+        let decrypted = decrypt(&jwe, &key).unwrap().unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn test_add_recipient() {
+        let key = EncryptionKey::from(P256KeyPair::generate());
+        let other = EncryptionKey::from(P256KeyPair::generate());
+        let data = "Hello, world!".as_bytes();
+
+        let mut jwe = encrypt(data, &[(key.url.clone(), key.public_key())]).unwrap();
+        let ciphertext = jwe.ciphertext.clone();
+
+        add_recipient(&mut jwe, &key, other.url.clone(), &other.public_key()).unwrap();
+        assert_eq!(jwe.recipients.len(), 2);
+        assert_eq!(jwe.ciphertext, ciphertext);
+
+        let decrypted = decrypt(&jwe, &other).unwrap().unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn test_decrypt_not_recipient() {
+        let key = EncryptionKey::from(P256KeyPair::generate());
+        let other = EncryptionKey::from(P256KeyPair::generate());
+
+        let jwe = encrypt(&[0, 1, 2, 3], &[(key.url.clone(), key.public_key())]).unwrap();
+        assert!(decrypt(&jwe, &other).unwrap().is_none());
+    }
+}
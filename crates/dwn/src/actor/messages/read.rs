@@ -0,0 +1,112 @@
+use anyhow::bail;
+use dwn_core::{
+    message::{Message, descriptor::MessagesReadBuilder},
+    reply::Reply,
+    store::MessageLogEntry,
+};
+use reqwest::Url;
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+impl Actor {
+    pub fn read_message(&self, message_cid: String) -> ActorMessagesReadBuilder<'_> {
+        ActorMessagesReadBuilder {
+            actor: self,
+            msg: MessagesReadBuilder::new(message_cid),
+            auth: true,
+            permission_grant: None,
+            target: None,
+        }
+    }
+}
+
+pub struct ActorMessagesReadBuilder<'a> {
+    actor: &'a Actor,
+    msg: MessagesReadBuilder,
+    auth: bool,
+    permission_grant: Option<String>,
+    target: Option<&'a Did>,
+}
+
+impl<'a> ActorMessagesReadBuilder<'a> {
+    /// Whether to authorize the message.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    fn build(self) -> anyhow::Result<Message> {
+        let mut msg = self.msg.build()?;
+
+        if self.auth {
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
+        }
+
+        Ok(msg)
+    }
+
+    /// Sends the message to the actor's primary remote DWN.
+    pub async fn send_remote(self) -> anyhow::Result<Option<MessageLogEntry>> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Sends the message to a remote DWN.
+    pub async fn send(self, url: &Url) -> anyhow::Result<Option<MessageLogEntry>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor.send(target, &msg, url).await?;
+
+        parse_reply(reply)
+    }
+
+    /// Processes the message with the actor's local DWN.
+    pub async fn process(self) -> anyhow::Result<Option<MessageLogEntry>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor
+            .dwn
+            .process_message(target, msg)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+
+        parse_reply(reply)
+    }
+}
+
+fn parse_reply(reply: Option<Reply>) -> anyhow::Result<Option<MessageLogEntry>> {
+    match reply {
+        Some(Reply::MessagesRead(read)) => Ok(read.message),
+        Some(other) => {
+            bail!("got invalid reply from DWN: {other:?}")
+        }
+        None => {
+            bail!("got no reply from DWN")
+        }
+    }
+}
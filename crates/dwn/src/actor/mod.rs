@@ -5,17 +5,21 @@ use dwn_core::message::{
     AuthPayload, Header, Jws, Message, Signature,
     cid::{CidGenerationError, compute_cid_cbor},
 };
-use reqwest::Url;
 use thiserror::Error;
 use xdid::{core::did::Did, methods::key::Signer};
 
 use crate::Dwn;
 
-use self::document_key::DocumentKey;
+use self::{document_key::DocumentKey, encryption::EncryptionKey, outbox::Outbox, remote::Remote};
 
 pub mod document_key;
+pub mod encryption;
+pub mod messages;
+pub mod outbox;
+pub mod permissions;
 pub mod protocols;
 pub mod records;
+pub mod remote;
 pub mod sync;
 
 #[derive(Clone)]
@@ -25,10 +29,13 @@ pub struct Actor {
 
     pub auth_key: Option<Arc<DocumentKey>>,
     pub sign_key: Option<Arc<DocumentKey>>,
+    /// Key agreement key, used to decrypt record data.
+    pub encryption_key: Option<Arc<EncryptionKey>>,
 
-    /// URL of a remote DWN to sync with.
-    pub remote: Option<Url>,
+    /// Remote DWNs to sync with.
+    pub remotes: Vec<Remote>,
     client: reqwest::Client,
+    outbox: Arc<Outbox>,
 }
 
 impl Actor {
@@ -38,8 +45,10 @@ impl Actor {
             dwn,
             auth_key: None,
             sign_key: None,
-            remote: None,
+            encryption_key: None,
+            remotes: Vec::new(),
             client: reqwest::Client::default(),
+            outbox: Arc::default(),
         }
     }
 
@@ -72,6 +81,16 @@ impl Actor {
     /// Authorizes the message with a [DID authentication](https://www.w3.org/TR/did-core/#authentication) key.
     /// If the message has been signed, the assertion will also be authorized.
     pub fn authorize(&self, msg: &mut Message) -> Result<(), SignError> {
+        self.authorize_with_grant(msg, None)
+    }
+
+    /// Authorizes the message, referencing a permission grant issued to this actor.
+    /// The grant allows the message to act on the granting DID's DWN.
+    pub fn authorize_with_grant(
+        &self,
+        msg: &mut Message,
+        permissions_grant_id: Option<String>,
+    ) -> Result<(), SignError> {
         let Some(doc_key) = self.auth_key.as_ref() else {
             return Err(SignError::MissingKey);
         };
@@ -92,7 +111,7 @@ impl Actor {
 
         let auth_payload = serde_json::to_string(&AuthPayload {
             descriptor_cid,
-            permissions_grant_cid: None,
+            permissions_grant_cid: permissions_grant_id,
             attestation_cid,
         })
         .unwrap();
@@ -0,0 +1,160 @@
+use std::{collections::HashSet, time::Duration};
+
+use dwn_core::{
+    message::{Message, OffsetDateTime},
+    store::{OutboxItem, OutboxStatus},
+};
+use reqwest::Url;
+use tokio::{
+    sync::{Mutex, Notify},
+    task::JoinHandle,
+};
+use tracing::warn;
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+/// Delay before the first retry of an outbox item.
+/// Doubles with each failed attempt.
+const OUTBOX_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay between retries of an outbox item.
+const OUTBOX_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Delivery attempts before an outbox item is marked failed.
+pub const OUTBOX_MAX_ATTEMPTS: u32 = 16;
+
+#[derive(Default)]
+pub(crate) struct Outbox {
+    /// Held while delivering, so items are not sent twice.
+    lock: Mutex<()>,
+    /// Wakes the background task when items are queued.
+    notify: Notify,
+}
+
+impl Actor {
+    /// Returns every item in the outbox, in queue order.
+    pub fn outbox(&self) -> anyhow::Result<Vec<OutboxItem>> {
+        Ok(self.dwn.record_store.read_outbox()?)
+    }
+
+    /// Removes an item from the outbox, such as a failed item that will not be retried.
+    pub fn discard_outbox_item(&self, id: u64) -> anyhow::Result<()> {
+        Ok(self.dwn.record_store.remove_outbox(id)?)
+    }
+
+    /// Queues the message for delivery to every remote.
+    /// Delivery is left to [Self::spawn_outbox], or an explicit [Self::flush_outbox].
+    pub(crate) fn enqueue(&self, target: &Did, msg: &Message) -> anyhow::Result<()> {
+        if self.remotes.is_empty() {
+            return Ok(());
+        }
+
+        for remote in &self.remotes {
+            self.dwn
+                .record_store
+                .push_outbox(target, remote.url.as_str(), msg.clone())?;
+        }
+
+        self.outbox.notify.notify_one();
+
+        Ok(())
+    }
+
+    /// Attempts delivery of every due outbox item.
+    /// Items are delivered in queue order for each remote, so a remote with an
+    /// undelivered item is skipped until that item is retried.
+    pub async fn flush_outbox(&self) -> anyhow::Result<()> {
+        let _guard = self.outbox.lock.lock().await;
+
+        let now = OffsetDateTime::now_utc();
+        let mut blocked = HashSet::new();
+
+        for mut item in self.dwn.record_store.read_outbox()? {
+            if item.status == OutboxStatus::Failed || blocked.contains(&item.remote) {
+                continue;
+            }
+
+            if item.next_attempt > now {
+                blocked.insert(item.remote);
+                continue;
+            }
+
+            let url = Url::parse(&item.remote)?;
+
+            let Err(e) = self.send(&item.target, &item.message, &url).await else {
+                self.dwn.record_store.remove_outbox(item.id)?;
+                continue;
+            };
+
+            item.attempts += 1;
+            item.last_error = Some(format!("{e:#}"));
+
+            // Retrying is pointless if the remote rejected the message.
+            let rejected = e
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status())
+                .is_some_and(|s| s.is_client_error());
+
+            if rejected || item.attempts >= OUTBOX_MAX_ATTEMPTS {
+                warn!("Failed to deliver outbox item {}: {e:?}", item.id);
+                item.status = OutboxStatus::Failed;
+            } else {
+                item.next_attempt = now + retry_delay(item.attempts);
+                blocked.insert(item.remote.clone());
+            }
+
+            self.dwn.record_store.update_outbox(&item)?;
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a task that delivers outbox items in the background,
+    /// retrying failed deliveries with exponential backoff.
+    pub fn spawn_outbox(&self) -> JoinHandle<()> {
+        let actor = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = actor.flush_outbox().await {
+                    warn!("Failed to flush outbox: {e:?}");
+                }
+
+                let wait = match actor.next_outbox_attempt() {
+                    Ok(Some(next)) => (next - OffsetDateTime::now_utc())
+                        .try_into()
+                        .unwrap_or_default(),
+                    Ok(None) => OUTBOX_MAX_DELAY,
+                    Err(e) => {
+                        warn!("Failed to read outbox: {e:?}");
+                        OUTBOX_MAX_DELAY
+                    }
+                };
+
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = actor.outbox.notify.notified() => {}
+                }
+            }
+        })
+    }
+
+    /// Returns when the earliest pending outbox item is due.
+    fn next_outbox_attempt(&self) -> anyhow::Result<Option<OffsetDateTime>> {
+        Ok(self
+            .dwn
+            .record_store
+            .read_outbox()?
+            .into_iter()
+            .filter(|item| item.status == OutboxStatus::Pending)
+            .map(|item| item.next_attempt)
+            .min())
+    }
+}
+
+fn retry_delay(attempts: u32) -> Duration {
+    OUTBOX_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(OUTBOX_MAX_DELAY)
+}
@@ -0,0 +1,81 @@
+use dwn_core::message::{
+    OffsetDateTime,
+    descriptor::{PermissionScope, PermissionsGrantBuilder},
+};
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+impl Actor {
+    /// Grants a permission over the actor's DWN to another DID.
+    pub fn grant_permission(
+        &self,
+        grantee: Did,
+        scope: PermissionScope,
+        date_expires: OffsetDateTime,
+    ) -> ActorGrantPermissionBuilder<'_> {
+        ActorGrantPermissionBuilder {
+            actor: self,
+            msg: PermissionsGrantBuilder::new(grantee, scope, date_expires),
+            auth: true,
+            sync: true,
+        }
+    }
+}
+
+pub struct ActorGrantPermissionBuilder<'a> {
+    actor: &'a Actor,
+    msg: PermissionsGrantBuilder,
+    auth: bool,
+    sync: bool,
+}
+
+impl ActorGrantPermissionBuilder<'_> {
+    pub fn description(mut self, value: String) -> Self {
+        self.msg.description = Some(value);
+        self
+    }
+
+    /// Sets the `PermissionsRequest` this grant responds to.
+    pub fn request_id(mut self, value: String) -> Self {
+        self.msg.permissions_request_id = Some(value);
+        self
+    }
+
+    /// Whether to authorize the message.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Whether to sync the message with the remotes.
+    /// Defaults to `true`.
+    pub fn sync(mut self, value: bool) -> Self {
+        self.sync = value;
+        self
+    }
+
+    /// Processes the message with the actor's DWN.
+    /// Returns the grant ID.
+    pub async fn process(self) -> anyhow::Result<String> {
+        let mut msg = self.msg.build()?;
+        let id = msg.record_id.clone();
+
+        if self.auth {
+            self.actor.authorize(&mut msg)?;
+        }
+
+        self.actor
+            .dwn
+            .process_message(&self.actor.did, msg.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+
+        if self.sync {
+            self.actor.enqueue(&self.actor.did, &msg)?;
+        }
+
+        Ok(id)
+    }
+}
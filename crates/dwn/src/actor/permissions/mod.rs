@@ -0,0 +1,3 @@
+pub mod grant;
+pub mod request;
+pub mod revoke;
@@ -0,0 +1,100 @@
+use dwn_core::message::{
+    Message,
+    descriptor::{PermissionScope, PermissionsRequestBuilder},
+};
+use reqwest::Url;
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+impl Actor {
+    /// Requests a permission from the target DID.
+    pub fn request_permission(&self, scope: PermissionScope) -> ActorRequestPermissionBuilder<'_> {
+        ActorRequestPermissionBuilder {
+            actor: self,
+            msg: PermissionsRequestBuilder::new(self.did.clone(), scope),
+            auth: true,
+            target: None,
+        }
+    }
+}
+
+pub struct ActorRequestPermissionBuilder<'a> {
+    actor: &'a Actor,
+    msg: PermissionsRequestBuilder,
+    auth: bool,
+    target: Option<&'a Did>,
+}
+
+impl<'a> ActorRequestPermissionBuilder<'a> {
+    pub fn description(mut self, value: String) -> Self {
+        self.msg.description = Some(value);
+        self
+    }
+
+    /// Whether to authorize the message.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    fn build(self) -> anyhow::Result<Message> {
+        let mut msg = self.msg.build()?;
+
+        if self.auth {
+            self.actor.authorize(&mut msg)?;
+        }
+
+        Ok(msg)
+    }
+
+    /// Sends the message to the actor's primary remote DWN.
+    /// Returns the request ID.
+    pub async fn send_remote(self) -> anyhow::Result<String> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Sends the message to a remote DWN.
+    /// Returns the request ID.
+    pub async fn send(self, url: &Url) -> anyhow::Result<String> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+        let id = msg.record_id.clone();
+
+        actor.send(target, &msg, url).await?;
+
+        Ok(id)
+    }
+
+    /// Processes the message with the actor's local DWN.
+    /// Returns the request ID.
+    pub async fn process(self) -> anyhow::Result<String> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+        let id = msg.record_id.clone();
+
+        actor
+            .dwn
+            .process_message(target, msg)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+
+        Ok(id)
+    }
+}
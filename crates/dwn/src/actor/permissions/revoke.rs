@@ -0,0 +1,59 @@
+use dwn_core::message::descriptor::PermissionsRevokeBuilder;
+
+use crate::Actor;
+
+impl Actor {
+    /// Revokes a permission grant issued by the actor.
+    pub fn revoke_permission(&self, grant_id: String) -> ActorRevokePermissionBuilder<'_> {
+        ActorRevokePermissionBuilder {
+            actor: self,
+            msg: PermissionsRevokeBuilder::new(grant_id),
+            auth: true,
+            sync: true,
+        }
+    }
+}
+
+pub struct ActorRevokePermissionBuilder<'a> {
+    actor: &'a Actor,
+    msg: PermissionsRevokeBuilder,
+    auth: bool,
+    sync: bool,
+}
+
+impl ActorRevokePermissionBuilder<'_> {
+    /// Whether to authorize the message.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Whether to sync the message with the remotes.
+    /// Defaults to `true`.
+    pub fn sync(mut self, value: bool) -> Self {
+        self.sync = value;
+        self
+    }
+
+    /// Processes the message with the actor's DWN.
+    pub async fn process(self) -> anyhow::Result<()> {
+        let mut msg = self.msg.build()?;
+
+        if self.auth {
+            self.actor.authorize(&mut msg)?;
+        }
+
+        self.actor
+            .dwn
+            .process_message(&self.actor.did, msg.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+
+        if self.sync {
+            self.actor.enqueue(&self.actor.did, &msg)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
+use anyhow::Context;
 use dwn_core::message::{
-    Version,
-    descriptor::{ProtocolDefinition, ProtocolsConfigureBuilder},
+    Message, Version,
+    descriptor::{Descriptor, ProtocolDefinition, ProtocolsConfigureBuilder},
 };
 
-use crate::Actor;
+use crate::{Actor, remote::RemoteResult};
 
 impl Actor {
     pub fn configure_protocol(
@@ -35,32 +36,55 @@ impl ActorConfigureProtocolBuilder<'_> {
         self
     }
 
-    /// Whether to sync the message with the remote.
+    /// Whether to sync the message with the remotes.
     /// Defaults to `true`.
     pub fn sync(mut self, value: bool) -> Self {
         self.sync = value;
         self
     }
 
-    /// Processes the message with the actor's DWN.
-    pub async fn process(self) -> anyhow::Result<()> {
+    /// Validates the definition, then builds the message.
+    fn build(self) -> anyhow::Result<Message> {
         let mut msg = self.msg.build()?;
 
+        if let Descriptor::ProtocolsConfigure(desc) = &msg.descriptor {
+            desc.definition
+                .validate()
+                .context("invalid protocol definition")?;
+        }
+
         if self.auth {
             self.actor.authorize(&mut msg)?;
         }
 
-        if self.sync && self.actor.remote.is_some() {
-            self.actor.send_remote(&self.actor.did, &msg).await?;
-        }
+        Ok(msg)
+    }
 
-        let _ = self
-            .actor
+    /// Sends the message to every remote DWN of the actor.
+    /// Returns the result for each remote.
+    pub async fn send_remotes(self) -> anyhow::Result<Vec<RemoteResult>> {
+        let actor = self.actor;
+        let msg = self.build()?;
+        Ok(actor.send_remotes(&actor.did, &msg).await)
+    }
+
+    /// Processes the message with the actor's DWN.
+    /// The definition is validated before the message is sent.
+    pub async fn process(self) -> anyhow::Result<()> {
+        let sync = self.sync;
+        let actor = self.actor;
+        let msg = self.build()?;
+
+        let _ = actor
             .dwn
-            .process_message(&self.actor.did, msg)
+            .process_message(&actor.did, msg.clone())
             .await
             .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
 
+        if sync {
+            actor.enqueue(&actor.did, &msg)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,132 @@
+use anyhow::bail;
+use dwn_core::{
+    message::{
+        Message, VersionReq,
+        descriptor::{Descriptor, ProtocolsConfigure, ProtocolsQueryBuilder},
+    },
+    reply::Reply,
+};
+use reqwest::Url;
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+impl Actor {
+    pub fn query_protocols(&self) -> ActorQueryProtocolsBuilder<'_> {
+        ActorQueryProtocolsBuilder {
+            actor: self,
+            msg: ProtocolsQueryBuilder::default(),
+            auth: true,
+            permission_grant: None,
+            target: None,
+        }
+    }
+}
+
+pub struct ActorQueryProtocolsBuilder<'a> {
+    actor: &'a Actor,
+    msg: ProtocolsQueryBuilder,
+    auth: bool,
+    permission_grant: Option<String>,
+    target: Option<&'a Did>,
+}
+
+impl<'a> ActorQueryProtocolsBuilder<'a> {
+    pub fn protocol(mut self, value: String) -> Self {
+        self.msg.filter.protocol = Some(value);
+        self
+    }
+
+    /// Only returns versions matching the requirement.
+    pub fn version(mut self, value: VersionReq) -> Self {
+        self.msg.filter.version = Some(value);
+        self
+    }
+
+    /// Whether to authorize the message.
+    /// Unauthorized queries only return published protocols.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    fn build(self) -> anyhow::Result<Message> {
+        let mut msg = self.msg.build()?;
+
+        if self.auth {
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
+        }
+
+        Ok(msg)
+    }
+
+    /// Sends the message to the actor's primary remote DWN.
+    pub async fn send_remote(self) -> anyhow::Result<Vec<ProtocolsConfigure>> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Sends the message to a remote DWN.
+    pub async fn send(self, url: &Url) -> anyhow::Result<Vec<ProtocolsConfigure>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor.send(target, &msg, url).await?;
+
+        parse_reply(reply)
+    }
+
+    /// Processes the message with the actor's DWN.
+    pub async fn process(self) -> anyhow::Result<Vec<ProtocolsConfigure>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor
+            .dwn
+            .process_message(target, msg)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+
+        parse_reply(reply)
+    }
+}
+
+fn parse_reply(reply: Option<Reply>) -> anyhow::Result<Vec<ProtocolsConfigure>> {
+    match reply {
+        Some(Reply::ProtocolsQuery(entries)) => entries
+            .into_iter()
+            .map(|m| match m.descriptor {
+                Descriptor::ProtocolsConfigure(desc) => Ok(*desc),
+                other => bail!("got invalid protocol entry: {other:?}"),
+            })
+            .collect(),
+        Some(other) => {
+            bail!("got invalid reply from DWN: {other:?}")
+        }
+        None => {
+            bail!("got no reply from DWN")
+        }
+    }
+}
@@ -2,7 +2,7 @@ use dwn_core::message::{Message, descriptor::RecordsDeleteBuilder};
 use reqwest::Url;
 use xdid::core::did::Did;
 
-use crate::Actor;
+use crate::{Actor, remote::RemoteResult};
 
 impl Actor {
     pub fn delete(&self, record_id: String) -> ActorDeleteBuilder<'_> {
@@ -10,6 +10,7 @@ impl Actor {
             actor: self,
             msg: RecordsDeleteBuilder::new(record_id),
             auth: true,
+            permission_grant: None,
             sync: true,
             target: None,
         }
@@ -20,6 +21,7 @@ pub struct ActorDeleteBuilder<'a> {
     actor: &'a Actor,
     msg: RecordsDeleteBuilder,
     auth: bool,
+    permission_grant: Option<String>,
     sync: bool,
     target: Option<&'a Did>,
 }
@@ -32,6 +34,19 @@ impl<'a> ActorDeleteBuilder<'a> {
         self
     }
 
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Whether to also delete descendants of the record.
+    /// Defaults to `false`.
+    pub fn prune(mut self, value: bool) -> Self {
+        self.msg = self.msg.prune(value);
+        self
+    }
+
     /// Sets the target DID for DWN processing.
     /// Defaults to the actor's own DID.
     pub fn target(mut self, value: &'a Did) -> Self {
@@ -39,7 +54,7 @@ impl<'a> ActorDeleteBuilder<'a> {
         self
     }
 
-    /// Whether to sync the message with the remote.
+    /// Whether to sync the message with the remotes.
     /// Defaults to `true`.
     pub fn sync(mut self, value: bool) -> Self {
         self.sync = value;
@@ -50,18 +65,18 @@ impl<'a> ActorDeleteBuilder<'a> {
         let mut msg = self.msg.build()?;
 
         if self.auth {
-            self.actor.authorize(&mut msg)?;
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
         }
 
         Ok(msg)
     }
 
-    /// Sends the message to the actor's remote DWN.
+    /// Sends the message to the actor's primary remote DWN.
     pub async fn send_remote(self) -> anyhow::Result<()> {
         let url = self
             .actor
-            .remote
-            .as_ref()
+            .primary_remote()
             .ok_or(anyhow::anyhow!("no remote"))?;
         self.send(url).await
     }
@@ -78,6 +93,17 @@ impl<'a> ActorDeleteBuilder<'a> {
         Ok(())
     }
 
+    /// Sends the message to every remote DWN of the actor.
+    /// Returns the result for each remote.
+    pub async fn send_remotes(self) -> anyhow::Result<Vec<RemoteResult>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        Ok(actor.send_remotes(target, &msg).await)
+    }
+
     /// Processes the message with the actor's DWN.
     pub async fn process(self) -> anyhow::Result<()> {
         let sync = self.sync;
@@ -86,16 +112,16 @@ impl<'a> ActorDeleteBuilder<'a> {
 
         let msg = self.build()?;
 
-        if sync && actor.remote.is_some() {
-            actor.send_remote(&actor.did, &msg).await?;
-        }
-
         let _ = actor
             .dwn
-            .process_message(target, msg)
+            .process_message(target, msg.clone())
             .await
             .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
 
+        if sync {
+            actor.enqueue(target, &msg)?;
+        }
+
         Ok(())
     }
 }
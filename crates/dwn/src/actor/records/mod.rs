@@ -1,9 +1,17 @@
-use base64::{DecodeError, Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use std::ops::Range;
+
+use anyhow::Context;
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use dwn_core::message::{Message, data::Data};
 
+use crate::encryption::{EncryptionKey, decrypt};
+
 pub mod delete;
 pub mod query;
 pub mod read;
+pub mod share;
+pub mod subscribe;
+pub mod upload;
 pub mod write;
 
 pub struct RecordView {
@@ -12,13 +20,18 @@ pub struct RecordView {
 }
 
 impl RecordView {
-    fn from_entry(mut entry: Message) -> Result<Self, DecodeError> {
+    /// Decodes the entry's data.
+    /// Encrypted data is decrypted if `key` is a recipient, otherwise no data is returned.
+    fn from_entry(mut entry: Message, key: Option<&EncryptionKey>) -> anyhow::Result<Self> {
         let data = match entry.data.take() {
             Some(Data::Base64(encoded)) => {
                 let decoded = BASE64_URL_SAFE_NO_PAD.decode(encoded)?;
                 Some(decoded)
             }
-            Some(Data::Encrypted(_)) => todo!(),
+            Some(Data::Encrypted(jwe)) => match key {
+                Some(key) => decrypt(&jwe, key).context("decrypt data")?,
+                None => None,
+            },
             None => None,
         };
 
@@ -28,6 +41,14 @@ impl RecordView {
     pub fn data(&self) -> Option<&[u8]> {
         self.data.as_deref()
     }
+    /// Returns a byte range of the data, clamped to its length.
+    /// Use [`read::ActorReadBuilder::range`] to avoid loading the full data.
+    pub fn data_range(&self, range: Range<usize>) -> Option<&[u8]> {
+        let data = self.data.as_deref()?;
+        let end = range.end.min(data.len());
+        Some(&data[range.start.min(end)..end])
+    }
+
     pub fn into_data(self) -> Option<Vec<u8>> {
         self.data
     }
@@ -2,11 +2,12 @@ use anyhow::bail;
 use dwn_core::{
     message::{
         Message, Version,
-        descriptor::{DateFilter, DateSort, RecordsQueryBuilder},
+        descriptor::{DateFilter, DateSort, RecordsQueryBuilder, TagFilter},
         mime::Mime,
     },
     reply::Reply,
 };
+use futures_util::{Stream, TryStreamExt, stream};
 use reqwest::Url;
 use xdid::core::did::Did;
 
@@ -18,6 +19,7 @@ impl Actor {
             actor: self,
             msg: RecordsQueryBuilder::default(),
             auth: true,
+            permission_grant: None,
             target: None,
         }
     }
@@ -27,6 +29,7 @@ pub struct ActorQueryBuilder<'a> {
     actor: &'a Actor,
     msg: RecordsQueryBuilder,
     auth: bool,
+    permission_grant: Option<String>,
     target: Option<&'a Did>,
 }
 
@@ -76,6 +79,17 @@ impl<'a> ActorQueryBuilder<'a> {
         self
     }
 
+    /// Adds a predicate on a tag.
+    /// Records must match every tag predicate.
+    pub fn tag(mut self, name: String, filter: TagFilter) -> Self {
+        self.msg
+            .filter
+            .tags
+            .get_or_insert_default()
+            .insert(name, filter);
+        self
+    }
+
     pub fn date_created(mut self, value: DateFilter) -> Self {
         self.msg.filter.date_created = Some(value);
         self
@@ -86,6 +100,18 @@ impl<'a> ActorQueryBuilder<'a> {
         self
     }
 
+    /// Maximum number of entries per page.
+    pub fn limit(mut self, value: usize) -> Self {
+        self.msg.pagination.get_or_insert_default().limit = Some(value);
+        self
+    }
+
+    /// Continues after a cursor returned by a previous page.
+    pub fn cursor(mut self, value: String) -> Self {
+        self.msg.pagination.get_or_insert_default().cursor = Some(value);
+        self
+    }
+
     /// Whether to authorize the message.
     /// Defaults to `true`.
     pub fn auth(mut self, value: bool) -> Self {
@@ -93,6 +119,12 @@ impl<'a> ActorQueryBuilder<'a> {
         self
     }
 
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
     /// Sets the target DID for DWN processing.
     /// Defaults to the actor's own DID.
     pub fn target(mut self, value: &'a Did) -> Self {
@@ -100,62 +132,113 @@ impl<'a> ActorQueryBuilder<'a> {
         self
     }
 
-    fn build(self) -> anyhow::Result<Message> {
-        let mut msg = self.msg.build()?;
+    fn build(&self) -> anyhow::Result<Message> {
+        let mut msg = self.msg.clone().build()?;
 
         if self.auth {
-            self.actor.authorize(&mut msg)?;
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant.clone())?;
         }
 
         Ok(msg)
     }
 
-    /// Sends the message to the actor's remote DWN.
+    /// Sends the message to the actor's primary remote DWN.
     pub async fn send_remote(self) -> anyhow::Result<Vec<RecordView>> {
         let url = self
             .actor
-            .remote
-            .as_ref()
+            .primary_remote()
             .ok_or(anyhow::anyhow!("no remote"))?;
         self.send(url).await
     }
 
     /// Sends the message to a remote DWN.
     pub async fn send(self, url: &Url) -> anyhow::Result<Vec<RecordView>> {
-        let actor = self.actor;
-        let target = self.target.unwrap_or(&actor.did);
+        self.fetch_page(Some(url)).await.map(|(entries, _)| entries)
+    }
 
-        let msg = self.build()?;
+    /// Processes the message with the actor's DWN.
+    pub async fn process(self) -> anyhow::Result<Vec<RecordView>> {
+        self.fetch_page(None).await.map(|(entries, _)| entries)
+    }
 
-        let reply = actor.send(target, &msg, url).await?;
+    /// Streams every matching record from the actor's DWN,
+    /// fetching pages of [Self::limit] entries as needed.
+    pub fn stream(self) -> impl Stream<Item = anyhow::Result<RecordView>> + 'a {
+        self.stream_pages(None)
+    }
 
-        parse_reply(reply)
+    /// Streams every matching record from the actor's primary remote DWN.
+    pub fn stream_remote(
+        self,
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<RecordView>> + 'a> {
+        let url = self
+            .actor
+            .primary_remote()
+            .cloned()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        Ok(self.stream_send(url))
     }
 
-    /// Processes the message with the actor's DWN.
-    pub async fn process(self) -> anyhow::Result<Vec<RecordView>> {
+    /// Streams every matching record from a remote DWN.
+    pub fn stream_send(self, url: Url) -> impl Stream<Item = anyhow::Result<RecordView>> + 'a {
+        self.stream_pages(Some(url))
+    }
+
+    /// Streams pages from a remote DWN, or the local DWN if `url` is `None`.
+    fn stream_pages(self, url: Option<Url>) -> impl Stream<Item = anyhow::Result<RecordView>> + 'a {
+        stream::try_unfold(Some(self), move |builder| {
+            let url = url.clone();
+
+            async move {
+                let Some(builder) = builder else {
+                    return anyhow::Ok(None);
+                };
+
+                let (entries, cursor) = builder.fetch_page(url.as_ref()).await?;
+                let next = cursor.map(|c| builder.cursor(c));
+
+                Ok(Some((stream::iter(entries.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    async fn fetch_page(
+        &self,
+        url: Option<&Url>,
+    ) -> anyhow::Result<(Vec<RecordView>, Option<String>)> {
         let actor = self.actor;
         let target = self.target.unwrap_or(&actor.did);
 
         let msg = self.build()?;
 
-        let reply = actor
-            .dwn
-            .process_message(target, msg)
-            .await
-            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?;
+        let reply = match url {
+            Some(url) => actor.send(target, &msg, url).await?,
+            None => actor
+                .dwn
+                .process_message(target, msg)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))?,
+        };
 
-        parse_reply(reply)
+        parse_reply(actor, reply)
     }
 }
 
-fn parse_reply(reply: Option<Reply>) -> anyhow::Result<Vec<RecordView>> {
+fn parse_reply(
+    actor: &Actor,
+    reply: Option<Reply>,
+) -> anyhow::Result<(Vec<RecordView>, Option<String>)> {
     match reply {
-        Some(Reply::RecordsQuery(query)) => Ok(query
-            .entries
-            .into_iter()
-            .map(RecordView::from_entry)
-            .collect::<Result<Vec<_>, _>>()?),
+        Some(Reply::RecordsQuery(query)) => {
+            let entries = query
+                .entries
+                .into_iter()
+                .map(|e| RecordView::from_entry(e, actor.encryption_key.as_deref()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((entries, query.cursor))
+        }
         Some(other) => {
             bail!("got invalid reply from DWN: {other:?}")
         }
@@ -1,9 +1,13 @@
-use anyhow::bail;
+use std::ops::Range;
+
+use anyhow::{Context, bail};
 use dwn_core::{
     message::{Message, descriptor::RecordsReadBuilder},
     reply::Reply,
+    store::DataReader,
 };
-use reqwest::Url;
+use futures_util::{Stream, stream};
+use reqwest::{StatusCode, Url, header::RANGE};
 use xdid::core::did::Did;
 
 use crate::{Actor, records::RecordView};
@@ -14,6 +18,8 @@ impl Actor {
             actor: self,
             msg: RecordsReadBuilder::new(record_id),
             auth: true,
+            permission_grant: None,
+            range: None,
             target: None,
         }
     }
@@ -23,6 +29,8 @@ pub struct ActorReadBuilder<'a> {
     actor: &'a Actor,
     msg: RecordsReadBuilder,
     auth: bool,
+    permission_grant: Option<String>,
+    range: Option<Range<u64>>,
     target: Option<&'a Did>,
 }
 
@@ -34,6 +42,25 @@ impl<'a> ActorReadBuilder<'a> {
         self
     }
 
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Reads a specific retained entry by its entry id, instead of the latest.
+    pub fn entry_id(mut self, value: String) -> Self {
+        self.msg = self.msg.entry_id(value);
+        self
+    }
+
+    /// Only reads a byte range of the record's data.
+    /// Applies when streaming data with [`Self::process_data`] or [`Self::send_data`].
+    pub fn range(mut self, value: Range<u64>) -> Self {
+        self.range = Some(value);
+        self
+    }
+
     /// Sets the target DID for DWN processing.
     /// Defaults to the actor's own DID.
     pub fn target(mut self, value: &'a Did) -> Self {
@@ -45,18 +72,18 @@ impl<'a> ActorReadBuilder<'a> {
         let mut msg = self.msg.build()?;
 
         if self.auth {
-            self.actor.authorize(&mut msg)?;
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
         }
 
         Ok(msg)
     }
 
-    /// Sends the message to the actor's remote DWN.
+    /// Sends the message to the actor's primary remote DWN.
     pub async fn send_remote(self) -> anyhow::Result<Option<RecordView>> {
         let url = self
             .actor
-            .remote
-            .as_ref()
+            .primary_remote()
             .ok_or(anyhow::anyhow!("no remote"))?;
         self.send(url).await
     }
@@ -70,7 +97,7 @@ impl<'a> ActorReadBuilder<'a> {
 
         let reply = actor.send(target, &msg, url).await?;
 
-        parse_reply(reply)
+        parse_reply(actor, reply)
     }
 
     /// Processes the message with the actor's local DWN.
@@ -86,13 +113,138 @@ impl<'a> ActorReadBuilder<'a> {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))?;
 
-        parse_reply(reply)
+        parse_reply(actor, reply)
+    }
+
+    /// Lists the record's retained entries on the actor's primary remote DWN.
+    pub async fn send_remote_history(self) -> anyhow::Result<Vec<Message>> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send_history(url).await
+    }
+
+    /// Lists the record's retained entries on a remote DWN, in timestamp order.
+    /// Entries are returned without data.
+    pub async fn send_history(mut self, url: &Url) -> anyhow::Result<Vec<Message>> {
+        self.msg = self.msg.history(true);
+
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor.send(target, &msg, url).await?;
+
+        parse_history_reply(reply)
+    }
+
+    /// Lists the record's retained entries on the actor's local DWN, in timestamp order.
+    /// Entries are returned without data.
+    pub async fn process_history(mut self) -> anyhow::Result<Vec<Message>> {
+        self.msg = self.msg.history(true);
+
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let reply = actor
+            .dwn
+            .process_message(target, msg)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))?;
+
+        parse_history_reply(reply)
+    }
+
+    /// Streams the record's data from the actor's primary remote DWN.
+    pub async fn send_remote_data(
+        self,
+    ) -> anyhow::Result<Option<impl Stream<Item = anyhow::Result<Vec<u8>>>>> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send_data(url).await
+    }
+
+    /// Streams the record's data from a remote DWN.
+    /// Returns `None` if the record was not found, or its data is not stored as blocks.
+    pub async fn send_data(
+        self,
+        url: &Url,
+    ) -> anyhow::Result<Option<impl Stream<Item = anyhow::Result<Vec<u8>>>>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let range = self.range.clone();
+        let msg = self.build()?;
+
+        let url = format!("{url}{target}/data");
+
+        let mut req = actor.client.put(url).json(&msg);
+
+        if let Some(range) = range {
+            if range.is_empty() {
+                bail!("empty range");
+            }
+            req = req.header(RANGE, format!("bytes={}-{}", range.start, range.end - 1));
+        }
+
+        let req = req.build().context("build request")?;
+        let res = actor.client.execute(req).await.context("execute request")?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let res = res.error_for_status()?;
+
+        let stream = stream::try_unfold(res, |mut res| async move {
+            let chunk = res.chunk().await.context("read data")?;
+            Ok(chunk.map(|c| (c.to_vec(), res)))
+        });
+
+        Ok(Some(stream))
+    }
+
+    /// Streams the record's data from the actor's local DWN.
+    /// Returns `None` if the record was not found, or its data is not stored as blocks.
+    pub async fn process_data(self) -> anyhow::Result<Option<DataReader>> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let range = self.range.clone();
+        let msg = self.build()?;
+
+        actor
+            .dwn
+            .read_data(target, msg, range)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))
+    }
+}
+
+fn parse_reply(actor: &Actor, reply: Option<Reply>) -> anyhow::Result<Option<RecordView>> {
+    match reply {
+        Some(Reply::RecordsRead(read)) => Ok(read
+            .entry
+            .map(|e| RecordView::from_entry(e, actor.encryption_key.as_deref()))
+            .transpose()?),
+        Some(other) => {
+            bail!("got invalid reply from DWN: {other:?}")
+        }
+        None => {
+            bail!("got no reply from DWN")
+        }
     }
 }
 
-fn parse_reply(reply: Option<Reply>) -> anyhow::Result<Option<RecordView>> {
+fn parse_history_reply(reply: Option<Reply>) -> anyhow::Result<Vec<Message>> {
     match reply {
-        Some(Reply::RecordsRead(read)) => Ok(read.entry.map(RecordView::from_entry).transpose()?),
+        Some(Reply::RecordsRead(read)) => Ok(read.history),
         Some(other) => {
             bail!("got invalid reply from DWN: {other:?}")
         }
@@ -0,0 +1,169 @@
+use anyhow::{Context, bail};
+use dwn_core::{
+    message::{
+        Message,
+        data::Data,
+        descriptor::{Descriptor, RecordsReadBuilder, RecordsWriteBuilder},
+    },
+    reply::Reply,
+};
+use reqwest::Url;
+use xdid::core::did::Did;
+
+use crate::{
+    Actor,
+    encryption::{add_recipient, resolve_key_agreement},
+};
+
+impl Actor {
+    /// Shares an encrypted record with another DID,
+    /// by wrapping the record's encryption key for their key agreement key.
+    /// The data itself is not re-encrypted.
+    pub fn share(&self, record_id: String, did: Did) -> ActorShareBuilder<'_> {
+        ActorShareBuilder {
+            actor: self,
+            record_id,
+            did,
+            sync: true,
+            target: None,
+        }
+    }
+}
+
+pub struct ActorShareBuilder<'a> {
+    actor: &'a Actor,
+    record_id: String,
+    did: Did,
+    sync: bool,
+    target: Option<&'a Did>,
+}
+
+impl<'a> ActorShareBuilder<'a> {
+    /// Whether to sync the message with the actor's remote DWNs after processing.
+    /// Defaults to `true`.
+    pub fn sync(mut self, value: bool) -> Self {
+        self.sync = value;
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    /// Builds a `RecordsWrite` updating the entry with the new recipient.
+    async fn build(&self, entry: Message) -> anyhow::Result<Message> {
+        let Some(Data::Encrypted(mut jwe)) = entry.data else {
+            bail!("record data is not encrypted")
+        };
+
+        let Descriptor::RecordsWrite(desc) = entry.descriptor else {
+            bail!("invalid record entry")
+        };
+
+        let key = self
+            .actor
+            .encryption_key
+            .as_ref()
+            .ok_or(anyhow::anyhow!("missing encryption key"))?;
+
+        let (kid, public) = resolve_key_agreement(&self.did)
+            .await
+            .with_context(|| format!("resolve key agreement for {}", self.did))?;
+
+        add_recipient(&mut jwe, key, kid, &public).context("add recipient")?;
+
+        let mut msg = RecordsWriteBuilder {
+            encrypted_data: Some(jwe),
+            data_format: desc.data_format,
+            context_id: entry.context_id,
+            protocol: desc.protocol,
+            protocol_path: desc.protocol_path,
+            protocol_version: desc.protocol_version,
+            published: desc.published,
+            recipient: desc.recipient,
+            record_id: Some(entry.record_id),
+            schema: desc.schema,
+            tags: desc.tags,
+            ..Default::default()
+        }
+        .build()?;
+
+        self.actor.authorize(&mut msg)?;
+
+        Ok(msg)
+    }
+
+    fn read_message(&self) -> anyhow::Result<Message> {
+        let mut msg = RecordsReadBuilder::new(self.record_id.clone()).build()?;
+        self.actor.authorize(&mut msg)?;
+        Ok(msg)
+    }
+
+    /// Sends the message to the actor's primary remote DWN.
+    pub async fn send_remote(self) -> anyhow::Result<()> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Reads the record from a remote DWN, and sends the update back to it.
+    pub async fn send(self, url: &Url) -> anyhow::Result<()> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let read = self.read_message()?;
+        let reply = actor.send(target, &read, url).await?;
+        let entry = parse_reply(reply)?;
+
+        let msg = self.build(entry).await?;
+
+        actor.send(target, &msg, url).await?;
+
+        Ok(())
+    }
+
+    /// Processes the message with the actor's local DWN.
+    pub async fn process(self) -> anyhow::Result<()> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let read = self.read_message()?;
+        let reply = actor
+            .dwn
+            .process_message(target, read)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))?;
+        let entry = parse_reply(reply)?;
+
+        let msg = self.build(entry).await?;
+
+        actor
+            .dwn
+            .process_message(target, msg.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))?;
+
+        if self.sync {
+            actor.enqueue(target, &msg)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_reply(reply: Option<Reply>) -> anyhow::Result<Message> {
+    match reply {
+        Some(Reply::RecordsRead(read)) => read.entry.ok_or(anyhow::anyhow!("record not found")),
+        Some(other) => {
+            bail!("got invalid reply from DWN: {other:?}")
+        }
+        None => {
+            bail!("got no reply from DWN")
+        }
+    }
+}
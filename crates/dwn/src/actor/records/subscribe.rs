@@ -0,0 +1,218 @@
+use anyhow::Context;
+use dwn_core::message::{
+    Message, Version,
+    descriptor::{RecordFilter, RecordsSubscribeBuilder, TagFilter},
+    mime::Mime,
+};
+use reqwest::Url;
+use tokio::sync::mpsc::unbounded_channel;
+use tracing::warn;
+use xdid::core::did::Did;
+
+use crate::{Actor, RecordSubscription};
+
+impl Actor {
+    pub fn subscribe(&self) -> ActorSubscribeBuilder<'_> {
+        ActorSubscribeBuilder {
+            actor: self,
+            msg: RecordsSubscribeBuilder::default(),
+            auth: true,
+            permission_grant: None,
+            target: None,
+        }
+    }
+}
+
+pub struct ActorSubscribeBuilder<'a> {
+    actor: &'a Actor,
+    msg: RecordsSubscribeBuilder,
+    auth: bool,
+    permission_grant: Option<String>,
+    target: Option<&'a Did>,
+}
+
+impl<'a> ActorSubscribeBuilder<'a> {
+    pub fn filter(mut self, value: RecordFilter) -> Self {
+        self.msg.filter = value;
+        self
+    }
+
+    pub fn attester(mut self, value: Did) -> Self {
+        self.msg.filter.attester = Some(value);
+        self
+    }
+
+    pub fn recipient(mut self, value: Did) -> Self {
+        self.msg.filter.recipient = Some(value);
+        self
+    }
+
+    pub fn schema(mut self, value: String) -> Self {
+        self.msg.filter.schema = Some(value);
+        self
+    }
+
+    pub fn record_id(mut self, value: String) -> Self {
+        self.msg.filter.record_id = Some(value);
+        self
+    }
+
+    pub fn parent_id(mut self, value: String) -> Self {
+        self.msg.filter.parent_id = Some(value);
+        self
+    }
+
+    pub fn protocol(mut self, value: String) -> Self {
+        self.msg.filter.protocol = Some(value);
+        self
+    }
+
+    pub fn protocol_path(mut self, value: String) -> Self {
+        self.msg.filter.protocol_path = Some(value);
+        self
+    }
+
+    pub fn protocol_version(mut self, value: Version) -> Self {
+        self.msg.filter.protocol_version = Some(value);
+        self
+    }
+
+    pub fn data_format(mut self, value: Mime) -> Self {
+        self.msg.filter.data_format = Some(value);
+        self
+    }
+
+    /// Adds a predicate on a tag.
+    /// Records must match every tag predicate.
+    pub fn tag(mut self, name: String, filter: TagFilter) -> Self {
+        self.msg
+            .filter
+            .tags
+            .get_or_insert_default()
+            .insert(name, filter);
+        self
+    }
+
+    /// Whether to authorize the message.
+    /// Defaults to `true`.
+    pub fn auth(mut self, value: bool) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    fn build(self) -> anyhow::Result<Message> {
+        let mut msg = self.msg.build()?;
+
+        if self.auth {
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
+        }
+
+        Ok(msg)
+    }
+
+    /// Subscribes to the actor's primary remote DWN.
+    pub async fn send_remote(self) -> anyhow::Result<RecordSubscription> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Subscribes to a remote DWN.
+    pub async fn send(self, url: &Url) -> anyhow::Result<RecordSubscription> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        let url = format!("{url}{target}/subscribe");
+
+        let req = actor
+            .client
+            .put(url)
+            .json(&msg)
+            .build()
+            .context("build request")?;
+        let mut res = actor
+            .client
+            .execute(req)
+            .await
+            .context("execute request")?
+            .error_for_status()?;
+
+        let (sender, receiver) = unbounded_channel();
+
+        // Parse the server-sent event stream.
+        tokio::spawn(async move {
+            let mut buf = String::new();
+
+            loop {
+                let chunk = match res.chunk().await {
+                    Ok(Some(c)) => c,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Subscription stream failed: {e:?}");
+                        break;
+                    }
+                };
+
+                buf.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(end) = buf.find("\n\n") {
+                    let event = buf[..end].to_string();
+                    buf.drain(..end + 2);
+
+                    let data = event
+                        .lines()
+                        .filter_map(|l| l.strip_prefix("data:"))
+                        .map(|l| l.trim_start())
+                        .collect::<String>();
+
+                    if data.is_empty() {
+                        continue;
+                    }
+
+                    match serde_json::from_str::<Message>(&data) {
+                        Ok(msg) => {
+                            if sender.send(msg).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => warn!("Failed to parse subscription message: {e:?}"),
+                    }
+                }
+            }
+        });
+
+        Ok(RecordSubscription::new(receiver))
+    }
+
+    /// Subscribes to the actor's local DWN.
+    pub async fn process(self) -> anyhow::Result<RecordSubscription> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build()?;
+
+        actor
+            .dwn
+            .subscribe(target, msg)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to process message: {e}"))
+    }
+}
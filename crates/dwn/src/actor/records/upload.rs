@@ -0,0 +1,179 @@
+use anyhow::Context;
+use dwn_core::message::data::DataChunker;
+use futures_util::{Stream, StreamExt, future::join_all};
+use reqwest::Url;
+use tracing::warn;
+use xdid::core::did::Did;
+
+use crate::{
+    Actor,
+    remote::{Remote, RemoteRole},
+};
+
+impl Actor {
+    /// Uploads data as unixfs blocks, chunk by chunk.
+    /// The returned root CID can then be written with [`ActorWriteBuilder::data_cid`](crate::records::write::ActorWriteBuilder::data_cid).
+    pub fn upload<S, B, E>(&self, data: S) -> ActorUploadBuilder<'_, S>
+    where
+        S: Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]>,
+        E: Into<anyhow::Error>,
+    {
+        ActorUploadBuilder {
+            actor: self,
+            data,
+            sync: true,
+            target: None,
+        }
+    }
+
+    /// Mirrors that fail are removed from `remotes`, so the rest of the
+    /// upload skips them.
+    async fn put_block(
+        &self,
+        target: &Did,
+        cid: &str,
+        block: Vec<u8>,
+        local: bool,
+        remotes: &mut Vec<Remote>,
+    ) -> anyhow::Result<()> {
+        let results = join_all(
+            remotes
+                .iter()
+                .map(|r| self.send_block(target, cid, block.clone(), &r.url)),
+        )
+        .await;
+
+        let mut failed = Vec::new();
+
+        for (remote, result) in remotes.iter().zip(results) {
+            match (remote.role, result) {
+                (_, Ok(())) => {}
+                (RemoteRole::Primary, Err(e)) => {
+                    return Err(e.context(format!("upload to remote {}", remote.url)));
+                }
+                (RemoteRole::Mirror, Err(e)) => {
+                    warn!("Failed to upload to mirror {}: {e:?}", remote.url);
+                    failed.push(remote.url.clone());
+                }
+            }
+        }
+
+        remotes.retain(|r| !failed.contains(&r.url));
+
+        if local {
+            self.dwn
+                .write_block(target, cid, block)
+                .map_err(|e| anyhow::anyhow!("Failed to write block: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    async fn send_block(
+        &self,
+        target: &Did,
+        cid: &str,
+        block: Vec<u8>,
+        url: &Url,
+    ) -> anyhow::Result<()> {
+        let url = format!("{url}{target}/blocks/{cid}");
+
+        let req = self
+            .client
+            .put(url)
+            .body(block)
+            .build()
+            .context("build request")?;
+        self.client
+            .execute(req)
+            .await
+            .context("execute request")?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+pub struct ActorUploadBuilder<'a, S> {
+    actor: &'a Actor,
+    data: S,
+    sync: bool,
+    target: Option<&'a Did>,
+}
+
+impl<'a, S, B, E> ActorUploadBuilder<'a, S>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+{
+    /// Whether to upload the blocks to the actor's remote DWNs as well.
+    /// Defaults to `true`.
+    pub fn sync(mut self, value: bool) -> Self {
+        self.sync = value;
+        self
+    }
+
+    /// Sets the target DID for DWN processing.
+    /// Defaults to the actor's own DID.
+    pub fn target(mut self, value: &'a Did) -> Self {
+        self.target = Some(value);
+        self
+    }
+
+    /// Uploads the data to the actor's primary remote DWN.
+    pub async fn send_remote(self) -> anyhow::Result<String> {
+        let url = self
+            .actor
+            .primary_remote()
+            .ok_or(anyhow::anyhow!("no remote"))?;
+        self.send(url).await
+    }
+
+    /// Uploads the data to a remote DWN.
+    /// Returns the data root CID.
+    pub async fn send(self, url: &Url) -> anyhow::Result<String> {
+        self.upload(false, vec![Remote::primary(url.clone())]).await
+    }
+
+    /// Stores the data in the actor's local DWN.
+    /// Returns the data root CID.
+    pub async fn process(self) -> anyhow::Result<String> {
+        let remotes = if self.sync {
+            self.actor.remotes.clone()
+        } else {
+            Vec::new()
+        };
+        self.upload(true, remotes).await
+    }
+
+    async fn upload(mut self, local: bool, mut remotes: Vec<Remote>) -> anyhow::Result<String> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let mut chunker = DataChunker::default();
+
+        while let Some(chunk) = self.data.next().await {
+            let chunk = chunk.map_err(Into::into)?;
+
+            for (cid, block) in chunker.push(chunk.as_ref()) {
+                actor
+                    .put_block(target, &cid, block, local, &mut remotes)
+                    .await?;
+            }
+        }
+
+        // The root block is always last.
+        let mut root = None;
+
+        for (cid, block) in chunker.finish() {
+            actor
+                .put_block(target, &cid, block, local, &mut remotes)
+                .await?;
+            root = Some(cid);
+        }
+
+        root.context("no root block")
+    }
+}
@@ -1,8 +1,17 @@
-use dwn_core::message::{Message, Version, descriptor::RecordsWriteBuilder, mime::Mime};
+use anyhow::Context;
+use dwn_core::message::{
+    Message, Version,
+    descriptor::{RecordsWriteBuilder, TagValue},
+    mime::Mime,
+};
 use reqwest::Url;
 use xdid::core::did::Did;
 
-use crate::Actor;
+use crate::{
+    Actor,
+    encryption::{encrypt, resolve_key_agreement},
+    remote::RemoteResult,
+};
 
 impl Actor {
     pub fn write(&self) -> ActorWriteBuilder<'_> {
@@ -10,6 +19,9 @@ impl Actor {
             actor: self,
             msg: RecordsWriteBuilder::default(),
             auth: true,
+            permission_grant: None,
+            encrypt: false,
+            encrypt_for: Vec::new(),
             sign: false,
             sync: true,
             target: None,
@@ -21,6 +33,9 @@ pub struct ActorWriteBuilder<'a> {
     actor: &'a Actor,
     msg: RecordsWriteBuilder,
     auth: bool,
+    permission_grant: Option<String>,
+    encrypt: bool,
+    encrypt_for: Vec<Did>,
     sign: bool,
     sync: bool,
     target: Option<&'a Did>,
@@ -43,6 +58,13 @@ impl<'a> ActorWriteBuilder<'a> {
         self
     }
 
+    /// Sets the root CID of data uploaded with [`Actor::upload`].
+    pub fn data_cid(mut self, format: Mime, cid: String) -> Self {
+        self.msg.data_format = Some(format);
+        self.msg.data_cid = Some(cid);
+        self
+    }
+
     pub fn schema(mut self, value: String) -> Self {
         self.msg.schema = Some(value);
         self
@@ -60,6 +82,19 @@ impl<'a> ActorWriteBuilder<'a> {
         self
     }
 
+    /// Adds a tag to the record.
+    pub fn tag(mut self, name: String, value: TagValue) -> Self {
+        self.msg.tags.get_or_insert_default().insert(name, value);
+        self
+    }
+
+    /// Sets the intended recipient of the record.
+    /// Cannot be changed by later updates.
+    pub fn recipient(mut self, value: Did) -> Self {
+        self.msg.recipient = Some(value);
+        self
+    }
+
     /// Whether to authorize the message.
     /// Defaults to `true`.
     pub fn auth(mut self, value: bool) -> Self {
@@ -67,6 +102,27 @@ impl<'a> ActorWriteBuilder<'a> {
         self
     }
 
+    /// Authorizes the message using a permission grant issued by the target.
+    pub fn permission_grant(mut self, grant_id: String) -> Self {
+        self.permission_grant = Some(grant_id);
+        self
+    }
+
+    /// Whether to encrypt the message data for the actor's encryption key.
+    /// Defaults to `false`.
+    pub fn encrypt(mut self, value: bool) -> Self {
+        self.encrypt = value;
+        self
+    }
+
+    /// Encrypts the message data for another DID, in addition to the actor.
+    /// The DID's key agreement key is resolved from its document.
+    pub fn encrypt_for(mut self, value: Did) -> Self {
+        self.encrypt = true;
+        self.encrypt_for.push(value);
+        self
+    }
+
     /// Whether to sign the message data.
     /// Defaults to `false`.
     pub fn sign(mut self, value: bool) -> Self {
@@ -74,7 +130,7 @@ impl<'a> ActorWriteBuilder<'a> {
         self
     }
 
-    /// Whether to sync the message with the actor's remote DWN after processing.
+    /// Whether to sync the message with the actor's remote DWNs after processing.
     /// Defaults to `true`.
     pub fn sync(mut self, value: bool) -> Self {
         self.sync = value;
@@ -88,25 +144,47 @@ impl<'a> ActorWriteBuilder<'a> {
         self
     }
 
-    fn build(self) -> anyhow::Result<Message> {
+    async fn build(mut self) -> anyhow::Result<Message> {
+        if self.encrypt
+            && let Some(data) = self.msg.data.take()
+        {
+            let key = self
+                .actor
+                .encryption_key
+                .as_ref()
+                .ok_or(anyhow::anyhow!("missing encryption key"))?;
+
+            let mut recipients = vec![(key.url.clone(), key.public_key())];
+
+            for did in &self.encrypt_for {
+                let recipient = resolve_key_agreement(did)
+                    .await
+                    .with_context(|| format!("resolve key agreement for {did}"))?;
+                recipients.push(recipient);
+            }
+
+            let jwe = encrypt(&data, &recipients).context("encrypt data")?;
+            self.msg.encrypted_data = Some(jwe);
+        }
+
         let mut msg = self.msg.build()?;
 
         if self.sign {
             self.actor.sign(&mut msg)?;
         }
         if self.auth {
-            self.actor.authorize(&mut msg)?;
+            self.actor
+                .authorize_with_grant(&mut msg, self.permission_grant)?;
         }
 
         Ok(msg)
     }
 
-    /// Sends the message to the actor's remote DWN.
+    /// Sends the message to the actor's primary remote DWN.
     pub async fn send_remote(self) -> anyhow::Result<String> {
         let url = self
             .actor
-            .remote
-            .as_ref()
+            .primary_remote()
             .ok_or(anyhow::anyhow!("no remote"))?;
         self.send(url).await
     }
@@ -117,7 +195,7 @@ impl<'a> ActorWriteBuilder<'a> {
         let actor = self.actor;
         let target = self.target.unwrap_or(&actor.did);
 
-        let msg = self.build()?;
+        let msg = self.build().await?;
         let id = msg.record_id.clone();
 
         actor.send(target, &msg, url).await?;
@@ -125,6 +203,18 @@ impl<'a> ActorWriteBuilder<'a> {
         Ok(id)
     }
 
+    /// Sends the message to every remote DWN of the actor.
+    /// Returns the written record ID, and the result for each remote.
+    pub async fn send_remotes(self) -> anyhow::Result<(String, Vec<RemoteResult>)> {
+        let actor = self.actor;
+        let target = self.target.unwrap_or(&actor.did);
+
+        let msg = self.build().await?;
+        let id = msg.record_id.clone();
+
+        Ok((id, actor.send_remotes(target, &msg).await))
+    }
+
     /// Processes the message with the actor's local DWN.
     /// Returns the written record ID.
     pub async fn process(self) -> anyhow::Result<String> {
@@ -132,19 +222,19 @@ impl<'a> ActorWriteBuilder<'a> {
         let actor = self.actor;
         let target = self.target.unwrap_or(&actor.did);
 
-        let msg = self.build()?;
+        let msg = self.build().await?;
         let id = msg.record_id.clone();
 
-        if sync && actor.remote.is_some() {
-            actor.send_remote(target, &msg).await?;
-        }
-
         actor
             .dwn
-            .process_message(target, msg)
+            .process_message(target, msg.clone())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to process message: {e}"))?;
 
+        if sync {
+            actor.enqueue(target, &msg)?;
+        }
+
         Ok(id)
     }
 }
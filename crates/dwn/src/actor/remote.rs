@@ -0,0 +1,66 @@
+use dwn_core::message::Message;
+use futures_util::future::join_all;
+use reqwest::Url;
+use xdid::core::did::Did;
+
+use crate::Actor;
+
+/// A remote DWN the actor syncs with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remote {
+    pub url: Url,
+    pub role: RemoteRole,
+}
+
+impl Remote {
+    pub fn primary(url: Url) -> Self {
+        Self {
+            url,
+            role: RemoteRole::Primary,
+        }
+    }
+
+    pub fn mirror(url: Url) -> Self {
+        Self {
+            url,
+            role: RemoteRole::Mirror,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteRole {
+    /// Serves remote reads, and must be reachable to sync.
+    #[default]
+    Primary,
+    /// Synced on a best-effort basis.
+    Mirror,
+}
+
+/// The result of sending a message to one remote.
+#[derive(Debug)]
+pub struct RemoteResult {
+    pub remote: Remote,
+    pub result: anyhow::Result<()>,
+}
+
+impl Actor {
+    /// URL of the first primary remote, used for remote reads.
+    pub fn primary_remote(&self) -> Option<&Url> {
+        self.remotes
+            .iter()
+            .find(|r| r.role == RemoteRole::Primary)
+            .map(|r| &r.url)
+    }
+
+    /// Sends the message to every remote concurrently.
+    pub(crate) async fn send_remotes(&self, target: &Did, msg: &Message) -> Vec<RemoteResult> {
+        join_all(self.remotes.iter().map(|remote| async move {
+            RemoteResult {
+                remote: remote.clone(),
+                result: self.send(target, msg, &remote.url).await.map(|_| ()),
+            }
+        }))
+        .await
+    }
+}
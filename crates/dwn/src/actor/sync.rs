@@ -1,13 +1,24 @@
+use std::collections::HashSet;
+
 use anyhow::{Context, bail};
 use dwn_core::{
-    message::{Message, descriptor::Descriptor},
-    reply::Reply,
+    message::{
+        Message,
+        descriptor::{
+            Descriptor, ProtocolFilter, RecordsSync, SYNC_TREE_DEPTH, sync_children, sync_key,
+        },
+    },
+    reply::{RecordsSyncReply, Reply},
 };
 use reqwest::Url;
 use tracing::warn;
 use xdid::core::did::Did;
 
-use crate::Actor;
+use crate::{Actor, handlers::records::sync::MAX_SYNC_NODES, remote::RemoteRole};
+
+/// Differing sync subtrees with at most this many records, across both DWNs,
+/// are compared record by record.
+const MAX_SYNC_LEAF_RECORDS: usize = 64;
 
 impl Actor {
     pub(crate) async fn send(
@@ -37,49 +48,99 @@ impl Actor {
         Ok(reply)
     }
 
-    pub(crate) async fn send_remote(
-        &self,
-        target: &Did,
-        msg: &Message,
-    ) -> anyhow::Result<Option<Reply>> {
-        let Some(url) = &self.remote else {
-            bail!("remote url not set")
-        };
-        let reply = self.send(target, msg, url).await?;
-        Ok(reply)
-    }
-
-    /// Full sync with the remote DWN.
-    /// If an actor is provided, it will be used to authorize the sync.
+    /// Full sync with every remote DWN.
+    /// Remotes converge through the local DWN: after one pass it has every
+    /// remote's changes, and a second pass sends them to the earlier remotes.
+    /// Fails if a primary remote could not be synced.
     pub async fn sync(&self) -> anyhow::Result<()> {
-        let descriptor = Descriptor::RecordsSync(Box::new(
-            self.dwn.record_store.prepare_sync(&self.did, true)?,
-        ));
+        // Deliver queued messages first, so they are not sent twice.
+        self.flush_outbox().await?;
 
-        let mut msg = Message {
-            record_id: descriptor.compute_entry_id()?,
-            context_id: None,
-            data: None,
-            descriptor,
-            attestation: None,
-            authorization: None,
-        };
+        let mut failed = Vec::new();
 
-        self.authorize(&mut msg)?;
+        for pass in 0..2 {
+            // The last remote was already synced with every change.
+            let remotes = match pass {
+                0 => &self.remotes[..],
+                _ => &self.remotes[..self.remotes.len().saturating_sub(1)],
+            };
 
-        let reply = match self.send_remote(&self.did, &msg).await? {
-            Some(Reply::RecordsSync(reply)) => reply,
-            other => {
-                bail!("invalid reply: {other:?}");
+            for remote in remotes {
+                if failed.contains(&remote.url) {
+                    continue;
+                }
+
+                if let Err(e) = self.sync_remote(&remote.url).await {
+                    match remote.role {
+                        RemoteRole::Primary => {
+                            return Err(e.context(format!("sync with remote {}", remote.url)));
+                        }
+                        RemoteRole::Mirror => {
+                            warn!("Failed to sync with mirror {}: {e:?}", remote.url);
+                            failed.push(remote.url.clone());
+                        }
+                    }
+                }
             }
+        }
+
+        Ok(())
+    }
+
+    /// Full sync with a remote DWN.
+    /// Protocol configurations are synced first, so protocol records can be processed.
+    /// Sync trees are then compared, so only differing records are exchanged.
+    pub async fn sync_remote(&self, url: &Url) -> anyhow::Result<()> {
+        self.sync_protocols(url).await?;
+
+        let prefixes = self.diff_sync_tree(url).await?;
+
+        if prefixes.is_empty() {
+            return Ok(());
+        }
+
+        let mut local = self.dwn.record_store.prepare_sync(&self.did, true)?;
+
+        let in_scope = |record_id: &str| {
+            let key = sync_key(record_id);
+            prefixes.iter().any(|p| key.starts_with(p.as_str()))
         };
 
+        local.local_records.retain(|r| in_scope(&r.record_id));
+        local.local_tombstones.retain(|t| in_scope(&t.record_id));
+
+        let reply = self
+            .send_sync(
+                url,
+                RecordsSync::new(local.local_records, local.local_tombstones)
+                    .with_prefixes(prefixes),
+            )
+            .await?;
+
+        // Apply remote deletions.
+        for entry in reply.tombstones {
+            if let Err(e) = self.dwn.process_message(&self.did, entry).await {
+                warn!("Failed to process message during DWN sync: {e:?}");
+            };
+        }
+
         // Process new records.
         for record in reply.remote_only {
-            if let Err(e) = self
-                .dwn
-                .process_message(&self.did, record.initial_entry)
-                .await
+            if !record.initial_entry.data_matches_cid() || !record.latest_entry.data_matches_cid() {
+                warn!(
+                    "Remote record {} data does not match data CID",
+                    record.initial_entry.record_id
+                );
+                continue;
+            }
+
+            // The stored initial entry has no data, so if it is also the latest
+            // entry only the latest is processed.
+            if record.latest_entry.descriptor.compute_entry_id()? != record.initial_entry.record_id
+                && let Err(e) = self
+                    .dwn
+                    .process_message(&self.did, record.initial_entry)
+                    .await
             {
                 warn!("Failed to process message during DWN sync: {e:?}");
                 continue;
@@ -96,6 +157,14 @@ impl Actor {
 
         // Process conflicting entries.
         for entry in reply.conflict {
+            if !entry.data_matches_cid() {
+                warn!(
+                    "Remote entry {} data does not match data CID",
+                    entry.record_id
+                );
+                continue;
+            }
+
             if let Err(e) = self.dwn.process_message(&self.did, entry).await {
                 warn!("Failed to process message during DWN sync: {e:?}");
             };
@@ -111,14 +180,157 @@ impl Actor {
                 continue;
             };
 
-            self.send_remote(&self.did, &record.initial_entry).await?;
-
+            // The stored initial entry has no data, so if it is also the latest
+            // entry only the latest is sent.
             if record.latest_entry.descriptor.compute_entry_id()? != record.initial_entry.record_id
             {
-                self.send_remote(&self.did, &record.latest_entry).await?;
+                self.send(&self.did, &record.initial_entry, url).await?;
+            }
+
+            self.send(&self.did, &record.latest_entry, url).await?;
+        }
+
+        // Send local deletions to remote.
+        let mut sent = HashSet::new();
+
+        for record_id in reply.local_tombstones {
+            let Some(tombstone) = self
+                .dwn
+                .record_store
+                .read_tombstone(&self.did, &record_id)?
+            else {
+                continue;
+            };
+
+            if !sent.insert(tombstone.descriptor.compute_entry_id()?) {
+                continue;
+            }
+
+            self.send(&self.did, &tombstone, url).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Exchanges `ProtocolsConfigure` messages with the remote.
+    /// If both DWNs configured the same protocol version differently,
+    /// the remote's configuration is kept.
+    async fn sync_protocols(&self, url: &Url) -> anyhow::Result<()> {
+        let protocols =
+            self.dwn
+                .record_store
+                .query_protocols(&self.did, &ProtocolFilter::default(), true)?;
+
+        let local_protocols = protocols
+            .iter()
+            .map(|p| p.descriptor.compute_entry_id())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let reply = self
+            .send_sync(url, RecordsSync::from_protocols(local_protocols.clone()))
+            .await?;
+
+        let mut received = HashSet::new();
+
+        for entry in reply.protocols {
+            if let Descriptor::ProtocolsConfigure(desc) = &entry.descriptor {
+                received.insert((
+                    desc.definition.protocol.clone(),
+                    desc.protocol_version.clone(),
+                ));
+            }
+
+            if let Err(e) = self.dwn.process_message(&self.did, entry).await {
+                warn!("Failed to process message during DWN sync: {e:?}");
+            };
+        }
+
+        for (entry, entry_id) in protocols.iter().zip(local_protocols) {
+            if !reply.local_protocols.contains(&entry_id) {
+                continue;
+            }
+
+            if let Descriptor::ProtocolsConfigure(desc) = &entry.descriptor
+                && received.contains(&(
+                    desc.definition.protocol.clone(),
+                    desc.protocol_version.clone(),
+                ))
+            {
+                continue;
             }
+
+            self.send(&self.did, entry, url).await?;
         }
 
         Ok(())
     }
+
+    /// Compares sync trees with the remote in rounds, returning the prefixes of
+    /// differing subtrees small enough to compare record by record.
+    /// Only subtrees that differ are walked into.
+    async fn diff_sync_tree(&self, url: &Url) -> anyhow::Result<Vec<String>> {
+        let mut pending = vec![String::new()];
+        let mut differing = Vec::new();
+
+        while !pending.is_empty() {
+            let round = std::mem::take(&mut pending);
+
+            for prefixes in round.chunks(MAX_SYNC_NODES) {
+                let nodes = self
+                    .dwn
+                    .record_store
+                    .sync_nodes(&self.did, prefixes, true)?;
+
+                let reply = self
+                    .send_sync(url, RecordsSync::from_nodes(nodes.clone()))
+                    .await?;
+
+                if reply.nodes.len() != nodes.len() {
+                    bail!("invalid sync tree reply");
+                }
+
+                for (local, remote) in nodes.into_iter().zip(reply.nodes) {
+                    if local.hash == remote.hash {
+                        continue;
+                    }
+
+                    if local.count + remote.count <= MAX_SYNC_LEAF_RECORDS
+                        || local.prefix.len() >= SYNC_TREE_DEPTH
+                    {
+                        differing.push(local.prefix);
+                    } else {
+                        pending.extend(sync_children(&local.prefix));
+                    }
+                }
+            }
+        }
+
+        Ok(differing)
+    }
+
+    async fn send_sync(
+        &self,
+        url: &Url,
+        sync: RecordsSync,
+    ) -> anyhow::Result<Box<RecordsSyncReply>> {
+        let descriptor = Descriptor::RecordsSync(Box::new(sync));
+
+        let mut msg = Message {
+            record_id: descriptor.compute_entry_id()?,
+            context_id: None,
+            data: None,
+            descriptor,
+            attestation: None,
+            authorization: None,
+        };
+
+        self.authorize(&mut msg)?;
+
+        match self.send(&self.did, &msg, url).await? {
+            Some(Reply::RecordsSync(reply)) => Ok(reply),
+            other => {
+                bail!("invalid reply: {other:?}");
+            }
+        }
+    }
 }
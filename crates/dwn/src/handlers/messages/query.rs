@@ -0,0 +1,37 @@
+use dwn_core::{message::descriptor::Descriptor, reply::MessagesQueryReply};
+use reqwest::StatusCode;
+use tracing::warn;
+
+use crate::ProcessContext;
+
+pub async fn handle(
+    ProcessContext {
+        rs,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<MessagesQueryReply, StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::MessagesQuery(_)));
+
+    if !validation.is_authorized(target, &msg) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let Descriptor::MessagesQuery(desc) = msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    rs.query_messages(
+        target,
+        &desc.filter.unwrap_or_default(),
+        desc.cursor,
+        desc.limit,
+    )
+    .map(|(messages, cursor)| MessagesQueryReply { messages, cursor })
+    .map_err(|e| {
+        warn!("Messages query failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
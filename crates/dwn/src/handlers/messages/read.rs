@@ -0,0 +1,35 @@
+use dwn_core::{message::descriptor::Descriptor, reply::MessagesReadReply};
+use reqwest::StatusCode;
+use tracing::warn;
+
+use crate::ProcessContext;
+
+pub async fn handle(
+    ProcessContext {
+        rs,
+        ds,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<MessagesReadReply, StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::MessagesRead(_)));
+
+    if !validation.is_authorized(target, &msg) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let Descriptor::MessagesRead(desc) = msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    let message = rs
+        .read_message(ds, target, &desc.message_cid)
+        .map_err(|e| {
+            warn!("Failed to read message {}: {:?}", desc.message_cid, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(MessagesReadReply { message })
+}
@@ -1,3 +1,5 @@
+pub mod messages;
+pub mod permissions;
 pub mod protocols;
 pub mod records;
 pub mod validation;
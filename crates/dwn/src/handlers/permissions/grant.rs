@@ -0,0 +1,42 @@
+use dwn_core::message::descriptor::{Descriptor, Interface};
+use reqwest::StatusCode;
+use tracing::debug;
+
+use super::{validate_record_id, write_error};
+use crate::ProcessContext;
+
+pub async fn handle(
+    ProcessContext {
+        rs,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<(), StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::PermissionsGrant(_)));
+
+    let Descriptor::PermissionsGrant(desc) = &msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    validate_record_id(&msg)?;
+
+    if !validation.authenticated.contains(target) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    if desc.scope.interface == Interface::Permissions {
+        debug!("Cannot grant a permission over the Permissions interface");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if desc.date_expires <= desc.message_timestamp {
+        debug!("Grant expires before it was created");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    rs.write_permission(target, msg).map_err(write_error)?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use dwn_core::{message::Message, store::StoreError};
+use reqwest::StatusCode;
+use tracing::{debug, warn};
+
+pub mod grant;
+pub mod request;
+pub mod revoke;
+
+/// Permissions are stored by record id, so it must be derived from the
+/// descriptor to stop one message from claiming another's id.
+fn validate_record_id(msg: &Message) -> Result<(), StatusCode> {
+    let entry_id = msg.descriptor.compute_entry_id().map_err(|e| {
+        debug!("Failed to compute entry id: {:?}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if entry_id != msg.record_id {
+        debug!(
+            "Record id does not match entry id: {} != {}",
+            msg.record_id, entry_id
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(())
+}
+
+fn write_error(e: StoreError) -> StatusCode {
+    match e {
+        StoreError::InvalidInput(e) => {
+            debug!("Permission rejected: {e}");
+            StatusCode::CONFLICT
+        }
+        e => {
+            warn!("Permission write failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use dwn_core::message::descriptor::{Descriptor, Interface};
+use reqwest::StatusCode;
+use tracing::debug;
+
+use super::{validate_record_id, write_error};
+use crate::ProcessContext;
+
+pub async fn handle(
+    ProcessContext {
+        rs,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<(), StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::PermissionsRequest(_)));
+
+    let Descriptor::PermissionsRequest(desc) = &msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    validate_record_id(&msg)?;
+
+    // Anyone may request a permission, but only for themselves.
+    if !validation.authenticated.contains(&desc.grantee) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    if desc.scope.interface == Interface::Permissions {
+        debug!("Cannot request a permission over the Permissions interface");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    rs.write_permission(target, msg).map_err(write_error)?;
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use dwn_core::message::descriptor::Descriptor;
+use reqwest::StatusCode;
+use tracing::{debug, warn};
+
+use super::{validate_record_id, write_error};
+use crate::ProcessContext;
+
+pub async fn handle(
+    ProcessContext {
+        rs,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<(), StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::PermissionsRevoke(_)));
+
+    let Descriptor::PermissionsRevoke(desc) = &msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    validate_record_id(&msg)?;
+
+    if !validation.authenticated.contains(target) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let grant = rs
+        .read_permission(target, &desc.permissions_grant_id)
+        .map_err(|e| {
+            warn!(
+                "Failed to read grant {}: {:?}",
+                desc.permissions_grant_id, e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !matches!(
+        grant.map(|m| m.descriptor),
+        Some(Descriptor::PermissionsGrant(_))
+    ) {
+        debug!("Grant {} not found", desc.permissions_grant_id);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let revocation = rs
+        .read_permission_revocation(target, &desc.permissions_grant_id)
+        .map_err(|e| {
+            warn!(
+                "Failed to read revocation for grant {}: {:?}",
+                desc.permissions_grant_id, e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if revocation.is_some() {
+        // Already revoked.
+        return Ok(());
+    }
+
+    rs.write_permission(target, msg).map_err(write_error)?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use dwn_core::message::descriptor::Descriptor;
 use reqwest::StatusCode;
-use tracing::warn;
+use tracing::{debug, warn};
 
 use crate::ProcessContext;
 
@@ -15,10 +15,19 @@ pub async fn handle(
 ) -> Result<(), StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::ProtocolsConfigure(_)));
 
-    if !validation.authenticated.contains(target) {
+    if !validation.is_authorized(target, &msg) {
         return Err(StatusCode::UNAUTHORIZED);
     }
 
+    let Descriptor::ProtocolsConfigure(desc) = &msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    if let Err(e) = desc.definition.validate() {
+        debug!("Invalid protocol definition: {e}");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     rs.configure_protocol(target, msg).map_err(|e| {
         warn!("Protocol configure failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
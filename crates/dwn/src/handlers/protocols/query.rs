@@ -1,14 +1,29 @@
 use dwn_core::message::{Message, descriptor::Descriptor};
 use reqwest::StatusCode;
+use tracing::warn;
 
 use crate::ProcessContext;
 
 pub async fn handle(
-    ProcessContext { msg, .. }: ProcessContext<'_>,
+    ProcessContext {
+        rs,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
 ) -> Result<Vec<Message>, StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::ProtocolsQuery(_)));
 
-    // TODO
+    let authorized = validation.is_authorized(target, &msg);
 
-    Ok(Vec::new())
+    let Descriptor::ProtocolsQuery(desc) = msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    rs.query_protocols(target, &desc.filter, authorized)
+        .map_err(|e| {
+            warn!("Protocol query failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
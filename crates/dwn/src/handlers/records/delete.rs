@@ -1,28 +1,95 @@
-use dwn_core::message::descriptor::Descriptor;
+use dwn_core::message::descriptor::{Can, Descriptor};
 use reqwest::StatusCode;
-use tracing::warn;
+use tracing::{debug, warn};
 
-use crate::ProcessContext;
+use crate::{ProcessContext, handlers::records::protocol};
 
 pub async fn handle(
     ProcessContext {
         rs,
         ds,
+        subscriptions,
         validation,
         target,
         msg,
+        ..
     }: ProcessContext<'_>,
 ) -> Result<(), StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::RecordsDelete(_)));
 
-    if !validation.authenticated.contains(target) {
-        return Err(StatusCode::UNAUTHORIZED);
+    let Descriptor::RecordsDelete(desc) = &msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    let record = rs.read(ds, target, &desc.record_id).map_err(|e| {
+        warn!("Failed to read record {}: {:?}", desc.record_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let descendants = match (&record, desc.prune) {
+        (Some(record), Some(true)) => {
+            rs.read_descendants(target, &record.initial_entry)
+                .map_err(|e| {
+                    warn!("Failed to read descendants of {}: {:?}", desc.record_id, e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?
+        }
+        _ => Vec::new(),
+    };
+
+    if !validation.is_authorized(target, &msg) {
+        let Some(record) = &record else {
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+
+        if !protocol::can_perform(
+            rs,
+            target,
+            &validation,
+            &record.latest_entry,
+            Some(record),
+            Can::Delete,
+        )? {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        // Pruning requires permission to delete every descendant.
+        for descendant in &descendants {
+            if !protocol::can_perform(
+                rs,
+                target,
+                &validation,
+                &descendant.latest_entry,
+                Some(descendant),
+                Can::Delete,
+            )? {
+                debug!(
+                    "Cannot prune descendant {}",
+                    descendant.latest_entry.record_id
+                );
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+        }
     }
 
-    rs.delete(ds, target, msg).map_err(|e| {
+    // Visibility may depend on ancestors, so is checked before they are removed.
+    let subscribers = match record {
+        Some(record) => {
+            let mut records = descendants;
+            records.push(record);
+            subscriptions.matching(rs, target, &records)
+        }
+        None => Vec::new(),
+    };
+
+    rs.delete(ds, target, msg.clone()).map_err(|e| {
         warn!("Failed to delete record: {e:?}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    for sender in subscribers {
+        let _ = sender.send(msg.clone());
+    }
+
     Ok(())
 }
@@ -1,5 +1,7 @@
 pub mod delete;
+mod protocol;
 pub mod query;
 pub mod read;
+pub mod subscribe;
 pub mod sync;
 pub mod write;
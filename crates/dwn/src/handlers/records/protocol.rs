@@ -0,0 +1,338 @@
+use dwn_core::{
+    message::{
+        Message, Version, VersionReq,
+        descriptor::{
+            Can, Descriptor, ProtocolDefinition, ProtocolStructure, ProtocolType, RecordFilter, Who,
+        },
+    },
+    store::{Record, RecordStore},
+};
+use reqwest::StatusCode;
+use tracing::debug;
+use xdid::core::did::Did;
+
+use crate::handlers::validation::ValidationResult;
+
+/// Finds the protocol type a `RecordsWrite` is declared as.
+///
+/// The type is named by the last segment of the protocol path.
+pub fn find_type(
+    rs: &dyn RecordStore,
+    target: &Did,
+    entry: &Message,
+) -> Result<Option<ProtocolType>, StatusCode> {
+    let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
+        panic!("invalid descriptor: {:?}", entry.descriptor);
+    };
+
+    let (Some(protocol), Some(version), Some(path)) =
+        (&desc.protocol, &desc.protocol_version, &desc.protocol_path)
+    else {
+        return Ok(None);
+    };
+
+    let definition = find_definition(rs, target, protocol, version)?;
+
+    let name = path.rsplit("/").next().unwrap_or_default();
+
+    let Some(ty) = definition.types.get(name) else {
+        debug!("No protocol type for path: {path}");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    Ok(Some(ty.clone()))
+}
+
+/// Whether the protocol rules allow an action on a record.
+///
+/// `entry` is the `RecordsWrite` the action applies to, and `record` is the
+/// stored record, if one exists.
+pub fn can_perform(
+    rs: &dyn RecordStore,
+    target: &Did,
+    validation: &ValidationResult,
+    entry: &Message,
+    record: Option<&Record>,
+    can: Can,
+) -> Result<bool, StatusCode> {
+    let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
+        panic!("invalid descriptor: {:?}", entry.descriptor);
+    };
+
+    let Some(protocol) = &desc.protocol else {
+        return Ok(false);
+    };
+
+    let Some(version) = &desc.protocol_version else {
+        debug!("Protocol version not supplied");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let Some(path) = &desc.protocol_path else {
+        debug!("Protocol path not supplied");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let definition = find_definition(rs, target, protocol, version)?;
+
+    let mut structure: Option<&ProtocolStructure> = None;
+    let parts = path.split("/").collect::<Vec<_>>();
+
+    for part in &parts {
+        let structures = match structure {
+            Some(s) => &s.children,
+            None => &definition.structure,
+        };
+
+        let Some(s) = structures.get(*part) else {
+            debug!("Invalid path: {path}");
+            return Err(StatusCode::BAD_REQUEST);
+        };
+
+        structure = Some(s);
+    }
+
+    let Some(structure) = structure else {
+        debug!("Invalid path: {path}");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let Some(actions) = &structure.actions else {
+        debug!("No structure actions: {path}");
+        return Ok(false);
+    };
+
+    for action in actions {
+        if !action.can.contains(&can) {
+            continue;
+        }
+
+        let of_record = match &action.of {
+            Some(of) => match find_ancestor(rs, target, &parts, entry, of)? {
+                Some(m) => Some(m),
+                None => continue,
+            },
+            None => None,
+        };
+
+        match action.who {
+            Who::Anyone => return Ok(true),
+            Who::Author => {
+                let sigs = if let Some(of_record) = of_record {
+                    of_record
+                        .authorization
+                        .map(|a| a.signatures)
+                        .unwrap_or_default()
+                } else if let Some(record) = record {
+                    [&record.initial_entry, &record.latest_entry]
+                        .into_iter()
+                        .flat_map(|m| m.authorization.as_ref().map(|a| a.signatures.clone()))
+                        .flatten()
+                        .collect::<Vec<_>>()
+                } else {
+                    continue;
+                };
+
+                if sigs
+                    .iter()
+                    .any(|sig| validation.authenticated.contains(&sig.header.kid.did))
+                {
+                    return Ok(true);
+                }
+            }
+            Who::Recipient => {
+                // The recipient of a new record is chosen by its author,
+                // so it may only be used for existing records or ancestors.
+                let recipient_entry = match (&of_record, record) {
+                    (Some(m), _) => m,
+                    (None, Some(r)) => &r.initial_entry,
+                    (None, None) => continue,
+                };
+
+                let Descriptor::RecordsWrite(recipient_desc) = &recipient_entry.descriptor else {
+                    continue;
+                };
+
+                if let Some(recipient) = &recipient_desc.recipient
+                    && validation.authenticated.contains(recipient)
+                {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Validates the context ID of a protocol `RecordsWrite`.
+///
+/// Each segment of the context ID must name an existing record in the same
+/// protocol and a compatible version, at the matching ancestor protocol path.
+pub fn validate_context(
+    rs: &dyn RecordStore,
+    target: &Did,
+    entry: &Message,
+    max_depth: usize,
+) -> Result<(), StatusCode> {
+    let Descriptor::RecordsWrite(desc) = &entry.descriptor else {
+        panic!("invalid descriptor: {:?}", entry.descriptor);
+    };
+
+    let Some(path) = &desc.protocol_path else {
+        debug!("Protocol path not supplied");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let parts = path.split("/").collect::<Vec<_>>();
+
+    if parts.len() > max_depth {
+        debug!("Protocol path exceeds max depth of {max_depth}: {path}");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let context = entry
+        .context_id
+        .as_deref()
+        .map(|c| c.split("/").collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if context.len() != parts.len() - 1 {
+        debug!(
+            "Context id {:?} does not match protocol path {path}",
+            entry.context_id
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    for (i, ancestor_id) in context.iter().enumerate() {
+        let ancestor = match rs.query(
+            target,
+            &RecordFilter {
+                record_id: Some(ancestor_id.to_string()),
+                ..Default::default()
+            },
+            None,
+            true,
+        ) {
+            Ok(res) => match res.into_iter().next().map(|r| r.latest_entry) {
+                Some(m) => m,
+                None => {
+                    debug!("Ancestor record {ancestor_id} not found");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+            },
+            Err(e) => {
+                debug!("Could not find ancestor record: {e}");
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        let Descriptor::RecordsWrite(ancestor_desc) = &ancestor.descriptor else {
+            debug!("Ancestor {ancestor_id} is not a RecordsWrite");
+            return Err(StatusCode::BAD_REQUEST);
+        };
+
+        let ancestor_path = parts[..=i].join("/");
+
+        let compatible = match (&ancestor_desc.protocol_version, &desc.protocol_version) {
+            (Some(a), Some(b)) => is_compatible_upgrade(a, b) || is_compatible_upgrade(b, a),
+            (a, b) => a == b,
+        };
+
+        if ancestor_desc.protocol != desc.protocol
+            || !compatible
+            || ancestor_desc.protocol_path.as_deref() != Some(ancestor_path.as_str())
+        {
+            debug!("Ancestor {ancestor_id} is not at protocol path {ancestor_path}");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let ancestor_context = (i > 0).then(|| context[..i].join("/"));
+
+        if ancestor.context_id != ancestor_context {
+            debug!(
+                "Ancestor {ancestor_id} has context id {:?}, expected {:?}",
+                ancestor.context_id, ancestor_context
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a record at protocol version `from` may be updated to `to`.
+///
+/// Upgrades may not go backwards or cross a semver-incompatible boundary.
+pub fn is_compatible_upgrade(from: &Version, to: &Version) -> bool {
+    to >= from
+        && VersionReq::parse(&format!("^{from}"))
+            .map(|req| req.matches(to))
+            .unwrap_or_default()
+}
+
+fn find_definition(
+    rs: &dyn RecordStore,
+    target: &Did,
+    protocol: &str,
+    version: &Version,
+) -> Result<ProtocolDefinition, StatusCode> {
+    match rs.query_protocol(target, protocol.to_string(), vec![version.clone()], true) {
+        Ok(found) => match found.into_iter().next().map(|x| x.1) {
+            Some(d) => Ok(d),
+            None => {
+                debug!("Protocol {protocol} not found");
+                Err(StatusCode::NOT_FOUND)
+            }
+        },
+        Err(e) => {
+            debug!("Could not find protocol: {e}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Finds the ancestor record of `entry` at the protocol path segment `of`.
+fn find_ancestor(
+    rs: &dyn RecordStore,
+    target: &Did,
+    parts: &[&str],
+    entry: &Message,
+    of: &str,
+) -> Result<Option<Message>, StatusCode> {
+    let Some(of_i) = parts.iter().rposition(|p| *p == of) else {
+        return Ok(None);
+    };
+
+    let Some(context_id) = &entry.context_id else {
+        return Ok(None);
+    };
+
+    let Some(of_id) = context_id.split("/").nth(of_i) else {
+        debug!("Invalid context id");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    match rs.query(
+        target,
+        &RecordFilter {
+            record_id: Some(of_id.to_string()),
+            ..Default::default()
+        },
+        None,
+        true,
+    ) {
+        Ok(res) => match res.into_iter().next().map(|r| r.latest_entry) {
+            Some(m) => Ok(Some(m)),
+            None => {
+                debug!("Target record {of_id} not found");
+                Err(StatusCode::NOT_FOUND)
+            }
+        },
+        Err(e) => {
+            debug!("Could not find target record: {e}");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
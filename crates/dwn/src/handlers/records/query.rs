@@ -1,8 +1,16 @@
-use dwn_core::{message::descriptor::Descriptor, reply::RecordsQueryReply};
+use dwn_core::{
+    message::descriptor::{Can, Descriptor, Pagination, QueryCursor},
+    reply::RecordsQueryReply,
+    store::{Record, RecordStore, StoreError},
+};
 use reqwest::StatusCode;
-use tracing::warn;
+use tracing::{debug, warn};
+use xdid::core::did::Did;
 
-use crate::ProcessContext;
+use crate::{
+    ProcessContext,
+    handlers::{records::protocol, validation::ValidationResult},
+};
 
 pub async fn handle(
     ProcessContext {
@@ -15,16 +23,99 @@ pub async fn handle(
 ) -> Result<RecordsQueryReply, StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::RecordsQuery(_)));
 
+    let authorized = validation.is_authorized(target, &msg);
+
     let Descriptor::RecordsQuery(desc) = msg.descriptor else {
         panic!("invalid descriptor: {:?}", msg.descriptor);
     };
 
-    let authorized = validation.authenticated.contains(target);
+    let filter = desc.filter.unwrap_or_default();
+
+    // Fetch one extra entry to know whether another page follows.
+    let limit = desc.pagination.as_ref().and_then(|p| p.limit);
+    let mut pagination = Pagination {
+        limit: limit.map(|l| l.saturating_add(1)),
+        cursor: desc.pagination.and_then(|p| p.cursor),
+    };
+
+    // Unpublished records may be visible through protocol rules, which are
+    // checked per entry. Keep fetching pages until enough entries are visible.
+    let mut entries = Vec::new();
+
+    loop {
+        let page = rs
+            .query(target, &filter, Some(&pagination), true)
+            .map_err(|e| match e {
+                StoreError::InvalidInput(e) => {
+                    debug!("Invalid query: {e}");
+                    StatusCode::BAD_REQUEST
+                }
+                e => {
+                    warn!("Query failed: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            })?;
+
+        let exhausted = pagination.limit.is_none_or(|l| page.len() < l);
+
+        pagination.cursor = page
+            .last()
+            .and_then(QueryCursor::from_record)
+            .map(|c| c.encode());
+
+        for record in page {
+            if authorized || can_query(rs, target, &validation, &record)? {
+                entries.push(record);
+            }
+        }
+
+        if exhausted || limit.is_some_and(|l| entries.len() > l) {
+            break;
+        }
+    }
+
+    let cursor = match limit {
+        Some(limit) if entries.len() > limit => {
+            entries.truncate(limit);
+            entries
+                .last()
+                .and_then(QueryCursor::from_record)
+                .map(|c| c.encode())
+        }
+        _ => None,
+    };
 
-    rs.query(target, &desc.filter.unwrap_or_default(), authorized)
-        .map(|entries| RecordsQueryReply { entries })
-        .map_err(|e| {
-            warn!("Query failed: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })
+    let entries = entries.into_iter().map(|r| r.latest_entry).collect();
+
+    Ok(RecordsQueryReply { entries, cursor })
+}
+
+/// Whether protocol rules allow the record to be queried.
+/// Uses the record from the query, so no data is loaded.
+pub(crate) fn can_query(
+    rs: &dyn RecordStore,
+    target: &Did,
+    validation: &ValidationResult,
+    record: &Record,
+) -> Result<bool, StatusCode> {
+    let Descriptor::RecordsWrite(desc) = &record.latest_entry.descriptor else {
+        return Ok(false);
+    };
+
+    if desc.published == Some(true) {
+        return Ok(true);
+    }
+
+    if desc.protocol.is_none() {
+        return Ok(false);
+    }
+
+    protocol::can_perform(
+        rs,
+        target,
+        validation,
+        &record.latest_entry,
+        Some(record),
+        Can::Query,
+    )
 }
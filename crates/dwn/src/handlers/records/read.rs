@@ -1,8 +1,14 @@
-use dwn_core::{message::descriptor::Descriptor, reply::RecordsReadReply};
+use dwn_core::{
+    message::{
+        Message,
+        descriptor::{Can, Descriptor},
+    },
+    reply::RecordsReadReply,
+};
 use reqwest::StatusCode;
 use tracing::warn;
 
-use crate::ProcessContext;
+use crate::{ProcessContext, handlers::records::protocol};
 
 pub async fn handle(
     ProcessContext {
@@ -11,10 +17,13 @@ pub async fn handle(
         validation,
         target,
         msg,
+        ..
     }: ProcessContext<'_>,
 ) -> Result<RecordsReadReply, StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::RecordsRead(_)));
 
+    let tenant = validation.is_authorized(target, &msg);
+
     let Descriptor::RecordsRead(desc) = msg.descriptor else {
         panic!("invalid descriptor: {:?}", msg.descriptor);
     };
@@ -24,19 +33,61 @@ pub async fn handle(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let authorized = validation.authenticated.contains(target);
-
-    Ok(RecordsReadReply {
-        entry: record.map(|r| r.latest_entry).and_then(|m| {
-            if let Descriptor::RecordsWrite(d) = &m.descriptor {
-                if d.published != Some(true) && !authorized {
-                    None
-                } else {
-                    Some(m)
-                }
-            } else {
-                None
+    let Some(record) = record else {
+        return Ok(RecordsReadReply {
+            entry: None,
+            history: Vec::new(),
+        });
+    };
+
+    // Each entry is authorized on its own descriptor,
+    // as past entries may have been private.
+    let can_read = |entry: &Message| -> Result<bool, StatusCode> {
+        let Descriptor::RecordsWrite(d) = &entry.descriptor else {
+            return Ok(false);
+        };
+
+        Ok(tenant
+            || d.published == Some(true)
+            || protocol::can_perform(rs, target, &validation, entry, Some(&record), Can::Read)?)
+    };
+
+    if !can_read(&record.latest_entry)? {
+        return Ok(RecordsReadReply {
+            entry: None,
+            history: Vec::new(),
+        });
+    }
+
+    let entry = match &desc.entry_id {
+        Some(entry_id) => rs
+            .read_entry(ds, target, &desc.record_id, entry_id)
+            .map_err(|e| {
+                warn!("Failed to read entry {entry_id}: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+        None => Some(record.latest_entry.clone()),
+    };
+
+    let entry = match entry {
+        Some(entry) if can_read(&entry)? => Some(entry),
+        _ => None,
+    };
+
+    let mut history = Vec::new();
+
+    if desc.history == Some(true) {
+        let entries = rs.read_history(target, &desc.record_id).map_err(|e| {
+            warn!("Failed to read history {}: {:?}", desc.record_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        for entry in entries {
+            if can_read(&entry)? {
+                history.push(entry);
             }
-        }),
-    })
+        }
+    }
+
+    Ok(RecordsReadReply { entry, history })
 }
@@ -0,0 +1,29 @@
+use dwn_core::message::descriptor::Descriptor;
+use reqwest::StatusCode;
+
+use crate::{ProcessContext, RecordSubscription};
+
+pub async fn handle(
+    ProcessContext {
+        subscriptions,
+        validation,
+        target,
+        msg,
+        ..
+    }: ProcessContext<'_>,
+) -> Result<RecordSubscription, StatusCode> {
+    debug_assert!(matches!(msg.descriptor, Descriptor::RecordsSubscribe(_)));
+
+    let authorized = validation.is_authorized(target, &msg);
+
+    let Descriptor::RecordsSubscribe(desc) = msg.descriptor else {
+        panic!("invalid descriptor: {:?}", msg.descriptor);
+    };
+
+    Ok(subscriptions.add(
+        target.clone(),
+        desc.filter.unwrap_or_default(),
+        authorized,
+        validation,
+    ))
+}
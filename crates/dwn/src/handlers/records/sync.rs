@@ -1,9 +1,18 @@
-use dwn_core::{message::descriptor::Descriptor, reply::RecordsSyncReply, store::StoreError};
+use std::collections::HashSet;
+
+use dwn_core::{
+    message::descriptor::{Descriptor, ProtocolFilter, SYNC_TREE_DEPTH, sync_key},
+    reply::RecordsSyncReply,
+    store::StoreError,
+};
 use reqwest::StatusCode;
-use tracing::warn;
+use tracing::{debug, warn};
 
 use crate::ProcessContext;
 
+/// Maximum number of sync tree nodes compared per message.
+pub(crate) const MAX_SYNC_NODES: usize = 4096;
+
 pub async fn handle(
     ProcessContext {
         rs,
@@ -11,27 +20,97 @@ pub async fn handle(
         validation,
         target,
         msg,
+        ..
     }: ProcessContext<'_>,
 ) -> Result<RecordsSyncReply, StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::RecordsSync(_)));
 
+    let authorized = validation.is_authorized(target, &msg);
+
     let Descriptor::RecordsSync(desc) = msg.descriptor else {
         panic!("invalid descriptor: {:?}", msg.descriptor);
     };
 
-    let authorized = validation.authenticated.contains(target);
-
     let mut reply = RecordsSyncReply {
         conflict: Vec::new(),
         local_only: Vec::new(),
         remote_only: Vec::new(),
+        tombstones: Vec::new(),
+        local_tombstones: Vec::new(),
+        nodes: Vec::new(),
+        protocols: Vec::new(),
+        local_protocols: Vec::new(),
     };
 
+    // Compare protocol configurations.
+    if let Some(remote_protocols) = desc.local_protocols {
+        let protocols = rs
+            .query_protocols(target, &ProtocolFilter::default(), authorized)
+            .map_err(|e| {
+                warn!("Failed to query protocols {}: {:?}", msg.record_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        let mut local_protocols = HashSet::new();
+
+        for entry in protocols {
+            let entry_id = entry.descriptor.compute_entry_id().map_err(|e| {
+                warn!("Failed to compute entry id {}: {:?}", msg.record_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+            if !remote_protocols.contains(&entry_id) {
+                reply.protocols.push(entry);
+            }
+
+            local_protocols.insert(entry_id);
+        }
+
+        reply.local_protocols = remote_protocols
+            .into_iter()
+            .filter(|id| !local_protocols.contains(id))
+            .collect();
+
+        return Ok(reply);
+    }
+
+    // Compare sync tree nodes.
+    if !desc.nodes.is_empty() {
+        if desc.nodes.len() > MAX_SYNC_NODES {
+            debug!("Too many sync nodes: {}", desc.nodes.len());
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        if desc.nodes.iter().any(|n| n.prefix.len() > SYNC_TREE_DEPTH) {
+            debug!("Sync node prefix too long");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let prefixes = desc.nodes.into_iter().map(|n| n.prefix).collect::<Vec<_>>();
+
+        reply.nodes = rs.sync_nodes(target, &prefixes, authorized).map_err(|e| {
+            warn!("Failed to read sync nodes {}: {:?}", msg.record_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        return Ok(reply);
+    }
+
     let mut local = rs.prepare_sync(target, authorized).map_err(|e| {
         warn!("Failed to prepare sync {}: {:?}", msg.record_id, e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    if let Some(prefixes) = &desc.prefixes {
+        let in_scope = |record_id: &str| {
+            let key = sync_key(record_id);
+            prefixes.iter().any(|p| key.starts_with(p.as_str()))
+        };
+
+        local.local_records.retain(|r| in_scope(&r.record_id));
+        local.local_tombstones.retain(|t| in_scope(&t.record_id));
+    }
+
     for record in desc.local_records {
         // Remove from local records.
         if let Some(found_idx) = local
@@ -42,6 +121,15 @@ pub async fn handle(
             local.local_records.remove(found_idx);
         };
 
+        // Deleted records are resolved with tombstones below.
+        if local
+            .local_tombstones
+            .iter()
+            .any(|t| t.record_id == record.record_id)
+        {
+            continue;
+        }
+
         // Process given record.
         if let Some(found) = rs.read(ds, target, &record.record_id).map_err(|e| {
             warn!("Failed to read record {}: {:?}", msg.record_id, e);
@@ -64,9 +152,48 @@ pub async fn handle(
         };
     }
 
+    let remote_tombstones = desc
+        .local_tombstones
+        .iter()
+        .map(|t| t.record_id.as_str())
+        .collect::<HashSet<_>>();
+
+    // Send tombstones the remote is missing, once per delete message.
+    let mut sent = HashSet::new();
+
+    for tombstone in &local.local_tombstones {
+        if remote_tombstones.contains(tombstone.record_id.as_str())
+            || !sent.insert(tombstone.latest_entry_id.clone())
+        {
+            continue;
+        }
+
+        match rs.read_tombstone(target, &tombstone.record_id) {
+            Ok(Some(entry)) => reply.tombstones.push(entry),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to read tombstone {}: {:?}", tombstone.record_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
+    let local_tombstones = local
+        .local_tombstones
+        .iter()
+        .map(|t| t.record_id.as_str())
+        .collect::<HashSet<_>>();
+
+    reply.local_tombstones = remote_tombstones
+        .iter()
+        .filter(|id| !local_tombstones.contains(*id))
+        .map(|id| id.to_string())
+        .collect();
+
     reply.remote_only = local
         .local_records
         .into_iter()
+        .filter(|r| !remote_tombstones.contains(r.record_id.as_str()))
         .map(|id| match rs.read(ds, target, &id.record_id) {
             Ok(Some(r)) => Ok(r),
             Ok(None) => Err(StoreError::BackendError(
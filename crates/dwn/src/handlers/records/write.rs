@@ -1,29 +1,35 @@
 use std::str::FromStr;
 
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
-use dwn_core::message::{
-    data::Data,
-    descriptor::{Can, Descriptor, ProtocolStructure, RecordFilter, Who},
-    mime::APPLICATION_JSON,
+use dwn_core::{
+    message::{
+        data::Data,
+        descriptor::{Can, Descriptor},
+        mime::APPLICATION_JSON,
+    },
+    store::{Record, has_blocks, read_blocks},
 };
 use reqwest::StatusCode;
 use serde_json::Value;
 use tracing::{debug, error, warn};
 
-use crate::ProcessContext;
+use crate::{ProcessContext, handlers::records::protocol};
 
 pub async fn handle(
     ProcessContext {
         rs,
         ds,
+        subscriptions,
         validation,
         target,
         msg,
+        max_context_depth,
+        max_history,
     }: ProcessContext<'_>,
 ) -> Result<(), StatusCode> {
     debug_assert!(matches!(msg.descriptor, Descriptor::RecordsWrite(_)));
 
-    let mut authenticated = validation.authenticated.contains(target);
+    let mut authenticated = validation.is_authorized(target, &msg);
 
     let computed_entry_id = msg.descriptor.compute_entry_id().map_err(|e| {
         debug!("Failed to compute entry id: {:?}", e);
@@ -59,6 +65,22 @@ pub async fn handle(
             return Err(StatusCode::BAD_REQUEST);
         }
 
+        if desc.recipient != initial_desc.recipient {
+            debug!(
+                "Recipient does not match: {:?} != {:?}",
+                desc.recipient, initial_desc.recipient
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        if msg.context_id != prev.initial_entry.context_id {
+            debug!(
+                "Context id does not match: {:?} != {:?}",
+                msg.context_id, prev.initial_entry.context_id
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
         if desc.protocol != initial_desc.protocol {
             debug!(
                 "Protocol does not match: {:?} != {:?}",
@@ -75,10 +97,24 @@ pub async fn handle(
             return Err(StatusCode::BAD_REQUEST);
         }
 
-        if desc.protocol_version != initial_desc.protocol_version {
+        // Protocol versions may only be upgraded to semver-compatible versions.
+        let prev_version = match &prev.latest_entry.descriptor {
+            Descriptor::RecordsWrite(d) => d.protocol_version.as_ref(),
+            _ => initial_desc.protocol_version.as_ref(),
+        };
+
+        let version_valid = match (prev_version, &desc.protocol_version) {
+            (Some(prev_version), Some(version)) => {
+                protocol::is_compatible_upgrade(prev_version, version)
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !version_valid {
             debug!(
-                "Protocol version does not match: {:?} != {:?}",
-                desc.protocol_version, initial_desc.protocol_version
+                "Protocol version is not a compatible upgrade: {:?} -> {:?}",
+                prev_version, desc.protocol_version
             );
             return Err(StatusCode::BAD_REQUEST);
         }
@@ -118,61 +154,8 @@ pub async fn handle(
     }
 
     // Validate protocol.
-    if let Some(protocol) = &desc.protocol {
-        let Some(version) = &desc.protocol_version else {
-            debug!("Protocol version not supplied");
-            return Err(StatusCode::BAD_REQUEST);
-        };
-
-        let Some(path) = &desc.protocol_path else {
-            debug!("Protocol path not supplied");
-            return Err(StatusCode::BAD_REQUEST);
-        };
-
-        let definition =
-            match rs.query_protocol(target, protocol.clone(), vec![version.clone()], true) {
-                Ok(found) => match found.into_iter().next().map(|x| x.1) {
-                    Some(d) => d,
-                    None => {
-                        debug!("Protocol {protocol} not found");
-                        return Err(StatusCode::NOT_FOUND);
-                    }
-                },
-                Err(e) => {
-                    debug!("Could not find protocol: {e}");
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
-                }
-            };
-
-        let mut structure: Option<&ProtocolStructure> = None;
-        let parts = path.split("/").collect::<Vec<_>>();
-
-        for part in &parts {
-            let structures = match structure {
-                Some(s) => &s.children,
-                None => &definition.structure,
-            };
-
-            let Some(s) = structures.get(*part) else {
-                debug!("Invalid path: {path}");
-                return Err(StatusCode::BAD_REQUEST);
-            };
-
-            structure = Some(s);
-        }
-
-        let Some(structure) = structure else {
-            debug!("Invalid path: {path}");
-            return Err(StatusCode::BAD_REQUEST);
-        };
-
-        let Some(actions) = &structure.actions else {
-            debug!("No structure actions: {path}");
-            return Err(StatusCode::BAD_REQUEST);
-        };
-
-        // TODO: Validate full context ID path
-        // TODO: Enforce max context depth
+    if desc.protocol.is_some() {
+        protocol::validate_context(rs, target, &msg, max_context_depth)?;
 
         let can = if latest_entry.is_some() {
             Can::Update
@@ -180,103 +163,46 @@ pub async fn handle(
             Can::Create
         };
 
-        let mut can_write = false;
+        if !protocol::can_perform(rs, target, &validation, &msg, latest_entry.as_ref(), can)? {
+            debug!("Cannot write according to protocol rules");
+            return Err(StatusCode::BAD_REQUEST);
+        }
 
-        for action in actions {
-            if !action.can.contains(&can) {
-                continue;
-            }
+        authenticated = true;
+    }
 
-            match action.who {
-                Who::Anyone => {
-                    can_write = true;
-                    break;
-                }
-                Who::Author => {
-                    let of_sigs = if let Some(of) = &action.of {
-                        let mut of_i = None;
-
-                        for (i, prev) in parts.iter().enumerate().rev() {
-                            if prev == of {
-                                of_i = Some(i);
-                                break;
-                            }
-                        }
-
-                        let Some(of_i) = of_i else {
-                            continue;
-                        };
-
-                        let Some(context_id) = &msg.context_id else {
-                            continue;
-                        };
-
-                        let Some(of_id) = context_id.split("/").nth(of_i) else {
-                            debug!("Invalid context id");
-                            return Err(StatusCode::BAD_REQUEST);
-                        };
-
-                        let target = match rs.query(
-                            target,
-                            &RecordFilter {
-                                record_id: Some(of_id.to_string()),
-                                ..Default::default()
-                            },
-                            true,
-                        ) {
-                            Ok(res) => match res.into_iter().next() {
-                                Some(m) => m,
-                                None => {
-                                    debug!("Target record {of_id} not found");
-                                    return Err(StatusCode::NOT_FOUND);
-                                }
-                            },
-                            Err(e) => {
-                                debug!("Could not find target record: {e}");
-                                return Err(StatusCode::BAD_REQUEST);
-                            }
-                        };
-
-                        target
-                            .authorization
-                            .map(|a| a.signatures)
-                            .unwrap_or_default()
-                    } else if let Some(entry) = &latest_entry {
-                        [&entry.initial_entry, &entry.latest_entry]
-                            .into_iter()
-                            .flat_map(|m| m.authorization.as_ref().map(|a| a.signatures.clone()))
-                            .flatten()
-                            .collect::<Vec<_>>()
-                    } else {
-                        continue;
-                    };
-
-                    for sig in of_sigs {
-                        if validation.authenticated.contains(&sig.header.kid.did) {
-                            can_write = true;
-                            break;
-                        }
-                    }
-                }
-                Who::Recipient => {
-                    if validation.authenticated.contains(target) {
-                        can_write = true;
-                        break;
-                    }
-                }
-            }
-        }
+    // Validate data format and schema against the protocol type.
+    let protocol_type = protocol::find_type(rs, target, &msg)?;
 
-        if !can_write {
-            debug!("Cannot write according to protocol rules");
+    if let Some(ty) = &protocol_type {
+        if !desc
+            .data_format
+            .as_ref()
+            .is_some_and(|f| ty.data_format.contains(f))
+        {
+            debug!(
+                "Data format not allowed by protocol type: {:?} not in {:?}",
+                desc.data_format, ty.data_format
+            );
             return Err(StatusCode::BAD_REQUEST);
         }
 
-        authenticated = true;
+        if desc.schema.is_some() && desc.schema != ty.schema {
+            debug!(
+                "Schema does not match protocol type: {:?} != {:?}",
+                desc.schema, ty.schema
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
     }
 
     // Validate data conforms to schema.
-    if let Some(schema_url) = &desc.schema {
+    let schema = desc
+        .schema
+        .as_ref()
+        .or(protocol_type.as_ref().and_then(|ty| ty.schema.as_ref()));
+
+    if let Some(schema_url) = schema {
         if desc.data_format != Some(APPLICATION_JSON) {
             debug!(
                 "Message has schema, but data format is not application/json: {:?}",
@@ -308,29 +234,41 @@ pub async fn handle(
             StatusCode::BAD_REQUEST
         })?;
 
-        let value = match &msg.data {
-            Some(Data::Base64(d)) => {
-                let decoded = BASE64_URL_SAFE_NO_PAD.decode(d).map_err(|e| {
-                    debug!("Failed to base64 decode data: {e:?}");
-                    StatusCode::BAD_REQUEST
-                })?;
-                let utf8 = String::from_utf8(decoded).map_err(|e| {
-                    debug!("Failed to parse data as utf8: {e:?}");
-                    StatusCode::BAD_REQUEST
-                })?;
-                Value::from_str(&utf8).map_err(|e| {
-                    debug!("Failed to parse data as JSON: {e:?}");
-                    StatusCode::BAD_REQUEST
-                })?
-            }
+        let decoded = match &msg.data {
+            Some(Data::Base64(d)) => BASE64_URL_SAFE_NO_PAD.decode(d).map_err(|e| {
+                debug!("Failed to base64 decode data: {e:?}");
+                StatusCode::BAD_REQUEST
+            })?,
             Some(Data::Encrypted(_)) => {
                 // TODO: Store the message without validation?
                 return Err(StatusCode::BAD_REQUEST);
             }
             None => {
-                return Err(StatusCode::BAD_REQUEST);
+                // Validate data uploaded as blocks.
+                let Some(data_cid) = &desc.data_cid else {
+                    debug!("Message has schema, but no data");
+                    return Err(StatusCode::BAD_REQUEST);
+                };
+
+                read_blocks(ds, target, data_cid)
+                    .map_err(|e| {
+                        debug!("Failed to read data blocks {data_cid}: {e:?}");
+                        StatusCode::BAD_REQUEST
+                    })?
+                    .ok_or_else(|| {
+                        debug!("Data blocks not found: {data_cid}");
+                        StatusCode::BAD_REQUEST
+                    })?
             }
         };
+        let utf8 = String::from_utf8(decoded).map_err(|e| {
+            debug!("Failed to parse data as utf8: {e:?}");
+            StatusCode::BAD_REQUEST
+        })?;
+        let value = Value::from_str(&utf8).map_err(|e| {
+            debug!("Failed to parse data as JSON: {e:?}");
+            StatusCode::BAD_REQUEST
+        })?;
 
         if !validator.is_valid(&value) {
             debug!("Data does not fulfill schema.");
@@ -342,10 +280,70 @@ pub async fn handle(
         return Err(StatusCode::UNAUTHORIZED);
     }
 
-    if let Err(e) = rs.write(ds, target, msg) {
+    // Reject writes that predate the record's deletion.
+    // Checked after authorization, so deletions are only revealed to writers.
+    let tombstone = rs.read_tombstone(target, &msg.record_id).map_err(|e| {
+        debug!("Failed to read tombstone {}: {:?}", msg.record_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(tombstone) = tombstone
+        && tombstone
+            .descriptor
+            .message_timestamp()
+            .is_some_and(|t| desc.message_timestamp <= *t)
+    {
+        debug!("Record {} has been deleted", msg.record_id);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Data written by CID alone must be the record's current data,
+    // or a complete upload that no record references yet.
+    if msg.data.is_none()
+        && let Some(data_cid) = &desc.data_cid
+    {
+        let prev_cid = match latest_entry.as_ref().map(|r| &r.latest_entry.descriptor) {
+            Some(Descriptor::RecordsWrite(d)) => d.data_cid.as_ref(),
+            _ => None,
+        };
+
+        if prev_cid != Some(data_cid) {
+            let staged = ds.is_staged(target, data_cid).map_err(|e| {
+                debug!("Failed to read staged block {data_cid}: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+            if !staged
+                || !has_blocks(ds, target, data_cid).map_err(|e| {
+                    debug!("Failed to read data blocks {data_cid}: {:?}", e);
+                    StatusCode::BAD_REQUEST
+                })?
+            {
+                debug!("Data not found in uploads: {data_cid}");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
+    }
+
+    if let Err(e) = rs.write(ds, target, msg.clone()) {
         warn!("Error during write: {e:?}");
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     };
 
+    if let Some(max_history) = max_history
+        && let Err(e) = rs.prune_history(ds, target, &msg.record_id, max_history)
+    {
+        warn!("Error pruning history of {}: {e:?}", msg.record_id);
+    }
+
+    let record = Record {
+        initial_entry: match latest_entry {
+            Some(prev) => prev.initial_entry,
+            None => msg.clone(),
+        },
+        latest_entry: msg.clone(),
+    };
+    subscriptions.notify(rs, target, &record, &msg);
+
     Ok(())
 }
@@ -5,7 +5,9 @@ use xdid::core::{did::Did, document::VerificationRole};
 
 use super::{ValidationError, jws::validate_jws};
 
-pub async fn validate_authorization(msg: &Message) -> Result<Vec<Did>, ValidationError> {
+pub async fn validate_authorization(
+    msg: &Message,
+) -> Result<(Vec<Did>, AuthPayload), ValidationError> {
     // Verify payload.
     let authorization = msg
         .authorization
@@ -40,5 +42,5 @@ pub async fn validate_authorization(msg: &Message) -> Result<Vec<Did>, Validatio
     // Validate JWS.
     let vc_dids = validate_jws(authorization, VerificationRole::Authentication).await?;
 
-    Ok(vc_dids)
+    Ok((vc_dids, payload))
 }
@@ -0,0 +1,159 @@
+use dwn_core::{
+    message::{
+        Message, OffsetDateTime,
+        descriptor::{Descriptor, Interface, PermissionScope, RecordFilter},
+    },
+    store::{RecordStore, StoreError},
+};
+use tracing::debug;
+use xdid::core::did::Did;
+
+use super::ValidationResult;
+
+/// Applies the permission grant referenced by the message's authorization.
+///
+/// If the grant is valid for the message, its scope is recorded in
+/// [`ValidationResult::grant_scope`] for handlers to check.
+pub fn apply_permission_grant(
+    rs: &dyn RecordStore,
+    target: &Did,
+    msg: &Message,
+    validation: &mut ValidationResult,
+) -> Result<(), StoreError> {
+    let Some(grant_id) = &validation.permissions_grant_id else {
+        return Ok(());
+    };
+
+    if validation.authenticated.contains(target) {
+        return Ok(());
+    }
+
+    validation.grant_scope = applied_grant(rs, target, msg, grant_id, &validation.authenticated)?;
+
+    Ok(())
+}
+
+/// Returns the scope of the grant, if it is valid for the message.
+fn applied_grant(
+    rs: &dyn RecordStore,
+    target: &Did,
+    msg: &Message,
+    grant_id: &str,
+    authenticated: &[Did],
+) -> Result<Option<PermissionScope>, StoreError> {
+    if msg.descriptor.interface() == Interface::Permissions {
+        debug!("Permission grants cannot authorize Permissions messages");
+        return Ok(None);
+    }
+
+    let Some(grant) = rs.read_permission(target, grant_id)? else {
+        debug!("Grant {grant_id} not found");
+        return Ok(None);
+    };
+
+    let Descriptor::PermissionsGrant(grant) = grant.descriptor else {
+        debug!("Permission {grant_id} is not a grant");
+        return Ok(None);
+    };
+
+    if !authenticated.contains(&grant.grantee) {
+        debug!("Message not authorized by grantee {}", grant.grantee);
+        return Ok(None);
+    }
+
+    if rs.read_permission_revocation(target, grant_id)?.is_some() {
+        debug!("Grant {grant_id} has been revoked");
+        return Ok(None);
+    }
+
+    if grant.date_expires <= OffsetDateTime::now_utc() {
+        debug!("Grant {grant_id} has expired");
+        return Ok(None);
+    }
+
+    if let Some(timestamp) = msg.descriptor.message_timestamp()
+        && *timestamp < grant.message_timestamp
+    {
+        debug!("Message created before grant {grant_id}");
+        return Ok(None);
+    }
+
+    if msg.descriptor.interface() != grant.scope.interface
+        || msg.descriptor.method() != grant.scope.method
+    {
+        debug!(
+            "Message outside grant scope: {} {}",
+            msg.descriptor.interface(),
+            msg.descriptor.method()
+        );
+        return Ok(None);
+    }
+
+    if grant.scope.protocol.is_none() && grant.scope.schema.is_none() {
+        return Ok(Some(grant.scope));
+    }
+
+    let (protocol, schema) = message_protocol_schema(rs, target, msg)?;
+
+    if grant.scope.protocol.is_some() && grant.scope.protocol != protocol {
+        debug!("Message protocol outside grant scope: {protocol:?}");
+        return Ok(None);
+    }
+
+    if grant.scope.schema.is_some() && grant.scope.schema != schema {
+        debug!("Message schema outside grant scope: {schema:?}");
+        return Ok(None);
+    }
+
+    Ok(Some(grant.scope))
+}
+
+/// Returns the protocol and schema a message operates on.
+fn message_protocol_schema(
+    rs: &dyn RecordStore,
+    target: &Did,
+    msg: &Message,
+) -> Result<(Option<String>, Option<String>), StoreError> {
+    let record_id = match &msg.descriptor {
+        Descriptor::MessagesQuery(d) => {
+            return Ok((d.filter.as_ref().and_then(|f| f.protocol.clone()), None));
+        }
+        Descriptor::ProtocolsConfigure(d) => {
+            return Ok((Some(d.definition.protocol.clone()), None));
+        }
+        Descriptor::ProtocolsQuery(d) => return Ok((d.filter.protocol.clone(), None)),
+        Descriptor::RecordsQuery(d) => {
+            return Ok(d
+                .filter
+                .as_ref()
+                .map(|f| (f.protocol.clone(), f.schema.clone()))
+                .unwrap_or_default());
+        }
+        Descriptor::RecordsSubscribe(d) => {
+            return Ok(d
+                .filter
+                .as_ref()
+                .map(|f| (f.protocol.clone(), f.schema.clone()))
+                .unwrap_or_default());
+        }
+        Descriptor::RecordsWrite(d) => return Ok((d.protocol.clone(), d.schema.clone())),
+        Descriptor::RecordsDelete(d) => d.record_id.clone(),
+        Descriptor::RecordsRead(d) => d.record_id.clone(),
+        _ => return Ok((None, None)),
+    };
+
+    let found = rs.query(
+        target,
+        &RecordFilter {
+            record_id: Some(record_id),
+            ..Default::default()
+        },
+        None,
+        true,
+    )?;
+
+    match found.into_iter().next().map(|r| r.latest_entry.descriptor) {
+        Some(Descriptor::RecordsWrite(d)) => Ok((d.protocol, d.schema)),
+        _ => Ok((None, None)),
+    }
+}
@@ -1,17 +1,41 @@
-use dwn_core::message::{Message, cid::CidGenerationError, descriptor::Descriptor};
+use dwn_core::message::{
+    Message,
+    cid::CidGenerationError,
+    descriptor::{Descriptor, PermissionScope},
+};
 use thiserror::Error;
 use xdid::core::{ResolutionError, did::Did};
 
 mod attestation;
 mod authorization;
+mod grant;
 mod jws;
 
+pub use grant::apply_permission_grant;
+
 #[derive(Debug)]
 pub struct ValidationResult {
     /// DIDs with valid attestation signatures.
     pub _attested: Vec<Did>,
     /// DIDs with valid authentication signatures.
     pub authenticated: Vec<Did>,
+    /// Permission grant referenced by the authorization, if any.
+    pub permissions_grant_id: Option<String>,
+    /// Scope of the permission grant, if it was applied to the message.
+    pub grant_scope: Option<PermissionScope>,
+}
+
+impl ValidationResult {
+    /// Whether the message has the target's access, either by being
+    /// authenticated by the target, or through a permission grant whose
+    /// scope covers the message.
+    pub fn is_authorized(&self, target: &Did, msg: &Message) -> bool {
+        self.authenticated.contains(target)
+            || self.grant_scope.as_ref().is_some_and(|scope| {
+                scope.interface == msg.descriptor.interface()
+                    && scope.method == msg.descriptor.method()
+            })
+    }
 }
 
 pub async fn validate_message(msg: &Message) -> Result<ValidationResult, ValidationError> {
@@ -25,6 +49,10 @@ pub async fn validate_message(msg: &Message) -> Result<ValidationResult, Validat
         if desc.data_format.is_none() {
             return Err(ValidationError::MissingDataInfo);
         }
+
+        if !msg.data_matches_cid() {
+            return Err(ValidationError::DataCidMismatch);
+        }
     }
 
     let attested = if msg.attestation.is_some() {
@@ -33,15 +61,18 @@ pub async fn validate_message(msg: &Message) -> Result<ValidationResult, Validat
         Vec::new()
     };
 
-    let authenticated = if msg.authorization.is_some() {
-        authorization::validate_authorization(msg).await?
+    let (authenticated, permissions_grant_id) = if msg.authorization.is_some() {
+        let (dids, payload) = authorization::validate_authorization(msg).await?;
+        (dids, payload.permissions_grant_cid)
     } else {
-        Vec::new()
+        (Vec::new(), None)
     };
 
     Ok(ValidationResult {
         _attested: attested,
         authenticated,
+        permissions_grant_id,
+        grant_scope: None,
     })
 }
 
@@ -49,6 +80,8 @@ pub async fn validate_message(msg: &Message) -> Result<ValidationResult, Validat
 pub enum ValidationError {
     #[error("failed to generate CID: {0}")]
     CidGeneration(#[from] CidGenerationError),
+    #[error("data does not match data CID")]
+    DataCidMismatch,
     #[error("failed to decode base64: {0}")]
     Decode(#[from] base64::DecodeError),
     #[error("failed to construct DID resolver: {0}")]
@@ -6,6 +6,8 @@
 //! # Example
 //!
 //! ```
+//! use std::sync::Arc;
+//!
 //! use dwn::{
 //!     core::{message::{descriptor::{RecordsReadBuilder, RecordsWriteBuilder}, mime::TEXT_PLAIN}, reply::Reply},
 //!     stores::NativeDbStore,
@@ -26,8 +28,8 @@
 //!    
 //!     // Create an actor to sign messages on behalf of our DID.
 //!     let mut actor = Actor::new(did, dwn);
-//!     actor.auth_key = Some(key.clone().into());
-//!     actor.sign_key = Some(key.into());
+//!     actor.auth_key = Some(Arc::new(key.clone().into()));
+//!     actor.sign_key = Some(Arc::new(key.into()));
 //!    
 //!     // Write a new record to the DWN.
 //!     let data = "Hello, world!".as_bytes().to_vec();
@@ -46,20 +48,25 @@
 //!         .unwrap()
 //!         .unwrap();
 //!
-//!    assert!(found.entry().record_id, record_id);
-//!    assert!(found.data().unwrap(), data);
+//!    assert_eq!(found.entry().record_id, record_id);
+//!    assert_eq!(found.data().unwrap(), data);
 //! }
 //! ```
 
-use std::sync::Arc;
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use dwn_core::{
-    message::{Message, descriptor::Descriptor},
+    message::{Message, OffsetDateTime, data::verify_block, descriptor::Descriptor},
     reply::Reply,
-    store::{DataStore, RecordStore},
+    store::{DataReader, DataStore, RecordStore},
 };
 use reqwest::StatusCode;
-use tracing::debug;
+use subscriptions::Subscriptions;
+use tracing::{debug, warn};
 use xdid::core::did::Did;
 
 pub use dwn_core as core;
@@ -71,15 +78,34 @@ pub mod stores {
 
 mod actor;
 mod handlers;
+mod subscriptions;
 
 pub use actor::*;
+pub use subscriptions::RecordSubscription;
 
 use crate::handlers::validation::ValidationResult;
 
+/// Default value of [`Dwn::max_context_depth`].
+pub const DEFAULT_MAX_CONTEXT_DEPTH: usize = 10;
+
+/// Default value of [`Dwn::block_ttl`].
+pub const DEFAULT_BLOCK_TTL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Clone)]
 pub struct Dwn {
     pub data_store: Arc<dyn DataStore>,
     pub record_store: Arc<dyn RecordStore>,
+    /// Maximum nesting depth of protocol records.
+    /// Writes with a longer protocol path are rejected.
+    pub max_context_depth: usize,
+    /// Maximum number of past entries kept per record, in addition to the latest.
+    /// If `None`, every entry is kept.
+    pub max_history: Option<usize>,
+    /// How long uploaded blocks are kept before a data root references them.
+    /// Expired blocks are pruned at most once per TTL, so may be kept for up to twice as long.
+    pub block_ttl: Duration,
+    subscriptions: Arc<Subscriptions>,
+    last_block_prune: Arc<Mutex<Option<OffsetDateTime>>>,
 }
 
 impl<T: DataStore + RecordStore + Clone + 'static> From<T> for Dwn {
@@ -91,9 +117,12 @@ impl<T: DataStore + RecordStore + Clone + 'static> From<T> for Dwn {
 struct ProcessContext<'a> {
     pub rs: &'a dyn RecordStore,
     pub ds: &'a dyn DataStore,
+    pub subscriptions: &'a Subscriptions,
     pub validation: ValidationResult,
     pub target: &'a Did,
     pub msg: Message,
+    pub max_context_depth: usize,
+    pub max_history: Option<usize>,
 }
 
 impl Dwn {
@@ -101,6 +130,11 @@ impl Dwn {
         Self {
             data_store,
             record_store,
+            max_context_depth: DEFAULT_MAX_CONTEXT_DEPTH,
+            max_history: None,
+            block_ttl: DEFAULT_BLOCK_TTL,
+            subscriptions: Arc::default(),
+            last_block_prune: Arc::default(),
         }
     }
 
@@ -109,23 +143,38 @@ impl Dwn {
         target: &Did,
         msg: Message,
     ) -> Result<Option<Reply>, StatusCode> {
-        let validation = match handlers::validation::validate_message(&msg).await {
-            Ok(a) => a,
-            Err(e) => {
-                debug!("Failed to validate message: {:?}", e);
-                return Err(StatusCode::BAD_REQUEST);
-            }
-        };
+        let validation = self.validate(target, &msg).await?;
 
         let ctx = ProcessContext {
             rs: self.record_store.as_ref(),
             ds: self.data_store.as_ref(),
+            subscriptions: self.subscriptions.as_ref(),
             validation,
             target,
             msg,
+            max_context_depth: self.max_context_depth,
+            max_history: self.max_history,
         };
 
         let res = match &ctx.msg.descriptor {
+            Descriptor::MessagesQuery(_) => handlers::messages::query::handle(ctx)
+                .await
+                .map(|v| Some(Reply::MessagesQuery(v)))?,
+            Descriptor::MessagesRead(_) => handlers::messages::read::handle(ctx)
+                .await
+                .map(|v| Some(Reply::MessagesRead(Box::new(v))))?,
+            Descriptor::PermissionsGrant(_) => {
+                handlers::permissions::grant::handle(ctx).await?;
+                None
+            }
+            Descriptor::PermissionsRequest(_) => {
+                handlers::permissions::request::handle(ctx).await?;
+                None
+            }
+            Descriptor::PermissionsRevoke(_) => {
+                handlers::permissions::revoke::handle(ctx).await?;
+                None
+            }
             Descriptor::ProtocolsConfigure(_) => {
                 handlers::protocols::configure::handle(ctx).await?;
                 None
@@ -143,6 +192,10 @@ impl Dwn {
             Descriptor::RecordsRead(_) => handlers::records::read::handle(ctx)
                 .await
                 .map(|v| Some(Reply::RecordsRead(Box::new(v))))?,
+            Descriptor::RecordsSubscribe(_) => {
+                debug!("RecordsSubscribe must be processed using Dwn::subscribe");
+                return Err(StatusCode::BAD_REQUEST);
+            }
             Descriptor::RecordsSync(_) => handlers::records::sync::handle(ctx)
                 .await
                 .map(|v| Some(Reply::RecordsSync(Box::new(v))))?,
@@ -154,4 +207,121 @@ impl Dwn {
 
         Ok(res)
     }
+
+    /// Processes a `RecordsSubscribe` message.
+    /// Returns a subscription that receives every subsequent `RecordsWrite` and
+    /// `RecordsDelete` matching the message's filter.
+    pub async fn subscribe(
+        &self,
+        target: &Did,
+        msg: Message,
+    ) -> Result<RecordSubscription, StatusCode> {
+        let validation = self.validate(target, &msg).await?;
+
+        let ctx = ProcessContext {
+            rs: self.record_store.as_ref(),
+            ds: self.data_store.as_ref(),
+            subscriptions: self.subscriptions.as_ref(),
+            validation,
+            target,
+            msg,
+            max_context_depth: self.max_context_depth,
+            max_history: self.max_history,
+        };
+
+        handlers::records::subscribe::handle(ctx).await
+    }
+
+    /// Stores a unixfs block of record data.
+    /// Blocks are uploaded before the `RecordsWrite` referencing their root `data_cid`,
+    /// and are removed if no record references them within [`Dwn::block_ttl`].
+    pub fn write_block(&self, target: &Did, cid: &str, block: Vec<u8>) -> Result<(), StatusCode> {
+        if !verify_block(cid, &block) {
+            debug!("Block does not match CID: {cid}");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        self.prune_blocks();
+
+        self.data_store.put_block(target, cid, block).map_err(|e| {
+            warn!("Failed to store block {cid}: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+    }
+
+    /// Processes a `RecordsRead` message, streaming the record's data from its stored blocks.
+    /// If a byte range is given, only that slice of the data is read.
+    /// Returns `None` if the record was not found, or its data is not stored as blocks.
+    pub async fn read_data(
+        &self,
+        target: &Did,
+        msg: Message,
+        range: Option<Range<u64>>,
+    ) -> Result<Option<DataReader>, StatusCode> {
+        if !matches!(msg.descriptor, Descriptor::RecordsRead(_)) {
+            debug!("Data must be read using RecordsRead");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let Some(Reply::RecordsRead(reply)) = self.process_message(target, msg).await? else {
+            return Ok(None);
+        };
+
+        let Some(Descriptor::RecordsWrite(desc)) = reply.entry.map(|e| e.descriptor) else {
+            return Ok(None);
+        };
+
+        let Some(cid) = desc.data_cid else {
+            return Ok(None);
+        };
+
+        DataReader::new(self.data_store.clone(), target.clone(), cid, range).map_err(|e| {
+            warn!("Failed to read data: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+    }
+
+    /// Removes expired blocks, if not already done within the last [`Dwn::block_ttl`].
+    fn prune_blocks(&self) {
+        let now = OffsetDateTime::now_utc();
+
+        {
+            let Ok(mut last) = self.last_block_prune.lock() else {
+                return;
+            };
+
+            if last.is_some_and(|t| now - t < self.block_ttl) {
+                return;
+            }
+
+            *last = Some(now);
+        }
+
+        if let Err(e) = self.data_store.prune_blocks(now - self.block_ttl) {
+            warn!("Failed to prune blocks: {:?}", e);
+        }
+    }
+
+    async fn validate(&self, target: &Did, msg: &Message) -> Result<ValidationResult, StatusCode> {
+        let mut validation = match handlers::validation::validate_message(msg).await {
+            Ok(a) => a,
+            Err(e) => {
+                debug!("Failed to validate message: {:?}", e);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        };
+
+        handlers::validation::apply_permission_grant(
+            self.record_store.as_ref(),
+            target,
+            msg,
+            &mut validation,
+        )
+        .map_err(|e| {
+            warn!("Failed to apply permission grant: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        Ok(validation)
+    }
 }
@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+
+use dwn_core::{
+    message::{Message, descriptor::RecordFilter},
+    store::{Record, RecordStore},
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use xdid::core::did::Did;
+
+use crate::handlers::{records::query::can_query, validation::ValidationResult};
+
+/// Active `RecordsSubscribe` subscriptions.
+#[derive(Default)]
+pub(crate) struct Subscriptions(Mutex<Vec<Subscriber>>);
+
+struct Subscriber {
+    target: Did,
+    filter: RecordFilter,
+    authorized: bool,
+    /// Validation of the `RecordsSubscribe`, for protocol visibility rules.
+    validation: ValidationResult,
+    sender: UnboundedSender<Message>,
+}
+
+impl Subscriptions {
+    pub fn add(
+        &self,
+        target: Did,
+        filter: RecordFilter,
+        authorized: bool,
+        validation: ValidationResult,
+    ) -> RecordSubscription {
+        let (sender, receiver) = unbounded_channel();
+
+        self.0.lock().unwrap().push(Subscriber {
+            target,
+            filter,
+            authorized,
+            validation,
+            sender,
+        });
+
+        RecordSubscription { receiver }
+    }
+
+    /// Sends a message to every subscriber whose filter matches the given record,
+    /// and who may query it.
+    pub fn notify(&self, rs: &dyn RecordStore, target: &Did, record: &Record, msg: &Message) {
+        for sender in self.matching(rs, target, std::slice::from_ref(record)) {
+            let _ = sender.send(msg.clone());
+        }
+    }
+
+    /// Returns every subscriber whose filter matches any of the given records,
+    /// and who may query it.
+    /// Visibility is checked with the same rules as `RecordsQuery`, using each
+    /// subscriber's own authorization.
+    pub fn matching(
+        &self,
+        rs: &dyn RecordStore,
+        target: &Did,
+        records: &[Record],
+    ) -> Vec<UnboundedSender<Message>> {
+        let mut subscribers = self.0.lock().unwrap();
+
+        // Drop subscribers that are no longer listening.
+        subscribers.retain(|s| !s.sender.is_closed());
+
+        subscribers
+            .iter()
+            .filter(|s| s.target == *target)
+            .filter(|s| {
+                records.iter().any(|record| {
+                    s.filter.matches_record(record)
+                        && (s.authorized
+                            || can_query(rs, target, &s.validation, record).unwrap_or(false))
+                })
+            })
+            .map(|s| s.sender.clone())
+            .collect()
+    }
+}
+
+/// A stream of `RecordsWrite` and `RecordsDelete` messages matching a subscription filter.
+pub struct RecordSubscription {
+    receiver: UnboundedReceiver<Message>,
+}
+
+impl RecordSubscription {
+    pub(crate) fn new(receiver: UnboundedReceiver<Message>) -> Self {
+        Self { receiver }
+    }
+
+    /// Waits for the next message.
+    /// Returns `None` once the subscription has been closed.
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.receiver.recv().await
+    }
+}
@@ -0,0 +1,234 @@
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use dwn_core::message::{
+    Version,
+    data::Data,
+    descriptor::{Descriptor, Interface, Method, ProtocolDefinition},
+    mime::TEXT_PLAIN,
+};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_messages() {
+    let (actor, ..) = init_dwn();
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    actor.delete(record_id.clone()).process().await.unwrap();
+
+    let (found, _) = actor.query_messages().process().await.unwrap();
+    assert_eq!(found.len(), 2);
+
+    assert_eq!(found[0].message.record_id, record_id);
+    assert!(matches!(
+        found[0].message.descriptor,
+        Descriptor::RecordsWrite(_)
+    ));
+    assert!(found[0].message.data.is_none());
+
+    let Descriptor::RecordsDelete(desc) = &found[1].message.descriptor else {
+        panic!("expected RecordsDelete");
+    };
+    assert_eq!(desc.record_id, record_id);
+    assert!(found[0].cursor < found[1].cursor);
+
+    // Resume after the first entry.
+    let (found, _) = actor
+        .query_messages()
+        .cursor(found[0].cursor)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(matches!(
+        found[0].message.descriptor,
+        Descriptor::RecordsDelete(_)
+    ));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_messages_limit() {
+    let (actor, ..) = init_dwn();
+
+    for i in 0..3 {
+        actor
+            .write()
+            .data(TEXT_PLAIN, format!("Hello, {i}!").into_bytes())
+            .process()
+            .await
+            .unwrap();
+    }
+
+    let (found, cursor) = actor.query_messages().limit(2).process().await.unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(cursor, Some(found[1].cursor));
+
+    let (rest, cursor) = actor
+        .query_messages()
+        .limit(2)
+        .cursor(cursor.unwrap())
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(rest.len(), 1);
+    assert!(rest[0].cursor > found[1].cursor);
+    assert!(cursor.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_messages_filter() {
+    let (actor, ..) = init_dwn();
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    actor
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    actor
+        .write()
+        .protocol(definition.protocol.clone(), version, "my-value".to_string())
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let other_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Goodbye, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let (found, _) = actor
+        .query_messages()
+        .interface(Interface::Protocols)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(matches!(
+        found[0].message.descriptor,
+        Descriptor::ProtocolsConfigure(_)
+    ));
+
+    let (found, _) = actor
+        .query_messages()
+        .interface(Interface::Records)
+        .method(Method::Write)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 2);
+
+    let (found, _) = actor
+        .query_messages()
+        .protocol(definition.protocol.clone())
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().all(|e| e.message.record_id != other_id));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_read_message() {
+    let (actor, ..) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    actor
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let (found, _) = actor.query_messages().process().await.unwrap();
+    assert_eq!(found.len(), 1);
+
+    let read = actor
+        .read_message(found[0].message_cid.clone())
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read.cursor, found[0].cursor);
+    assert_eq!(read.message.record_id, found[0].message.record_id);
+    assert_eq!(
+        read.message.data,
+        Some(Data::Base64(BASE64_URL_SAFE_NO_PAD.encode(data)))
+    );
+
+    assert!(
+        actor
+            .read_message("not-a-cid".to_string())
+            .process()
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_messages_require_auth() {
+    let (alice, bob, _) = init_dwn();
+
+    alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(alice.query_messages().auth(false).process().await.is_err());
+    assert!(
+        bob.query_messages()
+            .target(&alice.did)
+            .process()
+            .await
+            .is_err()
+    );
+
+    let (found, _) = alice.query_messages().process().await.unwrap();
+
+    assert!(
+        bob.read_message(found[0].message_cid.clone())
+            .target(&alice.did)
+            .process()
+            .await
+            .is_err()
+    );
+}
@@ -1,4 +1,6 @@
 mod actor;
+mod messages;
+mod permissions;
 mod protocols;
 mod records;
 pub mod utils;
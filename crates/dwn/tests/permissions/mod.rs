@@ -0,0 +1,316 @@
+use std::time::Duration;
+
+use dwn_core::message::{
+    OffsetDateTime,
+    descriptor::{Descriptor, Interface, Method, PermissionScope, PermissionsRequestBuilder},
+    mime::TEXT_PLAIN,
+};
+use reqwest::StatusCode;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn expires() -> OffsetDateTime {
+    OffsetDateTime::now_utc() + Duration::from_secs(60 * 60)
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_grant_write() {
+    let (alice, bob, dwn) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    assert!(
+        bob.write()
+            .data(TEXT_PLAIN, data.clone())
+            .target(&alice.did)
+            .process()
+            .await
+            .is_err()
+    );
+
+    let grant_id = alice
+        .grant_permission(
+            bob.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Write),
+            expires(),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = bob
+        .write()
+        .data(TEXT_PLAIN, data)
+        .target(&alice.did)
+        .permission_grant(grant_id)
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_grant_scope() {
+    let (alice, bob, _) = init_dwn();
+
+    let grant_id = alice
+        .grant_permission(
+            bob.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Query),
+            expires(),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .query()
+        .target(&alice.did)
+        .permission_grant(grant_id.clone())
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+
+    let found = bob.query().target(&alice.did).process().await.unwrap();
+    assert!(found.is_empty());
+
+    // Grant does not cover writes.
+    assert!(
+        bob.write()
+            .data(TEXT_PLAIN, "Goodbye, world!".as_bytes().to_vec())
+            .target(&alice.did)
+            .permission_grant(grant_id)
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_grant_protocol_scope() {
+    let (alice, bob, _) = init_dwn();
+
+    let mut scope = PermissionScope::new(Interface::Records, Method::Query);
+    scope.protocol = Some("my-protocol".to_string());
+
+    let grant_id = alice
+        .grant_permission(bob.did.clone(), scope, expires())
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .query()
+        .target(&alice.did)
+        .permission_grant(grant_id)
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_empty());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_revoke() {
+    let (alice, bob, _) = init_dwn();
+
+    let grant_id = alice
+        .grant_permission(
+            bob.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Write),
+            expires(),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .revoke_permission(grant_id.clone())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        bob.write()
+            .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+            .target(&alice.did)
+            .permission_grant(grant_id)
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_grant_expired() {
+    let (alice, bob, _) = init_dwn();
+
+    let grant_id = alice
+        .grant_permission(
+            bob.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Write),
+            OffsetDateTime::now_utc() + Duration::from_millis(500),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    assert!(
+        bob.write()
+            .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+            .target(&alice.did)
+            .permission_grant(grant_id)
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_grant_wrong_grantee() {
+    let (alice, bob, _) = init_dwn();
+
+    // Grant issued to alice cannot be used by bob.
+    let grant_id = alice
+        .grant_permission(
+            alice.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Write),
+            expires(),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        bob.write()
+            .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+            .target(&alice.did)
+            .permission_grant(grant_id)
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_request() {
+    let (alice, bob, _) = init_dwn();
+
+    let request_id = bob
+        .request_permission(PermissionScope::new(Interface::Records, Method::Write))
+        .description("Let me write".to_string())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+
+    let (found, _) = alice
+        .query_messages()
+        .interface(Interface::Permissions)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message.record_id, request_id);
+
+    let Descriptor::PermissionsRequest(desc) = &found[0].message.descriptor else {
+        panic!("expected PermissionsRequest");
+    };
+    assert_eq!(desc.grantee, bob.did);
+
+    alice
+        .grant_permission(desc.grantee.clone(), desc.scope.clone(), expires())
+        .request_id(request_id)
+        .process()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_request_record_id_mismatch() {
+    let (alice, bob, dwn) = init_dwn();
+
+    let grant_id = alice
+        .grant_permission(
+            bob.did.clone(),
+            PermissionScope::new(Interface::Records, Method::Write),
+            expires(),
+        )
+        .process()
+        .await
+        .unwrap();
+
+    // A request cannot take the record id of an existing grant.
+    let mut msg = PermissionsRequestBuilder::new(
+        bob.did.clone(),
+        PermissionScope::new(Interface::Records, Method::Delete),
+    )
+    .build()
+    .unwrap();
+    msg.record_id = grant_id.clone();
+    bob.authorize(&mut msg).unwrap();
+
+    assert_eq!(
+        dwn.process_message(&alice.did, msg).await,
+        Err(StatusCode::BAD_REQUEST)
+    );
+
+    let found = dwn
+        .record_store
+        .read_permission(&alice.did, &grant_id)
+        .unwrap()
+        .unwrap();
+    assert!(matches!(found.descriptor, Descriptor::PermissionsGrant(_)));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_request_duplicate() {
+    let (alice, bob, dwn) = init_dwn();
+
+    let mut msg = PermissionsRequestBuilder::new(
+        bob.did.clone(),
+        PermissionScope::new(Interface::Records, Method::Write),
+    )
+    .build()
+    .unwrap();
+    bob.authorize(&mut msg).unwrap();
+
+    dwn.process_message(&alice.did, msg.clone()).await.unwrap();
+
+    assert_eq!(
+        dwn.process_message(&alice.did, msg).await,
+        Err(StatusCode::CONFLICT)
+    );
+}
@@ -0,0 +1,209 @@
+use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn nested_definition() -> ProtocolDefinition {
+    let anyone_create = json!([{
+        "who": "anyone",
+        "can": ["create"],
+    }]);
+
+    let raw_definition = json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "thread": {
+                "dataFormat": ["text/plain"],
+            },
+            "reply": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "thread": {
+                "$actions": anyone_create,
+                "reply": {
+                    "$actions": anyone_create,
+                    "reply": {
+                        "$actions": anyone_create,
+                    }
+                }
+            }
+        }
+    });
+    serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_context() {
+    let (alice, ..) = init_dwn();
+
+    let definition = nested_definition();
+    let version = Version::new(1, 2, 3);
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let thread_id = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread".to_string(),
+        )
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let reply_id = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply".to_string(),
+        )
+        .context_id(thread_id.clone())
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply/reply".to_string(),
+        )
+        .context_id(format!("{thread_id}/{reply_id}"))
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    // Missing context.
+    let res = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply".to_string(),
+        )
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await;
+    assert!(res.is_err());
+
+    // Unknown parent.
+    let res = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply".to_string(),
+        )
+        .context_id("unknown".to_string())
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await;
+    assert!(res.is_err());
+
+    // Parent at the wrong protocol path.
+    let res = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply/reply".to_string(),
+        )
+        .context_id(format!("{reply_id}/{thread_id}"))
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await;
+    assert!(res.is_err());
+
+    // Parent in an incompatible protocol version.
+    let other_version = Version::new(2, 0, 0);
+
+    alice
+        .configure_protocol(other_version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let res = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            other_version,
+            "thread/reply".to_string(),
+        )
+        .context_id(thread_id.clone())
+        .data(TEXT_PLAIN, data)
+        .process()
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_max_depth() {
+    let (mut alice, ..) = init_dwn();
+    alice.dwn.max_context_depth = 2;
+
+    let definition = nested_definition();
+    let version = Version::new(1, 2, 3);
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let thread_id = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread".to_string(),
+        )
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let reply_id = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply".to_string(),
+        )
+        .context_id(thread_id.clone())
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let res = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread/reply/reply".to_string(),
+        )
+        .context_id(format!("{thread_id}/{reply_id}"))
+        .data(TEXT_PLAIN, data)
+        .process()
+        .await;
+    assert!(res.is_err());
+}
@@ -1,4 +1,8 @@
-use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use dwn_core::message::{
+    Version,
+    descriptor::{Descriptor, ProtocolDefinition},
+    mime::TEXT_PLAIN,
+};
 use serde_json::json;
 use tracing_test::traced_test;
 
@@ -160,3 +164,59 @@ async fn test_protocol_author_update() {
         .await;
     assert!(res.is_err())
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_create_foreign_data_cid() {
+    let (alice, bob, _) = init_dwn();
+
+    let raw_definition = json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create", "read"],
+                }]
+            }
+        }
+    });
+    let definition = serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    // Alice's private record.
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "secret".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = alice.read(record_id).process().await.unwrap().unwrap();
+    let Descriptor::RecordsWrite(desc) = &found.entry().descriptor else {
+        panic!("invalid descriptor");
+    };
+    let data_cid = desc.data_cid.clone().unwrap();
+
+    // Bob cannot reference its data from his own record.
+    let res = bob
+        .write()
+        .protocol(definition.protocol, version, "my-value".to_string())
+        .data_cid(TEXT_PLAIN, data_cid)
+        .target(&alice.did)
+        .process()
+        .await;
+    assert!(res.is_err());
+}
@@ -0,0 +1,184 @@
+use dwn::{Actor, Dwn};
+use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn delete_definition() -> ProtocolDefinition {
+    let raw_definition = json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "thread": {
+                "dataFormat": ["text/plain"],
+            },
+            "reply": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "thread": {
+                "$actions": [
+                    {
+                        "who": "anyone",
+                        "can": ["create"],
+                    },
+                    {
+                        "who": "author",
+                        "can": ["delete"],
+                    },
+                ],
+                "reply": {
+                    "$actions": [
+                        {
+                            "who": "anyone",
+                            "can": ["create"],
+                        },
+                        {
+                            "who": "author",
+                            "can": ["delete"],
+                        },
+                    ],
+                    "reply": {
+                        "$actions": [{
+                            "who": "anyone",
+                            "can": ["create"],
+                        }]
+                    }
+                }
+            }
+        }
+    });
+    serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap()
+}
+
+async fn write(actor: &Actor, target: &Actor, path: &str, context_id: Option<String>) -> String {
+    let definition = delete_definition();
+
+    let mut builder = actor
+        .write()
+        .protocol(definition.protocol, Version::new(1, 0, 0), path.to_string())
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .target(&target.did);
+
+    if let Some(context_id) = context_id {
+        builder = builder.context_id(context_id);
+    }
+
+    builder.process().await.unwrap()
+}
+
+fn exists(dwn: &Dwn, target: &Actor, record_id: &str) -> bool {
+    dwn.record_store
+        .read(dwn.data_store.as_ref(), &target.did, record_id)
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_author_delete() {
+    let (alice, bob, dwn) = init_dwn();
+
+    alice
+        .configure_protocol(Version::new(1, 0, 0), delete_definition())
+        .process()
+        .await
+        .unwrap();
+
+    let thread = write(&alice, &alice, "thread", None).await;
+    let reply = write(&bob, &alice, "thread/reply", Some(thread.clone())).await;
+
+    assert!(
+        bob.delete(thread.clone())
+            .target(&alice.did)
+            .process()
+            .await
+            .is_err()
+    );
+    assert!(exists(&dwn, &alice, &thread));
+
+    bob.delete(reply.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(!exists(&dwn, &alice, &reply));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_delete_prune() {
+    let (alice, _, dwn) = init_dwn();
+
+    alice
+        .configure_protocol(Version::new(1, 0, 0), delete_definition())
+        .process()
+        .await
+        .unwrap();
+
+    let thread_1 = write(&alice, &alice, "thread", None).await;
+    let reply_1 = write(&alice, &alice, "thread/reply", Some(thread_1.clone())).await;
+    let nested_1 = write(
+        &alice,
+        &alice,
+        "thread/reply/reply",
+        Some(format!("{thread_1}/{reply_1}")),
+    )
+    .await;
+
+    let thread_2 = write(&alice, &alice, "thread", None).await;
+    let reply_2 = write(&alice, &alice, "thread/reply", Some(thread_2.clone())).await;
+
+    // Without pruning, descendants are kept.
+    alice.delete(reply_2.clone()).process().await.unwrap();
+    assert!(!exists(&dwn, &alice, &reply_2));
+    assert!(exists(&dwn, &alice, &thread_2));
+
+    alice
+        .delete(thread_1.clone())
+        .prune(true)
+        .process()
+        .await
+        .unwrap();
+    assert!(!exists(&dwn, &alice, &thread_1));
+    assert!(!exists(&dwn, &alice, &reply_1));
+    assert!(!exists(&dwn, &alice, &nested_1));
+    assert!(exists(&dwn, &alice, &thread_2));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_prune_requires_delete() {
+    let (alice, bob, dwn) = init_dwn();
+
+    alice
+        .configure_protocol(Version::new(1, 0, 0), delete_definition())
+        .process()
+        .await
+        .unwrap();
+
+    let thread = write(&bob, &alice, "thread", None).await;
+    let reply = write(&alice, &alice, "thread/reply", Some(thread.clone())).await;
+
+    // Bob cannot delete Alice's reply.
+    assert!(
+        bob.delete(thread.clone())
+            .prune(true)
+            .target(&alice.did)
+            .process()
+            .await
+            .is_err()
+    );
+    assert!(exists(&dwn, &alice, &thread));
+    assert!(exists(&dwn, &alice, &reply));
+
+    bob.delete(thread.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(!exists(&dwn, &alice, &thread));
+    assert!(exists(&dwn, &alice, &reply));
+}
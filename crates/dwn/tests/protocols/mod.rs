@@ -4,7 +4,15 @@ use tracing_test::traced_test;
 
 use crate::utils::init_dwn;
 
+mod context;
 mod create;
+mod delete;
+mod query;
+mod read;
+mod recipient;
+mod types;
+mod validate;
+mod versions;
 
 #[tokio::test]
 #[traced_test]
@@ -0,0 +1,93 @@
+use dwn_core::message::{Version, descriptor::ProtocolDefinition};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn definition(protocol: &str, published: bool) -> ProtocolDefinition {
+    let raw_definition = json!({
+        "protocol": protocol,
+        "published": published,
+        "types": {},
+        "structure": {}
+    });
+    serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_query_published() {
+    let (alice, bob, _) = init_dwn();
+
+    alice
+        .configure_protocol(Version::new(1, 0, 0), definition("public", true))
+        .process()
+        .await
+        .unwrap();
+    alice
+        .configure_protocol(Version::new(1, 0, 0), definition("private", false))
+        .process()
+        .await
+        .unwrap();
+
+    let found = alice.query_protocols().process().await.unwrap();
+    assert_eq!(found.len(), 2);
+
+    let found = alice.query_protocols().auth(false).process().await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].definition.protocol, "public");
+
+    let found = bob
+        .query_protocols()
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].definition.protocol, "public");
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_query_filter() {
+    let (alice, ..) = init_dwn();
+
+    alice
+        .configure_protocol(Version::new(1, 2, 3), definition("my-protocol", true))
+        .process()
+        .await
+        .unwrap();
+    alice
+        .configure_protocol(Version::new(1, 0, 0), definition("my-protocol-2", true))
+        .process()
+        .await
+        .unwrap();
+
+    let found = alice
+        .query_protocols()
+        .protocol("my-protocol".to_string())
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].definition.protocol, "my-protocol");
+    assert_eq!(found[0].protocol_version, Version::new(1, 2, 3));
+
+    let found = alice
+        .query_protocols()
+        .version("^1.2".parse().unwrap())
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].definition.protocol, "my-protocol");
+
+    let found = alice
+        .query_protocols()
+        .protocol("my-protocol".to_string())
+        .version(">=2".parse().unwrap())
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_empty());
+}
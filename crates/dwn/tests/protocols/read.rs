@@ -0,0 +1,209 @@
+use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn read_definition() -> ProtocolDefinition {
+    let raw_definition = json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "notice": {
+                "dataFormat": ["text/plain"],
+            },
+            "thread": {
+                "dataFormat": ["text/plain"],
+            },
+            "reply": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "notice": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create", "read", "query"],
+                }]
+            },
+            "thread": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }],
+                "reply": {
+                    "$actions": [
+                        {
+                            "who": "anyone",
+                            "can": ["create"],
+                        },
+                        {
+                            "who": "author",
+                            "of": "thread",
+                            "can": ["read", "query"],
+                        },
+                    ]
+                }
+            }
+        }
+    });
+    serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_read_anyone() {
+    let (alice, bob, _) = init_dwn();
+
+    let definition = read_definition();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "notice".to_string(),
+        )
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .expect("record not found");
+    assert_eq!(found.entry().record_id, record_id);
+
+    let found = bob
+        .query()
+        .protocol(definition.protocol)
+        .protocol_path("notice".to_string())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, record_id);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_read_author_of() {
+    let (alice, bob, _) = init_dwn();
+
+    let definition = read_definition();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let bob_thread = bob
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread".to_string(),
+        )
+        .data(TEXT_PLAIN, data.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+
+    let alice_thread = alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "thread".to_string(),
+        )
+        .data(TEXT_PLAIN, data.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let mut replies = Vec::new();
+
+    for thread in [&bob_thread, &alice_thread] {
+        let reply = alice
+            .write()
+            .protocol(
+                definition.protocol.clone(),
+                version.clone(),
+                "thread/reply".to_string(),
+            )
+            .context_id(thread.clone())
+            .data(TEXT_PLAIN, data.clone())
+            .process()
+            .await
+            .unwrap();
+        replies.push(reply);
+    }
+
+    // Bob can see replies to his thread.
+    let found = bob
+        .read(replies[0].clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_some());
+
+    let found = bob
+        .read(replies[1].clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let found = bob
+        .query()
+        .protocol(definition.protocol.clone())
+        .protocol_path("thread/reply".to_string())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, replies[0]);
+
+    // Paging still fills up past hidden entries.
+    let found = bob
+        .query()
+        .protocol(definition.protocol.clone())
+        .limit(1)
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, replies[0]);
+
+    // Threads have no read rules.
+    let found = bob
+        .query()
+        .protocol(definition.protocol)
+        .protocol_path("thread".to_string())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_empty());
+}
@@ -0,0 +1,154 @@
+use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn recipient_definition() -> ProtocolDefinition {
+    let raw_definition = json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [
+                    {
+                        "who": "anyone",
+                        "can": ["create"],
+                    },
+                    {
+                        "who": "recipient",
+                        "can": ["read", "update", "delete"],
+                    },
+                ]
+            }
+        }
+    });
+    serde_json::from_value::<ProtocolDefinition>(raw_definition).unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_recipient() {
+    let (alice, bob, dwn) = init_dwn();
+
+    let definition = recipient_definition();
+    let version = Version::new(1, 2, 3);
+    let path = "my-value".to_string();
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = alice
+        .write()
+        .protocol(definition.protocol.clone(), version.clone(), path.clone())
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .recipient(bob.did.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .expect("record not found");
+    assert_eq!(found.entry().record_id, record_id);
+
+    bob.write()
+        .record_id(record_id.clone())
+        .protocol(definition.protocol.clone(), version.clone(), path.clone())
+        .data(TEXT_PLAIN, "Goodbye, world!".as_bytes().to_vec())
+        .recipient(bob.did.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+
+    let found = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+        .unwrap()
+        .unwrap();
+    assert_ne!(found.initial_entry.data, found.latest_entry.data);
+
+    bob.delete(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_not_recipient() {
+    let (alice, bob, dwn) = init_dwn();
+
+    let definition = recipient_definition();
+    let version = Version::new(1, 2, 3);
+    let path = "my-value".to_string();
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = alice
+        .write()
+        .protocol(definition.protocol.clone(), version.clone(), path.clone())
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .recipient(alice.did.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let res = bob
+        .write()
+        .record_id(record_id.clone())
+        .protocol(definition.protocol.clone(), version.clone(), path.clone())
+        .data(TEXT_PLAIN, "Goodbye, world!".as_bytes().to_vec())
+        .recipient(alice.did.clone())
+        .target(&alice.did)
+        .process()
+        .await;
+    assert!(res.is_err());
+
+    let res = bob
+        .delete(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await;
+    assert!(res.is_err());
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+            .unwrap()
+            .is_some()
+    );
+}
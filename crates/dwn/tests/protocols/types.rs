@@ -0,0 +1,118 @@
+use dwn_core::message::{
+    Version,
+    descriptor::ProtocolDefinition,
+    mime::{APPLICATION_JSON, TEXT_PLAIN},
+};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::{init_dwn, serve_string};
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_type_data_format() {
+    let (alice, ..) = init_dwn();
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "post": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "post": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "post".to_string(),
+        )
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        alice
+            .write()
+            .protocol(definition.protocol, version, "post".to_string())
+            .data(APPLICATION_JSON, json!("Hello").to_string().into_bytes())
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_type_schema() {
+    let (alice, ..) = init_dwn();
+
+    let schema_url = serve_string(json!({ "maxLength": 5 }).to_string()).await;
+    let other_url = serve_string(json!({ "maxLength": 10 }).to_string()).await;
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "post": {
+                "dataFormat": ["application/json"],
+                "schema": schema_url,
+            }
+        },
+        "structure": {
+            "post": {
+                "$actions": [{
+                    "who": "anyone",
+                    "can": ["create"],
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let write = |data: &str| {
+        alice
+            .write()
+            .protocol(
+                definition.protocol.clone(),
+                version.clone(),
+                "post".to_string(),
+            )
+            .data(APPLICATION_JSON, json!(data).to_string().into_bytes())
+    };
+
+    // Schema is taken from the type when omitted.
+    write("foo").process().await.unwrap();
+    assert!(write("foo bar baz").process().await.is_err());
+
+    // Explicit schema must match the type.
+    write("foo").schema(schema_url).process().await.unwrap();
+    assert!(write("foo").schema(other_url).process().await.is_err());
+}
@@ -0,0 +1,55 @@
+use dwn_core::message::{
+    Version,
+    descriptor::{ProtocolDefinition, ProtocolsConfigureBuilder},
+};
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+fn invalid_definition() -> ProtocolDefinition {
+    serde_json::from_value(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "thread": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "thread": {
+                "reply": {}
+            }
+        }
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_actor_rejects_invalid_definition() {
+    let (alice, ..) = init_dwn();
+
+    assert!(
+        alice
+            .configure_protocol(Version::new(1, 0, 0), invalid_definition())
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_dwn_rejects_invalid_definition() {
+    let (alice, _, dwn) = init_dwn();
+
+    let mut msg = ProtocolsConfigureBuilder::new(Version::new(1, 0, 0), invalid_definition())
+        .build()
+        .unwrap();
+    alice.authorize(&mut msg).unwrap();
+
+    let err = dwn.process_message(&alice.did, msg).await.unwrap_err();
+    assert_eq!(err, StatusCode::BAD_REQUEST);
+}
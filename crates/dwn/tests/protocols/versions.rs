@@ -0,0 +1,90 @@
+use dwn_core::message::{Version, descriptor::ProtocolDefinition, mime::TEXT_PLAIN};
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+#[tokio::test]
+#[traced_test]
+async fn test_protocol_versions() {
+    let (alice, _, dwn) = init_dwn();
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "post": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "post": {
+                "$actions": [
+                    {
+                        "who": "anyone",
+                        "can": ["create"],
+                    },
+                    {
+                        "who": "author",
+                        "can": ["update"],
+                    },
+                ]
+            }
+        }
+    }))
+    .unwrap();
+
+    let v1_0 = Version::new(1, 0, 0);
+    let v1_1 = Version::new(1, 1, 0);
+    let v2_0 = Version::new(2, 0, 0);
+
+    for version in [&v1_0, &v1_1, &v2_0] {
+        alice
+            .configure_protocol(version.clone(), definition.clone())
+            .process()
+            .await
+            .unwrap();
+    }
+
+    let found = dwn
+        .record_store
+        .query_protocol(&alice.did, definition.protocol.clone(), Vec::new(), true)
+        .unwrap();
+    let versions = found.into_iter().map(|(v, _)| v).collect::<Vec<_>>();
+    assert_eq!(versions, vec![v1_0.clone(), v1_1.clone(), v2_0.clone()]);
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    // Records can still be written at older versions.
+    let write = |version: &Version| {
+        alice
+            .write()
+            .protocol(
+                definition.protocol.clone(),
+                version.clone(),
+                "post".to_string(),
+            )
+            .data(TEXT_PLAIN, data.clone())
+    };
+
+    let record_id = write(&v1_0).process().await.unwrap();
+
+    // Compatible upgrade.
+    write(&v1_1)
+        .record_id(record_id.clone())
+        .process()
+        .await
+        .unwrap();
+
+    // Downgrade.
+    assert!(
+        write(&v1_0)
+            .record_id(record_id.clone())
+            .process()
+            .await
+            .is_err()
+    );
+
+    // Incompatible upgrade.
+    assert!(write(&v2_0).record_id(record_id).process().await.is_err());
+}
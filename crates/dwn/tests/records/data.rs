@@ -0,0 +1,248 @@
+use std::{convert::Infallible, time::Duration};
+
+use dwn_core::message::{
+    data::{DataChunker, compute_data_cid},
+    mime::{APPLICATION_JSON, APPLICATION_OCTET_STREAM, TEXT_PLAIN},
+};
+use futures_util::stream;
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing_test::traced_test;
+
+use crate::utils::{init_dwn, serve_string};
+
+fn test_data() -> Vec<u8> {
+    (0..1_000_000).map(|i| (i % 251) as u8).collect()
+}
+
+fn chunks(data: &[u8]) -> impl futures_util::Stream<Item = Result<Vec<u8>, Infallible>> + Unpin {
+    stream::iter(
+        data.chunks(64 * 1024)
+            .map(|c| Ok(c.to_vec()))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn single_block(data: &[u8]) -> (String, Vec<u8>) {
+    let mut chunker = DataChunker::default();
+    let mut blocks = chunker.push(data);
+    blocks.extend(chunker.finish());
+    assert_eq!(blocks.len(), 1);
+    blocks.remove(0)
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_upload_read() {
+    let (alice, _, _) = init_dwn();
+
+    let data = test_data();
+
+    let cid = alice.upload(chunks(&data)).process().await.unwrap();
+    assert_eq!(Some(cid.clone()), compute_data_cid(&data));
+
+    let record_id = alice
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .process()
+        .await
+        .unwrap();
+
+    // Data is not inlined.
+    let found = alice
+        .read(record_id.clone())
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(found.data().is_none());
+
+    let read = alice
+        .read(record_id)
+        .process_data()
+        .await
+        .unwrap()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .concat();
+    assert_eq!(read, data);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_read_data_range() {
+    let (alice, _, _) = init_dwn();
+
+    let data = test_data();
+
+    let cid = alice.upload(chunks(&data)).process().await.unwrap();
+
+    let record_id = alice
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .process()
+        .await
+        .unwrap();
+
+    let reader = alice
+        .read(record_id.clone())
+        .range(300_000..500_000)
+        .process_data()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reader.size(), data.len() as u64);
+    assert_eq!(*reader.range(), 300_000..500_000);
+
+    let read = reader.collect::<Result<Vec<_>, _>>().unwrap().concat();
+    assert_eq!(read, data[300_000..500_000]);
+
+    // Range is clamped to the data size.
+    let read = alice
+        .read(record_id)
+        .range(900_000..2_000_000)
+        .process_data()
+        .await
+        .unwrap()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .concat();
+    assert_eq!(read, data[900_000..]);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_data_range_inline() {
+    let (alice, _, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = alice.read(record_id).process().await.unwrap().unwrap();
+    assert_eq!(found.data_range(7..12), Some("world".as_bytes()));
+    assert_eq!(found.data_range(7..100), Some("world!".as_bytes()));
+    assert_eq!(found.data_range(100..200), Some(&[][..]));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_read_data_unauthorized() {
+    let (alice, bob, _) = init_dwn();
+
+    let cid = alice.upload(chunks(&test_data())).process().await.unwrap();
+
+    let record_id = alice
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id)
+        .target(&alice.did)
+        .process_data()
+        .await
+        .unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_delete_removes_blocks() {
+    let (alice, _, dwn) = init_dwn();
+
+    let cid = alice.upload(chunks(&test_data())).process().await.unwrap();
+
+    let record_id = alice
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid.clone())
+        .process()
+        .await
+        .unwrap();
+
+    alice.delete(record_id).process().await.unwrap();
+
+    let found = dwn.data_store.get_block(&alice.did, &cid).unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_write_block_invalid_cid() {
+    let (alice, _, dwn) = init_dwn();
+
+    let cid = compute_data_cid(b"some data").unwrap();
+
+    let res = dwn.write_block(&alice.did, &cid, b"other data".to_vec());
+    assert_eq!(res, Err(StatusCode::BAD_REQUEST));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_write_missing_blocks() {
+    let (alice, _, _) = init_dwn();
+
+    let data = test_data();
+    let cid = compute_data_cid(&data).unwrap();
+
+    let res = alice
+        .write()
+        .data_cid(APPLICATION_OCTET_STREAM, cid)
+        .process()
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_unreferenced_blocks_expire() {
+    let (alice, _, mut dwn) = init_dwn();
+    dwn.block_ttl = Duration::ZERO;
+
+    let (cid, block) = single_block(b"some data");
+    dwn.write_block(&alice.did, &cid, block).unwrap();
+
+    // Uploading another block prunes the expired one.
+    let (other, block) = single_block(b"other data");
+    dwn.write_block(&alice.did, &other, block).unwrap();
+
+    let found = dwn.data_store.get_block(&alice.did, &cid).unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_upload_schema() {
+    let (alice, _, _) = init_dwn();
+
+    let schema_url = serve_string(json!({ "maxLength": 5 }).to_string()).await;
+
+    let valid = json!("foo").to_string().into_bytes();
+    let cid = alice.upload(chunks(&valid)).process().await.unwrap();
+
+    alice
+        .write()
+        .data_cid(APPLICATION_JSON, cid)
+        .schema(schema_url.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let invalid = json!("foo bar").to_string().into_bytes();
+    let cid = alice.upload(chunks(&invalid)).process().await.unwrap();
+
+    let res = alice
+        .write()
+        .data_cid(APPLICATION_JSON, cid)
+        .schema(schema_url)
+        .process()
+        .await;
+    assert!(res.is_err());
+}
@@ -1,4 +1,8 @@
-use dwn_core::message::mime::TEXT_PLAIN;
+use dwn_core::message::{
+    descriptor::{Descriptor, RecordsWriteBuilder},
+    mime::TEXT_PLAIN,
+};
+use reqwest::StatusCode;
 use tracing_test::traced_test;
 
 use crate::utils::init_dwn;
@@ -68,3 +72,59 @@ async fn test_delete_requires_auth() {
     assert_eq!(found.initial_entry.record_id, record_id);
     assert_eq!(found.latest_entry.record_id, record_id);
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_delete_tombstone() {
+    let (actor, other, dwn) = init_dwn();
+
+    let mut msg = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("Hello, world!".as_bytes().to_vec()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    actor.authorize(&mut msg).unwrap();
+    let record_id = msg.record_id.clone();
+
+    dwn.process_message(&actor.did, msg.clone()).await.unwrap();
+
+    actor.delete(record_id.clone()).process().await.unwrap();
+
+    let tombstone = dwn
+        .record_store
+        .read_tombstone(&actor.did, &record_id)
+        .unwrap()
+        .expect("tombstone not found");
+    let Descriptor::RecordsDelete(desc) = &tombstone.descriptor else {
+        panic!("invalid tombstone: {tombstone:?}");
+    };
+    assert_eq!(desc.record_id, record_id);
+
+    // Replaying the write does not bring the record back.
+    let err = dwn.process_message(&actor.did, msg).await.unwrap_err();
+    assert_eq!(err, StatusCode::CONFLICT);
+
+    // Other writers are rejected as if the record never existed,
+    // so cannot probe for the tombstone.
+    let mut probe = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("Hello, world!".as_bytes().to_vec()),
+        record_id: Some(record_id.clone()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    other.authorize(&mut probe).unwrap();
+
+    let err = dwn.process_message(&actor.did, probe).await.unwrap_err();
+    assert_eq!(err, StatusCode::BAD_REQUEST);
+
+    assert!(
+        dwn.record_store
+            .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+            .unwrap()
+            .is_none()
+    );
+}
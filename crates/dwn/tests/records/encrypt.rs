@@ -0,0 +1,242 @@
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use dwn_core::message::{
+    data::{Data, compute_data_cid},
+    descriptor::{Descriptor, RecordsWriteBuilder},
+    mime::TEXT_PLAIN,
+};
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+#[tokio::test]
+#[traced_test]
+async fn test_encrypt() {
+    let (actor, _, dwn) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .encrypt(true)
+        .process()
+        .await
+        .unwrap();
+
+    // Stored data is encrypted.
+    let found = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+        .unwrap()
+        .unwrap();
+
+    let Some(Data::Encrypted(jwe)) = &found.latest_entry.data else {
+        panic!("data not encrypted: {:?}", found.latest_entry.data);
+    };
+
+    let Descriptor::RecordsWrite(desc) = &found.latest_entry.descriptor else {
+        panic!("invalid descriptor");
+    };
+
+    let ciphertext = BASE64_URL_SAFE_NO_PAD.decode(&jwe.ciphertext).unwrap();
+    assert_ne!(ciphertext, data);
+    assert_eq!(desc.data_cid, compute_data_cid(&ciphertext));
+
+    // Data is decrypted when read.
+    let found = actor.read(record_id).process().await.unwrap().unwrap();
+    assert_eq!(found.data(), Some(data.as_slice()));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_encrypt_not_recipient() {
+    let (alice, bob, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .encrypt(true)
+        .published(true)
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.entry().record_id, record_id);
+    assert!(found.data().is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_encrypt_requires_key() {
+    let (mut actor, ..) = init_dwn();
+    actor.encryption_key = None;
+
+    assert!(
+        actor
+            .write()
+            .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+            .encrypt(true)
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_encrypt_for() {
+    let (alice, bob, _) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .encrypt_for(bob.did.clone())
+        .published(true)
+        .process()
+        .await
+        .unwrap();
+
+    let found = alice
+        .read(record_id.clone())
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.data(), Some(data.as_slice()));
+
+    let found = bob
+        .read(record_id)
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.data(), Some(data.as_slice()));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_share() {
+    let (alice, bob, _) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .encrypt(true)
+        .published(true)
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(found.data().is_none());
+
+    let Descriptor::RecordsWrite(initial) = &found.entry().descriptor else {
+        panic!("invalid descriptor");
+    };
+    let data_cid = initial.data_cid.clone();
+
+    alice
+        .share(record_id.clone(), bob.did.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let found = bob
+        .read(record_id)
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.data(), Some(data.as_slice()));
+
+    // Data is not re-encrypted.
+    let Descriptor::RecordsWrite(desc) = &found.entry().descriptor else {
+        panic!("invalid descriptor");
+    };
+    assert_eq!(desc.data_cid, data_cid);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_share_unencrypted() {
+    let (alice, bob, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(
+        alice
+            .share(record_id, bob.did.clone())
+            .process()
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_shared_ciphertext_envelope() {
+    let (alice, _, dwn) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .encrypt(true)
+        .process()
+        .await
+        .unwrap();
+
+    let found = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+        .unwrap()
+        .unwrap();
+
+    let Some(Data::Encrypted(mut jwe)) = found.latest_entry.data else {
+        panic!("data not encrypted");
+    };
+
+    // Write another record with the same ciphertext, but a different envelope.
+    jwe.recipients.clear();
+    jwe.iv = BASE64_URL_SAFE_NO_PAD.encode([0; 12]);
+
+    let mut msg = RecordsWriteBuilder {
+        encrypted_data: Some(jwe),
+        data_format: Some(TEXT_PLAIN),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    alice.authorize(&mut msg).unwrap();
+
+    dwn.process_message(&alice.did, msg).await.unwrap();
+
+    // The original record is unaffected.
+    let found = alice.read(record_id).process().await.unwrap().unwrap();
+    assert_eq!(found.data(), Some(data.as_slice()));
+}
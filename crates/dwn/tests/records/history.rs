@@ -0,0 +1,210 @@
+use dwn_core::message::{descriptor::Descriptor, mime::TEXT_PLAIN};
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+#[tokio::test]
+#[traced_test]
+async fn test_history() {
+    let (alice, _, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "v1".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    for data in ["v2", "v3"] {
+        alice
+            .write()
+            .record_id(record_id.clone())
+            .data(TEXT_PLAIN, data.as_bytes().to_vec())
+            .process()
+            .await
+            .unwrap();
+    }
+
+    let history = alice
+        .read(record_id.clone())
+        .process_history()
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history.iter().all(|e| e.data.is_none()));
+    assert!(history.is_sorted_by_key(|e| *e.descriptor.message_timestamp().unwrap()));
+    assert_eq!(history[0].descriptor.compute_entry_id().unwrap(), record_id);
+
+    // Past entries can be read with their data.
+    let entry_id = history[1].descriptor.compute_entry_id().unwrap();
+    let found = alice
+        .read(record_id.clone())
+        .entry_id(entry_id)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.data(), Some("v2".as_bytes()));
+
+    let found = alice.read(record_id).process().await.unwrap().unwrap();
+    assert_eq!(found.data(), Some("v3".as_bytes()));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_history_retention() {
+    let (mut alice, _, dwn) = init_dwn();
+    alice.dwn.max_history = Some(1);
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "v1".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    for data in ["v2", "v3", "v4"] {
+        alice
+            .write()
+            .record_id(record_id.clone())
+            .data(TEXT_PLAIN, data.as_bytes().to_vec())
+            .process()
+            .await
+            .unwrap();
+    }
+
+    // Only the latest entry and one past entry are kept.
+    let history = alice
+        .read(record_id.clone())
+        .process_history()
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+
+    let found = alice
+        .read(record_id.clone())
+        .entry_id(record_id.clone())
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    // Pruned data is removed.
+    let initial = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &alice.did, &record_id)
+        .unwrap()
+        .unwrap()
+        .initial_entry;
+    let Descriptor::RecordsWrite(desc) = initial.descriptor else {
+        panic!("invalid descriptor");
+    };
+    let data_cid = desc.data_cid.unwrap();
+    assert!(
+        dwn.data_store
+            .read(&alice.did, &data_cid)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_history_unauthorized() {
+    let (alice, bob, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "v1".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let history = bob
+        .read(record_id)
+        .target(&alice.did)
+        .process_history()
+        .await
+        .unwrap();
+    assert!(history.is_empty());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_history_private_entries() {
+    let (alice, bob, _) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "v1".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .record_id(record_id.clone())
+        .data(TEXT_PLAIN, "v2".as_bytes().to_vec())
+        .published(true)
+        .process()
+        .await
+        .unwrap();
+
+    // Only the published entry is returned.
+    let history = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .process_history()
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_ne!(history[0].descriptor.compute_entry_id().unwrap(), record_id);
+
+    // The private initial entry cannot be read by id.
+    let found = bob
+        .read(record_id.clone())
+        .target(&alice.did)
+        .entry_id(record_id.clone())
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let found = bob
+        .read(record_id)
+        .target(&alice.did)
+        .process()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.data(), Some("v2".as_bytes()));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_delete_history() {
+    let (alice, _, dwn) = init_dwn();
+
+    let record_id = alice
+        .write()
+        .data(TEXT_PLAIN, "v1".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    alice
+        .write()
+        .record_id(record_id.clone())
+        .data(TEXT_PLAIN, "v2".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    alice.delete(record_id.clone()).process().await.unwrap();
+
+    let history = dwn
+        .record_store
+        .read_history(&alice.did, &record_id)
+        .unwrap();
+    assert!(history.is_empty());
+}
@@ -1,4 +1,8 @@
+mod data;
 mod delete;
+mod encrypt;
+mod history;
 mod query;
 mod read;
+mod subscribe;
 mod write;
@@ -1,9 +1,14 @@
 use dwn_core::{
-    message::descriptor::{
-        DateFilter, DateSort, RecordFilter, RecordsQueryBuilder, RecordsWriteBuilder,
+    message::{
+        descriptor::{
+            DateFilter, DateSort, Pagination, RecordFilter, RecordsQueryBuilder,
+            RecordsWriteBuilder, TagFilter, TagRange, TagValue,
+        },
+        mime::TEXT_PLAIN,
     },
     reply::Reply,
 };
+use futures_util::TryStreamExt;
 use tracing_test::traced_test;
 
 use crate::utils::init_dwn;
@@ -72,6 +77,51 @@ async fn test_query_record_id() {
             record_id: Some(msg_1.record_id.clone()),
             ..Default::default()
         },
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+
+    let reply = match dwn.process_message(&actor.did, query).await.unwrap() {
+        Some(Reply::RecordsQuery(v)) => v,
+        _ => panic!("invalid reply"),
+    };
+    assert_eq!(reply.entries.len(), 1);
+    assert_eq!(reply.entries[0], msg_1);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_recipient() {
+    let (actor, bob, dwn) = init_dwn();
+
+    let msg_1 = RecordsWriteBuilder {
+        published: Some(true),
+        recipient: Some(bob.did.clone()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    dwn.record_store
+        .write(dwn.data_store.as_ref(), &actor.did, msg_1.clone())
+        .unwrap();
+
+    let msg_2 = RecordsWriteBuilder {
+        published: Some(true),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    dwn.record_store
+        .write(dwn.data_store.as_ref(), &actor.did, msg_2)
+        .unwrap();
+
+    let query = RecordsQueryBuilder {
+        filter: RecordFilter {
+            recipient: Some(bob.did.clone()),
+            ..Default::default()
+        },
+        ..Default::default()
     }
     .build()
     .unwrap();
@@ -137,6 +187,7 @@ async fn test_query_date_filter() {
             }),
             ..Default::default()
         },
+        ..Default::default()
     }
     .build()
     .unwrap();
@@ -150,6 +201,77 @@ async fn test_query_date_filter() {
     assert_eq!(reply.entries[1], msg_2);
 }
 
+#[tokio::test]
+#[traced_test]
+async fn test_query_date_filter_updated() {
+    let (actor, _, dwn) = init_dwn();
+
+    let msg_1 = RecordsWriteBuilder {
+        published: Some(true),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    dwn.record_store
+        .write(dwn.data_store.as_ref(), &actor.did, msg_1.clone())
+        .unwrap();
+
+    let msg_2 = RecordsWriteBuilder {
+        published: Some(true),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    dwn.record_store
+        .write(dwn.data_store.as_ref(), &actor.did, msg_2.clone())
+        .unwrap();
+
+    let update = RecordsWriteBuilder {
+        record_id: Some(msg_1.record_id.clone()),
+        published: Some(true),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    dwn.record_store
+        .write(dwn.data_store.as_ref(), &actor.did, update.clone())
+        .unwrap();
+
+    // Updated records keep their creation date, for both sorting and filtering.
+    let query = RecordsQueryBuilder {
+        filter: RecordFilter {
+            date_sort: Some(DateSort::Descending),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    let reply = match dwn.process_message(&actor.did, query).await.unwrap() {
+        Some(Reply::RecordsQuery(v)) => v,
+        _ => panic!("invalid reply"),
+    };
+    assert_eq!(reply.entries, vec![msg_2.clone(), update.clone()]);
+
+    let query = RecordsQueryBuilder {
+        filter: RecordFilter {
+            date_created: Some(DateFilter {
+                from: *msg_2.descriptor.message_timestamp().unwrap(),
+                to: *update.descriptor.message_timestamp().unwrap(),
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    let reply = match dwn.process_message(&actor.did, query).await.unwrap() {
+        Some(Reply::RecordsQuery(v)) => v,
+        _ => panic!("invalid reply"),
+    };
+    assert_eq!(reply.entries, vec![msg_2]);
+}
+
 #[tokio::test]
 #[traced_test]
 async fn test_query_date_sort() {
@@ -180,6 +302,7 @@ async fn test_query_date_sort() {
             date_sort: Some(DateSort::Descending),
             ..Default::default()
         },
+        ..Default::default()
     }
     .build()
     .unwrap();
@@ -196,6 +319,7 @@ async fn test_query_date_sort() {
             date_sort: Some(DateSort::Ascending),
             ..Default::default()
         },
+        ..Default::default()
     }
     .build()
     .unwrap();
@@ -207,3 +331,313 @@ async fn test_query_date_sort() {
     assert_eq!(reply.entries[0], msg_1);
     assert_eq!(reply.entries[1], msg_2);
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_tags() {
+    let (actor, ..) = init_dwn();
+
+    let data = "Hello, world!".as_bytes().to_vec();
+
+    let draft_id = actor
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .tag("status".to_string(), TagValue::String("draft".to_string()))
+        .tag("priority".to_string(), TagValue::Number(1.into()))
+        .process()
+        .await
+        .unwrap();
+
+    let done_id = actor
+        .write()
+        .data(TEXT_PLAIN, data.clone())
+        .tag("status".to_string(), TagValue::String("done".to_string()))
+        .tag("priority".to_string(), TagValue::Number(5.into()))
+        .process()
+        .await
+        .unwrap();
+
+    let found = actor
+        .query()
+        .tag(
+            "status".to_string(),
+            TagFilter::Equal(TagValue::String("draft".to_string())),
+        )
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, draft_id);
+
+    let found = actor
+        .query()
+        .tag(
+            "status".to_string(),
+            TagFilter::StartsWith {
+                starts_with: "d".to_string(),
+            },
+        )
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 2);
+
+    let found = actor
+        .query()
+        .tag(
+            "priority".to_string(),
+            TagFilter::Range(TagRange {
+                gt: Some(2.into()),
+                ..Default::default()
+            }),
+        )
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, done_id);
+
+    // Updating a record replaces its indexed tags.
+    actor
+        .write()
+        .record_id(draft_id.clone())
+        .data(TEXT_PLAIN, data)
+        .tag("status".to_string(), TagValue::String("done".to_string()))
+        .process()
+        .await
+        .unwrap();
+
+    let found = actor
+        .query()
+        .tag(
+            "status".to_string(),
+            TagFilter::Equal(TagValue::String("draft".to_string())),
+        )
+        .process()
+        .await
+        .unwrap();
+    assert!(found.is_empty());
+
+    let found = actor
+        .query()
+        .tag(
+            "status".to_string(),
+            TagFilter::Equal(TagValue::String("done".to_string())),
+        )
+        .tag(
+            "priority".to_string(),
+            TagFilter::Equal(TagValue::Number(5.into())),
+        )
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].entry().record_id, done_id);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_pagination_update() {
+    let (actor, _, dwn) = init_dwn();
+
+    let mut record_ids = Vec::new();
+
+    for i in 0..4 {
+        let record_id = actor
+            .write()
+            .data(TEXT_PLAIN, format!("record {i}").into_bytes())
+            .process()
+            .await
+            .unwrap();
+        record_ids.push(record_id);
+    }
+
+    let query = |cursor: Option<String>| {
+        let mut msg = RecordsQueryBuilder {
+            pagination: Some(Pagination {
+                limit: Some(2),
+                cursor,
+            }),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        actor.authorize(&mut msg).unwrap();
+        msg
+    };
+
+    let first = match dwn.process_message(&actor.did, query(None)).await.unwrap() {
+        Some(Reply::RecordsQuery(v)) => v,
+        _ => panic!("invalid reply"),
+    };
+    assert_eq!(first.entries[0].record_id, record_ids[3]);
+    assert_eq!(first.entries[1].record_id, record_ids[2]);
+
+    // Updating a record does not move it across the cursor.
+    actor
+        .write()
+        .record_id(record_ids[0].clone())
+        .data(TEXT_PLAIN, "updated".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let second = match dwn
+        .process_message(&actor.did, query(first.cursor))
+        .await
+        .unwrap()
+    {
+        Some(Reply::RecordsQuery(v)) => v,
+        _ => panic!("invalid reply"),
+    };
+    assert_eq!(second.entries.len(), 2);
+    assert_eq!(second.entries[0].record_id, record_ids[1]);
+    assert_eq!(second.entries[1].record_id, record_ids[0]);
+    assert!(second.cursor.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_tag_number_range() {
+    let (actor, ..) = init_dwn();
+
+    let mut record_ids = Vec::new();
+
+    for n in [-250.5, -3.0, 0.0, 2.5, 10.0, 1e12] {
+        let record_id = actor
+            .write()
+            .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+            .tag(
+                "n".to_string(),
+                TagValue::Number(serde_json::Number::from_f64(n).unwrap()),
+            )
+            .process()
+            .await
+            .unwrap();
+        record_ids.push(record_id);
+    }
+
+    let query = |range: TagRange| {
+        let actor = &actor;
+        async move {
+            let mut found = actor
+                .query()
+                .tag("n".to_string(), TagFilter::Range(range))
+                .process()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|r| r.entry().record_id.clone())
+                .collect::<Vec<_>>();
+            found.sort();
+            found
+        }
+    };
+
+    let expected = |ids: &[usize]| {
+        let mut ids = ids
+            .iter()
+            .map(|i| record_ids[*i].clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(
+        query(TagRange {
+            gte: Some((-3).into()),
+            lt: Some(10.into()),
+            ..Default::default()
+        })
+        .await,
+        expected(&[1, 2, 3])
+    );
+    assert_eq!(
+        query(TagRange {
+            lt: Some(0.into()),
+            ..Default::default()
+        })
+        .await,
+        expected(&[0, 1])
+    );
+    assert_eq!(
+        query(TagRange {
+            gt: Some(2.into()),
+            ..Default::default()
+        })
+        .await,
+        expected(&[3, 4, 5])
+    );
+    assert_eq!(
+        query(TagRange {
+            gt: Some(10.into()),
+            lt: Some(10.into()),
+            ..Default::default()
+        })
+        .await,
+        expected(&[])
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_query_pagination() {
+    let (actor, ..) = init_dwn();
+
+    let mut record_ids = Vec::new();
+
+    for i in 0..5 {
+        let record_id = actor
+            .write()
+            .data(TEXT_PLAIN, format!("record {i}").into_bytes())
+            .process()
+            .await
+            .unwrap();
+        record_ids.push(record_id);
+    }
+
+    let first = actor
+        .query()
+        .date_sort(DateSort::Ascending)
+        .limit(2)
+        .process()
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0].entry().record_id, record_ids[0]);
+    assert_eq!(first[1].entry().record_id, record_ids[1]);
+
+    // Pages stay consistent as new records are written.
+    let newer = actor
+        .write()
+        .data(TEXT_PLAIN, "newer".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+    record_ids.push(newer);
+
+    let streamed = actor
+        .query()
+        .date_sort(DateSort::Ascending)
+        .limit(2)
+        .stream()
+        .map_ok(|r| r.entry().record_id.clone())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(streamed, record_ids);
+
+    let streamed = actor
+        .query()
+        .limit(4)
+        .stream()
+        .map_ok(|r| r.entry().record_id.clone())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    record_ids.reverse();
+    assert_eq!(streamed, record_ids);
+
+    let res = actor.query().cursor("invalid".to_string()).process().await;
+    assert!(res.is_err());
+}
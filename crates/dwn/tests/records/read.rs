@@ -88,7 +88,8 @@ async fn test_read_unpublished_requires_auth() {
     assert_eq!(
         dwn.process_message(&actor.did, read).await.unwrap(),
         Some(Reply::RecordsRead(Box::new(RecordsReadReply {
-            entry: None
+            entry: None,
+            history: Vec::new(),
         })))
     )
 }
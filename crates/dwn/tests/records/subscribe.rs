@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use dwn::RecordSubscription;
+use dwn_core::message::{
+    Message, Version,
+    descriptor::{Descriptor, ProtocolDefinition, RecordsWriteBuilder},
+    mime::TEXT_PLAIN,
+};
+use serde_json::json;
+use tokio::time::timeout;
+use tracing_test::traced_test;
+
+use crate::utils::init_dwn;
+
+async fn next(subscription: &mut RecordSubscription) -> Option<Message> {
+    timeout(Duration::from_millis(500), subscription.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_subscribe_write() {
+    let (actor, ..) = init_dwn();
+
+    let mut subscription = actor.subscribe().process().await.unwrap();
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let found = next(&mut subscription).await.unwrap();
+    assert_eq!(found.record_id, record_id);
+    assert!(matches!(found.descriptor, Descriptor::RecordsWrite(_)));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_subscribe_delete() {
+    let (actor, ..) = init_dwn();
+
+    let record_id = actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let mut subscription = actor
+        .subscribe()
+        .record_id(record_id.clone())
+        .process()
+        .await
+        .unwrap();
+
+    actor.delete(record_id.clone()).process().await.unwrap();
+
+    let found = next(&mut subscription).await.unwrap();
+    let Descriptor::RecordsDelete(desc) = found.descriptor else {
+        panic!("invalid descriptor: {:?}", found.descriptor);
+    };
+    assert_eq!(desc.record_id, record_id);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_subscribe_filter() {
+    let (actor, ..) = init_dwn();
+
+    let mut subscription = actor
+        .subscribe()
+        .schema("my-schema".to_string())
+        .process()
+        .await
+        .unwrap();
+
+    actor
+        .write()
+        .data(TEXT_PLAIN, "Hello, world!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    assert!(next(&mut subscription).await.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_subscribe_unpublished_requires_auth() {
+    let (actor, _, dwn) = init_dwn();
+
+    let mut subscription = actor.subscribe().auth(false).process().await.unwrap();
+
+    let mut unpublished = RecordsWriteBuilder::default().build().unwrap();
+    actor.authorize(&mut unpublished).unwrap();
+    dwn.process_message(&actor.did, unpublished).await.unwrap();
+
+    let mut published = RecordsWriteBuilder {
+        published: Some(true),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    actor.authorize(&mut published).unwrap();
+    dwn.process_message(&actor.did, published.clone())
+        .await
+        .unwrap();
+
+    let found = next(&mut subscription).await.unwrap();
+    assert_eq!(found.record_id, published.record_id);
+    assert!(next(&mut subscription).await.is_none());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn test_subscribe_protocol_visibility() {
+    let (alice, bob, _) = init_dwn();
+
+    let definition = serde_json::from_value::<ProtocolDefinition>(json!({
+        "protocol": "my-protocol",
+        "published": true,
+        "types": {
+            "my-value": {
+                "dataFormat": ["text/plain"],
+            }
+        },
+        "structure": {
+            "my-value": {
+                "$actions": [
+                    {
+                        "who": "anyone",
+                        "can": ["create"],
+                    },
+                    {
+                        "who": "recipient",
+                        "can": ["query"],
+                    },
+                ]
+            }
+        }
+    }))
+    .unwrap();
+    let version = Version::new(1, 0, 0);
+
+    alice
+        .configure_protocol(version.clone(), definition.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let mut subscription = bob.subscribe().target(&alice.did).process().await.unwrap();
+
+    // Only records Bob may query are sent to him.
+    alice
+        .write()
+        .protocol(
+            definition.protocol.clone(),
+            version.clone(),
+            "my-value".to_string(),
+        )
+        .data(TEXT_PLAIN, "Hello, Carol!".as_bytes().to_vec())
+        .process()
+        .await
+        .unwrap();
+
+    let record_id = alice
+        .write()
+        .protocol(definition.protocol.clone(), version, "my-value".to_string())
+        .data(TEXT_PLAIN, "Hello, Bob!".as_bytes().to_vec())
+        .recipient(bob.did.clone())
+        .process()
+        .await
+        .unwrap();
+
+    let found = next(&mut subscription).await.unwrap();
+    assert_eq!(found.record_id, record_id);
+    assert!(next(&mut subscription).await.is_none());
+
+    alice.delete(record_id.clone()).process().await.unwrap();
+
+    let found = next(&mut subscription).await.unwrap();
+    let Descriptor::RecordsDelete(desc) = found.descriptor else {
+        panic!("invalid descriptor: {:?}", found.descriptor);
+    };
+    assert_eq!(desc.record_id, record_id);
+}
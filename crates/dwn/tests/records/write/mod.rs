@@ -1,5 +1,6 @@
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use dwn::Dwn;
-use dwn_core::message::{Message, descriptor::RecordsWriteBuilder, mime::TEXT_PLAIN};
+use dwn_core::message::{Message, data::Data, descriptor::RecordsWriteBuilder, mime::TEXT_PLAIN};
 use tracing_test::traced_test;
 use xdid::core::did::Did;
 
@@ -65,6 +66,26 @@ async fn test_write_invalid_record_id() {
     expect_fail(&actor.did, &mut dwn, msg).await;
 }
 
+#[tokio::test]
+#[traced_test]
+async fn test_write_data_cid_mismatch() {
+    let (actor, _, mut dwn) = init_dwn();
+
+    let mut msg = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("Hello, world!".as_bytes().to_owned()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    msg.data = Some(Data::Base64(
+        BASE64_URL_SAFE_NO_PAD.encode("Goodbye, world!".as_bytes()),
+    ));
+    actor.authorize(&mut msg).unwrap();
+
+    expect_fail(&actor.did, &mut dwn, msg).await;
+}
+
 async fn expect_success(target: &Did, dwn: &mut Dwn, msg: Message) {
     let record_id = msg.record_id.clone();
 
@@ -49,3 +49,42 @@ async fn test_update_data() {
 
     expect_success(&actor.did, &mut dwn, msg_3).await;
 }
+
+#[tokio::test]
+#[traced_test]
+async fn test_update_recipient_immutable() {
+    let (actor, bob, mut dwn) = init_dwn();
+
+    let mut msg_1 = RecordsWriteBuilder {
+        data_format: Some(TEXT_PLAIN),
+        data: Some("hello, world!".as_bytes().to_owned()),
+        recipient: Some(bob.did.clone()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    actor.authorize(&mut msg_1).unwrap();
+
+    let record_id = msg_1.record_id.clone();
+    expect_success(&actor.did, &mut dwn, msg_1.clone()).await;
+
+    let mut msg_2 = RecordsWriteBuilder {
+        record_id: Some(record_id.clone()),
+        data_format: Some(TEXT_PLAIN),
+        data: Some("goodbye".as_bytes().to_owned()),
+        recipient: Some(actor.did.clone()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+    actor.authorize(&mut msg_2).unwrap();
+
+    assert!(dwn.process_message(&actor.did, msg_2).await.is_err());
+
+    let found = dwn
+        .record_store
+        .read(dwn.data_store.as_ref(), &actor.did, &record_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.latest_entry, msg_1);
+}
@@ -16,6 +16,7 @@ pub fn init_dwn() -> (Actor, Actor, Dwn) {
         let did = key.public().to_did();
 
         let mut alice = Actor::new(did, dwn.clone());
+        alice.encryption_key = Some(Arc::new(key.clone().into()));
 
         let key = Arc::<DocumentKey>::new(key.into());
         alice.auth_key = Some(key.clone());
@@ -29,6 +30,7 @@ pub fn init_dwn() -> (Actor, Actor, Dwn) {
         let did = key.public().to_did();
 
         let mut bob = Actor::new(did, dwn.clone());
+        bob.encryption_key = Some(Arc::new(key.clone().into()));
 
         let key = Arc::<DocumentKey>::new(key.into());
         bob.auth_key = Some(key.clone());